@@ -15,6 +15,12 @@ export const PromptAPI = {
   create: (name, content, tags) => call("create_prompt", { name, content, tags }),
   update: (id, name, content, tags) => call("update_prompt", { id, name, content, tags }),
   delete: (id) => call("delete_prompt", { id }),
+  listRevisions: (id) => call("list_prompt_revisions", { id }),
+  diffRevisions: (id, fromRevision, toRevision) =>
+    call("diff_prompt_revisions", { id, fromRevision, toRevision }),
+  revert: (id, revisionId) => call("revert_prompt", { id, revisionId }),
+  render: (id, clientId) => call("render_prompt", { id, clientId }),
+  search: (query, limit) => call("search_prompts", { query, limit }),
   exportPrompts: () => call("export_prompts"),
   importPrompts: (jsonData) => call("import_prompts", { jsonData }),
 };
@@ -41,6 +47,16 @@ export const ClientAPI = {
     }
     return call("import_clients", params);
   },
+  listConfigFiles: (id) => call("list_client_config_files", { id }),
+  getCatalog: () => call("get_client_catalog"),
+  discover: (projectRoot = null) => {
+    const params = {};
+    if (projectRoot !== null && projectRoot !== undefined) {
+      params.projectRoot = projectRoot;
+    }
+    return call("discover_clients", params);
+  },
+  acceptDiscovered: (clients) => call("accept_discovered_clients", { clients }),
 };
 
 export const ConfigFileAPI = {
@@ -58,6 +74,51 @@ export const ConfigFileAPI = {
     }
     return call("write_config_file", params);
   },
+  readValue: (clientId, keyPath = [], configPath = null) => {
+    const params = { clientId, keyPath };
+    if (configPath !== null && configPath !== undefined) {
+      params.configPath = configPath;
+    }
+    return call("read_config_value", params);
+  },
+  validate: (clientId, content = null, configPath = null) => {
+    const params = { clientId };
+    if (content !== null && content !== undefined) {
+      params.content = content;
+    }
+    if (configPath !== null && configPath !== undefined) {
+      params.configPath = configPath;
+    }
+    return call("validate_config_file", params);
+  },
+  patch: (clientId, keyPath, value, configPath = null) => {
+    const params = { clientId, keyPath, value };
+    if (configPath !== null && configPath !== undefined) {
+      params.configPath = configPath;
+    }
+    return call("patch_config_file", params);
+  },
+  watchAll: () => call("watch_all_clients"),
+  unwatch: (clientId) => call("unwatch_client_config", { clientId }),
+};
+
+export const CompositionAPI = {
+  getAll: () => call("get_compositions"),
+  create: (name, clientId, configPath, items) =>
+    call("create_composition", { name, clientId, configPath, items }),
+  update: (id, name, clientId, configPath, items) =>
+    call("update_composition", { id, name, clientId, configPath, items }),
+  delete: (id) => call("delete_composition", { id }),
+  preview: (id) => call("preview_composition", { id }),
+  render: (id) => call("render_composition", { id }),
+};
+
+export const VariableAPI = {
+  getAll: () => call("get_variables"),
+  saveAll: (variables) => call("save_variables", { variables }),
+  getForProject: (projectPath) => call("get_project_variables", { projectPath }),
+  setForProject: (projectPath, variables) =>
+    call("set_project_variables", { projectPath, variables }),
 };
 
 export const AppStateAPI = {
@@ -87,13 +148,37 @@ export const SnapshotAPI = {
     });
   },
   getAll: (clientId) => call("get_snapshots", { clientId }),
+  diff: (clientId, fromSnapshotId, toSnapshotId) =>
+    call("diff_snapshots", { clientId, fromSnapshotId, toSnapshotId }),
+  diffWithCurrent: (clientId, snapshotId) =>
+    call("diff_snapshot_with_current", { clientId, snapshotId }),
   restore: (clientId, snapshotId) => call("restore_snapshot", { clientId, snapshotId }),
+  // conflictStyle: "markers" writes git-style conflict markers, "list" writes nothing on conflict
+  restoreMerged: (clientId, snapshotId, conflictStyle = "markers") =>
+    call("restore_snapshot_merged", { clientId, snapshotId, conflictStyle }),
   delete: (clientId, snapshotId) => call("delete_snapshot", { clientId, snapshotId }),
   rename: (clientId, snapshotId, newName) =>
     call("rename_snapshot", { clientId, snapshotId, newName }),
+  pin: (clientId, snapshotId) => call("pin_snapshot", { clientId, snapshotId }),
+  unpin: (clientId, snapshotId) => call("unpin_snapshot", { clientId, snapshotId }),
+  setNotes: (clientId, snapshotId, notes) =>
+    call("set_snapshot_notes", { clientId, snapshotId, notes }),
+  setLabels: (clientId, snapshotId, labels) =>
+    call("set_snapshot_labels", { clientId, snapshotId, labels }),
+  // query: { client_id, labels, text, regex, from, to, limit }; every field is optional
+  search: (query = {}) => call("search_snapshots", { query }),
+  // snapshotIds omitted: full history including limits and retention policy
+  exportArchive: (clientId, snapshotIds = null) =>
+    call("export_snapshot_archive", { clientId, snapshotIds }),
+  importArchive: (jsonData, targetClientId = null) =>
+    call("import_snapshot_archive", { jsonData, targetClientId }),
   setMaxSnapshots: (clientId, max) => call("set_max_snapshots", { clientId, max }),
   setMaxAutoSnapshots: (clientId, max) => call("set_max_auto_snapshots", { clientId, max }),
   setMaxManualSnapshots: (clientId, max) => call("set_max_manual_snapshots", { clientId, max }),
+  // policy: { keep_all_hours, hourly_hours, daily_days, weekly_weeks, include_manual } or null for count limits only
+  setRetention: (clientId, policy) => call("set_snapshot_retention", { clientId, policy }),
+  setAutoLabels: (autoPrefix, beforeQuitPrefix) =>
+    call("set_auto_snapshot_labels", { autoPrefix, beforeQuitPrefix }),
   refreshTrayMenu: () => call("refresh_tray_menu"),
   refreshAppMenu: () => call("refresh_app_menu"),
 };
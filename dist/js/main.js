@@ -54,10 +54,8 @@ const state = {
   snapshotRestoredUnlisten: null,
   windowBehaviorUnlisten: null,
   menuSettingsUnlisten: null,
-  menuQuitUnlisten: null,
   menuSnapshotCreateUnlisten: null,
   menuSnapshotManageUnlisten: null,
-  isSavingInternally: false,
   windowBehavior: { ...DEFAULT_WINDOW_BEHAVIOR },
   userHomeDir: null,
   userHomeDirFetched: false,
@@ -283,6 +281,16 @@ const formatSnapshotName = (prefix) => {
   return `${label} ${formatted}`;
 };
 
+// 后端自动快照按当前界面语言命名
+const syncAutoSnapshotLabels = () => {
+  SnapshotAPI.setAutoLabels(
+    t("snapshots.autoPrefix", "Auto Snapshot"),
+    t("snapshots.beforeQuitPrefix", "Before Quit")
+  ).catch((error) => {
+    console.warn("[Snapshot] 同步自动快照名称失败:", error);
+  });
+};
+
 const createAutoSnapshot = async (clientId, prefix = null) => {
   if (!clientId) {
     throw new Error(t("errors.missingClientId", "Missing client ID, cannot create snapshot"));
@@ -295,7 +303,7 @@ const createAutoSnapshot = async (clientId, prefix = null) => {
     console.log(`[Snapshot] 已创建快照: ${name} (客户端: ${clientId})`);
     return { success: true, name };
   } catch (error) {
-    if (error && typeof error === "string" && error.includes("内容未变化")) {
+    if (error?.code === "snapshot.unchanged") {
       console.log(`[Snapshot] 内容未变化,跳过快照: ${prefix} (客户端: ${clientId})`);
       return { success: true, skipped: true, reason: "no_changes" };
     }
@@ -1548,8 +1556,10 @@ const initApp = async () => {
     console.error("[i18n] Initialization failed:", error);
   }
   applyTranslations(document);
+  syncAutoSnapshotLabels();
   onLanguageChange(() => {
     applyTranslations(document);
+    syncAutoSnapshotLabels();
     updateConfigFileName();
     renderClientDropdown();
     renderTagFilter();
@@ -1619,7 +1629,7 @@ const initApp = async () => {
     await listenToFileChanges();
     await listenToSnapshotRestored();
     await listenToMenuEvents();
-    await startFileWatcher(state.currentClientId);
+    await startAllFileWatchers();
   } catch (error) {
     showToast(getErrorMessage(error) || t("toast.initFailed", "Initialization failed"), "error");
   }
@@ -1631,8 +1641,24 @@ const loadClients = async () => {
   if (!state.clients.length) {
     throw new Error(t("errors.noClientsConfigured", "No clients configured"));
   }
+  await Promise.all(state.clients.map(attachConfigFiles));
 };
 
+const isPathPattern = (path) => typeof path === "string" && /[*?]|[\\/]$/.test(path);
+
+// 通配模式与目录展开为当前匹配的文件，供配置文件下拉框使用
+const attachConfigFiles = async (client) => {
+  if (!client.config_file_paths?.some(isPathPattern)) return;
+  try {
+    client.config_files = await ClientAPI.listConfigFiles(client.id);
+  } catch (error) {
+    console.warn(`[LoadClients] Failed to expand config paths for ${client.id}:`, error);
+  }
+};
+
+const getClientConfigFiles = (client) =>
+  client?.config_files ?? (client?.config_file_paths ?? []).filter((path) => !isPathPattern(path));
+
 const hydrateAppState = async () => {
   try {
     const appState = await AppStateAPI.get();
@@ -1828,6 +1854,16 @@ const startFileWatcher = async (clientId) => {
   }
 };
 
+const startAllFileWatchers = async () => {
+  try {
+    const watched = await ConfigFileAPI.watchAll();
+    console.log(`[FileWatcher] Started watching ${watched.length} client(s)`);
+  } catch (error) {
+    console.warn("[FileWatcher] Failed to watch all clients:", error);
+  }
+  await startFileWatcher(state.currentClientId);
+};
+
 const stopFileWatcher = async () => {
   const invoke = window.__TAURI_INTERNALS__?.invoke;
   if (typeof invoke !== "function") {
@@ -1909,20 +1945,29 @@ const ensureUserHomeDir = async () => {
   return state.userHomeDir;
 };
 
-const buildConfigUpdatedToastMessage = async (changedPathsInput) => {
+const CONFIG_CHANGE_TOAST_KEYS = {
+  modified: ["toast.configUpdated", "Config file updated", "toast.configUpdatedWithFile", "{file} updated"],
+  created: ["toast.configCreated", "Config file created", "toast.configCreatedWithFile", "{file} created"],
+  deleted: ["toast.configDeleted", "Config file deleted", "toast.configDeletedWithFile", "{file} deleted"],
+  renamed: ["toast.configRenamed", "Config file moved", "toast.configRenamedWithFile", "{file} moved"],
+};
+
+const buildConfigUpdatedToastMessage = async (changedPathsInput, kind = "modified") => {
   const normalizedPaths = normalizeChangedPaths(changedPathsInput);
-  const defaultMessage = t("toast.configUpdated", "Config file updated");
+  const [defaultKey, defaultFallback, fileKey, fileFallback] =
+    CONFIG_CHANGE_TOAST_KEYS[kind] ?? CONFIG_CHANGE_TOAST_KEYS.modified;
+  const defaultMessage = t(defaultKey, defaultFallback);
   if (normalizedPaths.length === 0) {
     return defaultMessage;
   }
 
   const applyTemplate = (label) => {
     if (!label) return defaultMessage;
-    const template = t("toast.configUpdatedWithFile", "{file} updated");
+    const template = t(fileKey, fileFallback);
     if (template.includes("{file}")) {
       return template.replace("{file}", label);
     }
-    return `${label} updated`;
+    return fileFallback.replace("{file}", label);
   };
 
   const userHomeDir = await ensureUserHomeDir();
@@ -1944,11 +1989,7 @@ const buildConfigUpdatedToastMessage = async (changedPathsInput) => {
   return applyTemplate(fileDisplayLabel);
 };
 
-const handleConfigFileChanged = async (changedPaths = null) => {
-  if (state.isSavingInternally) {
-    console.log("[FileChange] Ignoring file change during internal save");
-    return;
-  }
+const handleConfigFileChanged = async (changedPaths = null, kind = "modified") => {
   const normalizedPaths = normalizeChangedPaths(changedPaths);
   console.log(
     `[FileChange] Config file changed, editorDirty: ${state.editorDirty}, files: ${
@@ -1977,7 +2018,7 @@ const handleConfigFileChanged = async (changedPaths = null) => {
     );
   } else {
     console.log("[FileChange] Showing toast (no unsaved changes)");
-    const toastMessage = await buildConfigUpdatedToastMessage(normalizedPaths);
+    const toastMessage = await buildConfigUpdatedToastMessage(normalizedPaths, kind);
     const decoratedMessage = toastMessage.startsWith("📝") ? toastMessage : `📝 ${toastMessage}`;
     state.fileChangeToast = showActionToast(
       decoratedMessage,
@@ -2026,7 +2067,8 @@ const listenToFileChanges = async () => {
             return;
           }
 
-          await handleConfigFileChanged(normalizedPaths.length ? normalizedPaths : null);
+          const changeKind = payloadIsObject && typeof payload.kind === "string" ? payload.kind : "modified";
+          await handleConfigFileChanged(normalizedPaths.length ? normalizedPaths : null, changeKind);
         } catch (error) {
           console.warn("[FileWatcher] Failed to process config change:", error);
         }
@@ -2147,24 +2189,6 @@ const listenToMenuEvents = async () => {
     console.error("[Menu] Failed to register menu://settings listener:", error);
   }
 
-  try {
-    state.menuQuitUnlisten = await listen("menu://quit", async () => {
-      console.log("[Menu] Quit menu triggered, creating exit snapshot...");
-      try {
-        await createAutoSnapshot(
-          state.currentClientId,
-          t("snapshots.beforeQuitPrefix", "Before Quit")
-        );
-        console.log("[Menu] Exit snapshot created successfully");
-      } catch (error) {
-        console.warn("创建退出前快照失败:", error);
-      }
-    });
-    console.log("[Menu] menu://quit listener registered successfully!");
-  } catch (error) {
-    console.error("[Menu] Failed to register menu://quit listener:", error);
-  }
-
   try {
     state.menuSnapshotCreateUnlisten = await listen("menu://snapshot-create", async () => {
       console.log("[Menu] Snapshot Create menu clicked");
@@ -2215,7 +2239,7 @@ const listenToMenuEvents = async () => {
 };
 
 const cleanupMenuListeners = () => {
-  ["menuSettingsUnlisten", "menuQuitUnlisten", "menuSnapshotCreateUnlisten", "menuSnapshotManageUnlisten"].forEach(
+  ["menuSettingsUnlisten", "menuSnapshotCreateUnlisten", "menuSnapshotManageUnlisten"].forEach(
     (key) => {
       if (typeof state[key] === "function") {
         state[key]();
@@ -2241,13 +2265,9 @@ const saveConfigFile = async ({ silent = false, createSnapshot = false } = {}) =
   const content = getEditorContent();
   state.configContent = content;
   try {
-    state.isSavingInternally = true;
     await withLoading(async () => {
       await ConfigFileAPI.write(state.currentClientId, state.configContent);
     });
-    setTimeout(() => {
-      state.isSavingInternally = false;
-    }, 1000);
     state.editorDirty = false;
     if (!silent) {
       showToast(t("toast.configSaved", "Configuration saved"), "success");
@@ -2273,7 +2293,6 @@ const saveConfigFile = async ({ silent = false, createSnapshot = false } = {}) =
     }
     return true;
   } catch (error) {
-    state.isSavingInternally = false;
     showToast(
       getErrorMessage(error) || t("toast.saveConfigFailed", "Failed to save configuration"),
       "error"
@@ -2306,7 +2325,6 @@ const switchClient = async (clientId) => {
   renderPromptList();
   const client = getCurrentClient();
   try {
-    await stopFileWatcher();
     await withLoading(async () => {
       await AppStateAPI.setCurrentClient(clientId);
       await loadConfigFile(clientId, client?.active_config_path || null);
@@ -2325,7 +2343,7 @@ const switchConfigFile = async (configPath) => {
   if (configPath === state.currentConfigPath) return;
 
   const client = getCurrentClient();
-  if (!client || !client.config_file_paths?.includes(configPath)) {
+  if (!client || !getClientConfigFiles(client).includes(configPath)) {
     showToast(t("toast.invalidConfigPath", "Invalid config path"), "error");
     return;
   }
@@ -2425,7 +2443,7 @@ const renderConfigFileDropdown = () => {
   const list = elements.configFileDropdownList;
   const noClientLabel = t("clients.noClientSelected", "No Client Selected");
 
-  const configPaths = client?.config_file_paths ?? [];
+  const configPaths = getClientConfigFiles(client);
 
   // 始终显示容器
   container?.classList.remove("hidden");
@@ -2540,13 +2558,28 @@ const renderConfigFileDropdown = () => {
   }
 };
 
+// 写入编辑器前替换提示词中的 {{变量}}，失败时提示原因并返回 null
+const renderPromptContent = async (prompt) => {
+  try {
+    return await PromptAPI.render(prompt.id, state.currentClientId);
+  } catch (error) {
+    showToast(
+      getErrorMessage(error) || t("toast.promptRenderFailed", "Failed to resolve prompt variables"),
+      "error"
+    );
+    return null;
+  }
+};
+
 const applyPrompt = async (promptId) => {
   const prompt = state.prompts.find((item) => item.id === promptId);
   if (!prompt) {
     showToast(t("toast.promptNotFound", "Prompt not found"), "error");
     return;
   }
-  setEditorContent(prompt.content ?? "");
+  const content = await renderPromptContent(prompt);
+  if (content === null) return;
+  setEditorContent(content);
   const saved = await saveConfigFile({ silent: true });
   if (saved) {
     const messageTemplate = t("toast.promptApplied", 'Prompt "{value}" applied');
@@ -2561,8 +2594,9 @@ const appendPrompt = async (promptId) => {
     showToast(t("toast.promptNotFound", "Prompt not found"), "error");
     return;
   }
+  const promptContent = await renderPromptContent(prompt);
+  if (promptContent === null) return;
   const currentValue = getEditorContent();
-  const promptContent = prompt.content ?? "";
   const needsSpacer = currentValue.trim().length > 0;
   const insertionText = `${needsSpacer ? "\n\n" : ""}${promptContent}`;
   let handledByMonaco = false;
@@ -3479,7 +3513,18 @@ const updateEditorAvailability = () => {
   }
 };
 
-const getErrorMessage = (error) => (typeof error === "string" ? error : error?.message);
+// 后端错误形如 { code, message, params, cause }，优先按错误码本地化，找不到译文时退回默认消息
+const getErrorMessage = (error) => {
+  if (typeof error === "string") {
+    return error;
+  }
+  if (typeof error?.code === "string") {
+    const template = t(`errorCodes.${error.code}`, error.message);
+    const params = error.params || {};
+    return template.replace(/\{(\w+)\}/g, (match, key) => (key in params ? params[key] : match));
+  }
+  return error?.message;
+};
 
 window.addEventListener("beforeunload", () => {
   persistWindowState();
@@ -3263,7 +3263,18 @@ const switchTab = (targetId) => {
   }
 };
 
-const getErrorMessage = (error) => (typeof error === "string" ? error : error?.message);
+// 后端错误形如 { code, message, params, cause }，优先按错误码本地化，找不到译文时退回默认消息
+const getErrorMessage = (error) => {
+  if (typeof error === "string") {
+    return error;
+  }
+  if (typeof error?.code === "string") {
+    const template = t(`errorCodes.${error.code}`, error.message);
+    const params = error.params || {};
+    return template.replace(/\{(\w+)\}/g, (match, key) => (key in params ? params[key] : match));
+  }
+  return error?.message;
+};
 
 document.addEventListener("DOMContentLoaded", () => {
   initSettings();
@@ -1,12 +1,9 @@
 use std::io::{Error as IoError, ErrorKind};
-use std::sync::{Arc, Mutex};
 
-use crate::commands;
-use crate::file_watcher::ConfigFileWatcher;
+use crate::auto_snapshot;
+use crate::commands::event_sink::TauriEventSink;
 use crate::models::{ClientConfig, Snapshot};
-use crate::storage::{
-    client_repository::ClientRepository, snapshot_repository::SnapshotRepository,
-};
+use crate::service::VaultService;
 use chrono::{DateTime, Local};
 use serde::Serialize;
 use tauri::menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
@@ -239,20 +236,9 @@ pub fn handle_menu_event<R: Runtime>(
     }
 }
 
-/// 处理退出菜单
+/// 处理退出菜单：同步创建退出前快照后退出
 fn handle_quit_menu<R: Runtime>(app_handle: &AppHandle<R>) -> Result<(), String> {
-    // 向主窗口发送退出前事件,让前端创建快照
-    if let Some(window) = app_handle.get_webview_window("main") {
-        window.emit("menu://quit", ()).map_err(|e| e.to_string())?;
-    }
-
-    // 给前端200ms时间创建快照,然后退出应用
-    let app = app_handle.clone();
-    std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_millis(200));
-        app.exit(0);
-    });
-
+    auto_snapshot::exit_app(app_handle);
     Ok(())
 }
 
@@ -281,22 +267,15 @@ fn build_client_submenus_for_app_menu<R: Runtime>(
     let mut clients = collect_clients_for_menu(app_handle)?;
     clients.sort_by(|a, b| a.name.cmp(&b.name));
 
-    let snapshot_state = app_handle.state::<Arc<Mutex<SnapshotRepository>>>();
-    let snapshot_repo = Arc::clone(snapshot_state.inner());
-
-    let client_snapshot_pairs: Vec<(ClientConfig, Vec<Snapshot>)> = {
-        let repo = snapshot_repo
-            .lock()
-            .map_err(|_| menu_error("获取快照仓库锁失败"))?;
-        let mut data = Vec::with_capacity(clients.len());
-        for client in &clients {
-            let snapshots = repo
-                .get_snapshots(&client.id)
-                .map_err(|err| menu_error(err))?;
-            data.push((client.clone(), snapshots));
-        }
-        data
-    };
+    let service = app_handle.state::<VaultService>();
+    let mut client_snapshot_pairs: Vec<(ClientConfig, Vec<Snapshot>)> =
+        Vec::with_capacity(clients.len());
+    for client in &clients {
+        let snapshots = service
+            .get_snapshots(&client.id)
+            .map_err(|err| menu_error(err.to_string()))?;
+        client_snapshot_pairs.push((client.clone(), snapshots));
+    }
 
     let mut submenus = Vec::with_capacity(client_snapshot_pairs.len());
     for (client, snapshots) in client_snapshot_pairs {
@@ -311,13 +290,10 @@ fn build_client_submenus_for_app_menu<R: Runtime>(
 fn collect_clients_for_menu<R: Runtime>(
     app_handle: &AppHandle<R>,
 ) -> Result<Vec<ClientConfig>, tauri::Error> {
-    let client_state = app_handle.state::<Arc<Mutex<ClientRepository>>>();
-    let repo = client_state
-        .inner()
-        .lock()
-        .map_err(|_| menu_error("获取客户端仓库锁失败"))?;
-
-    repo.get_all().map_err(|err| menu_error(err))
+    let service = app_handle.state::<VaultService>();
+    service
+        .get_clients()
+        .map_err(|err| menu_error(err.to_string()))
 }
 
 fn build_client_submenu_for_app<R: Runtime>(
@@ -385,23 +361,13 @@ fn restore_snapshot_from_app_menu<R: Runtime>(
     client_id: &str,
     snapshot_id: &str,
 ) -> Result<(), String> {
-    let snapshot_state = app_handle.state::<Arc<Mutex<SnapshotRepository>>>();
-    let snapshot_repo = Arc::clone(snapshot_state.inner());
-    let client_state = app_handle.state::<Arc<Mutex<ClientRepository>>>();
-    let watcher_state = app_handle.state::<Arc<Mutex<ConfigFileWatcher>>>();
-
-    commands::snapshot::restore_snapshot_from_tray(
-        app_handle.clone(),
-        Arc::clone(&snapshot_repo),
-        Arc::clone(client_state.inner()),
-        Arc::clone(watcher_state.inner()),
-        client_id.to_string(),
-        snapshot_id.to_string(),
-    )?;
+    let service = app_handle.state::<VaultService>();
+    let sink = TauriEventSink::new(app_handle.clone());
+    let snapshot = service
+        .restore_snapshot(client_id, snapshot_id, &sink)
+        .map_err(|err| err.to_string())?;
 
-    if let Err(err) =
-        notify_snapshot_restored_from_menu(app_handle, snapshot_repo, client_id, snapshot_id)
-    {
+    if let Err(err) = notify_snapshot_restored_from_menu(app_handle, &snapshot) {
         eprintln!("通知发送失败: {}", err);
     }
 
@@ -416,25 +382,11 @@ struct SnapshotRestoredPayload {
 
 fn notify_snapshot_restored_from_menu<R: Runtime>(
     app_handle: &AppHandle<R>,
-    snapshot_repo: Arc<Mutex<SnapshotRepository>>,
-    client_id: &str,
-    snapshot_id: &str,
+    snapshot: &Snapshot,
 ) -> Result<(), String> {
-    let (snapshot_name, created_at_iso) = {
-        let repo = snapshot_repo
-            .lock()
-            .map_err(|e| format!("获取快照仓库锁失败: {}", e))?;
-        let snapshots = repo.get_snapshots(client_id)?;
-        snapshots
-            .into_iter()
-            .find(|snapshot| snapshot.id == snapshot_id)
-            .map(|snapshot| (snapshot.name, snapshot.created_at.to_rfc3339()))
-            .unwrap_or_else(|| ("未知快照".to_string(), Local::now().to_rfc3339()))
-    };
-
     let payload = SnapshotRestoredPayload {
-        snapshot_name,
-        created_at: created_at_iso,
+        snapshot_name: snapshot.name.clone(),
+        created_at: snapshot.created_at.to_rfc3339(),
     };
 
     app_handle
@@ -0,0 +1,162 @@
+//! 由后端驱动的自动快照
+//!
+//! 文件监听器发现配置文件变化后，经防抖为对应客户端创建一次自动快照；退出应用（包括关闭行为为
+//! 退出时关闭主窗口）时同步为全部监听中的客户端创建快照，不再依赖前端在退出前的短暂窗口内完成。
+
+use crate::commands::event_sink::TauriEventSink;
+use crate::error::ErrorCode;
+use crate::file_watcher::{ConfigFileWatcher, LEGACY_CLIENT_ID};
+use crate::service::VaultService;
+use chrono::Local;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// 最后一次变化后等待多久再创建快照，连续保存只产生一个快照
+const DEBOUNCE: Duration = Duration::from_secs(3);
+
+/// 自动快照名称的前缀，由前端按当前界面语言设置
+#[derive(Debug, Clone)]
+struct SnapshotLabels {
+    auto: String,
+    before_quit: String,
+}
+
+impl SnapshotLabels {
+    fn read(labels: &Mutex<Self>) -> Self {
+        labels
+            .lock()
+            .map(|labels| labels.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for SnapshotLabels {
+    fn default() -> Self {
+        Self {
+            auto: "Auto Snapshot".to_string(),
+            before_quit: "Before Quit".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AutoSnapshotScheduler {
+    /// 等待创建快照的客户端及其防抖到期时间
+    pending: Arc<Mutex<HashMap<String, Instant>>>,
+    labels: Arc<Mutex<SnapshotLabels>>,
+}
+
+impl AutoSnapshotScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置快照名称前缀，空白的前缀保持原值
+    pub fn set_labels(&self, auto: &str, before_quit: &str) {
+        let Ok(mut labels) = self.labels.lock() else {
+            return;
+        };
+        if !auto.trim().is_empty() {
+            labels.auto = auto.trim().to_string();
+        }
+        if !before_quit.trim().is_empty() {
+            labels.before_quit = before_quit.trim().to_string();
+        }
+    }
+
+    /// 客户端配置文件发生变化；到期前再次变化会顺延，每个客户端最多一个等待线程
+    pub fn schedule<R: Runtime>(&self, app_handle: &AppHandle<R>, client_id: &str) {
+        if client_id == LEGACY_CLIENT_ID {
+            return;
+        }
+        let Ok(mut pending) = self.pending.lock() else {
+            eprintln!("[AutoSnapshot] 获取防抖队列失败");
+            return;
+        };
+        let deadline = Instant::now() + DEBOUNCE;
+        if pending.insert(client_id.to_string(), deadline).is_some() {
+            return;
+        }
+        drop(pending);
+
+        let pending = Arc::clone(&self.pending);
+        let labels = Arc::clone(&self.labels);
+        let app_handle = app_handle.clone();
+        let client_id = client_id.to_string();
+        thread::spawn(move || {
+            loop {
+                let wait = {
+                    let Ok(mut pending) = pending.lock() else {
+                        return;
+                    };
+                    // 条目已被退出流程取走，快照由退出流程负责
+                    let Some(deadline) = pending.get(&client_id) else {
+                        return;
+                    };
+                    let wait = deadline.saturating_duration_since(Instant::now());
+                    if wait.is_zero() {
+                        pending.remove(&client_id);
+                    }
+                    wait
+                };
+                if wait.is_zero() {
+                    break;
+                }
+                thread::sleep(wait);
+            }
+            create_auto_snapshot(&app_handle, &client_id, &SnapshotLabels::read(&labels).auto);
+        });
+    }
+
+    /// 同步为监听中与等待防抖的全部客户端创建快照，内容未变化的客户端会被跳过
+    pub fn snapshot_before_exit<R: Runtime>(&self, app_handle: &AppHandle<R>) {
+        let mut client_ids: BTreeSet<String> = self
+            .pending
+            .lock()
+            .map(|mut pending| pending.drain().map(|(id, _)| id).collect())
+            .unwrap_or_default();
+        if let Some(watcher) = app_handle.try_state::<Arc<Mutex<ConfigFileWatcher>>>() {
+            if let Ok(watcher) = watcher.lock() {
+                client_ids.extend(watcher.client_ids());
+            }
+        }
+        let prefix = SnapshotLabels::read(&self.labels).before_quit;
+        for client_id in client_ids.iter().filter(|id| *id != LEGACY_CLIENT_ID) {
+            create_auto_snapshot(app_handle, client_id, &prefix);
+        }
+    }
+}
+
+/// 为监听中的客户端创建快照后退出应用
+pub fn exit_app<R: Runtime>(app_handle: &AppHandle<R>) {
+    if let Some(scheduler) = app_handle.try_state::<AutoSnapshotScheduler>() {
+        scheduler.snapshot_before_exit(app_handle);
+    }
+    app_handle.exit(0);
+}
+
+fn create_auto_snapshot<R: Runtime>(app_handle: &AppHandle<R>, client_id: &str, prefix: &str) {
+    let Some(service) = app_handle.try_state::<VaultService>() else {
+        return;
+    };
+    let name = format!("{} {}", prefix, Local::now().format("%Y-%m-%d %H:%M"));
+    let sink = TauriEventSink::new(app_handle.clone());
+    match service.create_snapshot(client_id, name, String::new(), true, &sink) {
+        Ok(snapshot) => {
+            eprintln!(
+                "[AutoSnapshot] 已创建快照: {} (客户端: {})",
+                snapshot.name, client_id
+            );
+        }
+        Err(err) if err.code == ErrorCode::SnapshotUnchanged => {}
+        Err(err) => {
+            eprintln!(
+                "[AutoSnapshot] 创建快照失败 (客户端: {}): {}",
+                client_id, err
+            );
+        }
+    }
+}
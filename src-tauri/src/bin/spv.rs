@@ -0,0 +1,748 @@
+//! `spv`：无需启动 Tauri 窗口即可操作 SystemPromptVault 数据目录的命令行工具
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Utc};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use systemprompt_vault::client_catalog::load_catalog;
+use systemprompt_vault::error::{ErrorCode, VaultError, VaultResult};
+use systemprompt_vault::models::{
+    ClientConfig, ConflictStyle, DiscoveryConfidence, Snapshot, SnapshotSearchQuery,
+};
+use systemprompt_vault::service::{snapshot_file_contents, NoopEventSink, VaultService};
+use systemprompt_vault::storage;
+
+#[derive(Parser)]
+#[command(
+    name = "spv",
+    version,
+    about = "Headless command-line interface for SystemPromptVault"
+)]
+struct Cli {
+    /// Print machine-readable JSON instead of plain text
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List configured clients
+    Clients,
+    /// List a client's config files, with wildcard patterns expanded
+    Files { client: String },
+    /// List known AI tools from the built-in and user client catalogs
+    Catalog,
+    /// Scan for AI tool instruction files and propose clients
+    Discover {
+        /// Also scan this project directory
+        #[arg(long)]
+        project: Option<String>,
+        /// Add the proposed clients (medium and high confidence)
+        #[arg(long)]
+        accept: bool,
+        /// With --accept: also add low-confidence proposals
+        #[arg(long, requires = "accept")]
+        include_low: bool,
+    },
+    /// Create, list, diff and restore snapshots
+    #[command(subcommand)]
+    Snapshot(SnapshotCommand),
+    /// List templates
+    Templates,
+    /// Apply a template to a project directory (creates a backup first)
+    Apply {
+        /// Project directory
+        project: String,
+        /// Template id or name
+        template: String,
+    },
+    /// Import or export the prompt library
+    #[command(subcommand)]
+    Prompts(PromptCommand),
+    /// Read, validate and edit keys of JSON, TOML and YAML client config files
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand)]
+enum SnapshotCommand {
+    /// List snapshots of a client, newest first
+    List { client: String },
+    /// Snapshot the client's current config files
+    Create {
+        client: String,
+        /// Snapshot name (defaults to a timestamped name)
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Mark as an auto snapshot (skipped when nothing changed)
+        #[arg(long)]
+        auto: bool,
+    },
+    /// Show a unified diff between two snapshots, or a snapshot and the live files
+    Diff {
+        client: String,
+        /// Snapshot id (or unique id prefix) to diff from
+        from: String,
+        /// Snapshot id to diff to; defaults to the current files on disk
+        to: Option<String>,
+    },
+    /// Search snapshots of all clients by label, text and creation date
+    Search {
+        /// Case-insensitive text matched against name, notes and file contents
+        text: Option<String>,
+        /// Treat TEXT as a regular expression
+        #[arg(long, requires = "text")]
+        regex: bool,
+        /// Only search this client
+        #[arg(short, long)]
+        client: Option<String>,
+        /// Required label; repeat to require several
+        #[arg(long = "label")]
+        labels: Vec<String>,
+        /// Created on or after this local date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Created on or before this local date (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<NaiveDate>,
+        /// Maximum number of results
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+    /// Export a client's snapshot history, or selected snapshots, as an archive
+    Export {
+        client: String,
+        /// Snapshot ids (or unique id prefixes); exports the full history when omitted
+        snapshots: Vec<String>,
+        /// Output file; prints to stdout when omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Merge a snapshot archive into a client's history ("-" reads stdin)
+    Import {
+        input: PathBuf,
+        /// Import into this client instead of the one recorded in the archive
+        #[arg(short, long)]
+        client: Option<String>,
+    },
+    /// Pin a snapshot so automatic pruning never deletes it
+    Pin { client: String, snapshot: String },
+    /// Unpin a snapshot; it counts toward the limits again
+    Unpin { client: String, snapshot: String },
+    /// Write a snapshot back to the client's config files
+    Restore {
+        client: String,
+        snapshot: String,
+        /// Three-way merge with edits made since the latest snapshot instead of overwriting
+        #[arg(long)]
+        merge: bool,
+        /// With --merge: on conflicts write nothing and only report them
+        #[arg(long, requires = "merge")]
+        no_markers: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PromptCommand {
+    /// Export all prompts as JSON
+    Export {
+        /// Output file; prints to stdout when omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import prompts from a JSON file ("-" reads stdin)
+    Import { input: PathBuf },
+    /// Search prompts, e.g. `"code review" tag:rust -tag:draft`
+    Search {
+        query: String,
+        /// Maximum number of results
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the value at a dotted key path, or the whole document when KEY is omitted
+    Get {
+        client: String,
+        key: Option<String>,
+        /// Config file of the client; defaults to the active one
+        #[arg(short, long)]
+        file: Option<String>,
+    },
+    /// Set the value at a dotted key path, keeping comments and formatting where possible
+    Set {
+        client: String,
+        key: String,
+        /// Parsed as JSON (`true`, `3`, `["a"]`); anything else is stored as a string
+        value: String,
+        /// Config file of the client; defaults to the active one
+        #[arg(short, long)]
+        file: Option<String>,
+    },
+    /// Check a client config file for syntax errors
+    Validate {
+        client: String,
+        /// Config file of the client; defaults to the active one
+        #[arg(short, long)]
+        file: Option<String>,
+    },
+}
+
+type CliResult<T> = VaultResult<T>;
+
+fn open_service() -> CliResult<VaultService> {
+    let data_dir = storage::ensure_app_dir()?;
+    VaultService::open(data_dir)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let json = cli.json;
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // --json 模式下错误同样以结构化形式输出，便于脚本按错误码分支
+            match serde_json::to_string(&err) {
+                Ok(content) if json => eprintln!("{}", content),
+                _ => eprintln!("spv: {}", err),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    let service = open_service()?;
+    let json = cli.json;
+
+    match cli.command {
+        Command::Clients => {
+            let clients = service.get_clients()?;
+            if json {
+                return print_json(&clients);
+            }
+            for client in clients {
+                println!(
+                    "{}\t{}\t{}",
+                    client.id,
+                    client.name,
+                    client.config_file_paths.join(", ")
+                );
+            }
+            Ok(())
+        }
+        Command::Files { client } => {
+            let client = resolve_client(&service, &client)?;
+            let files = service.list_client_config_files(&client.id)?;
+            if json {
+                return print_json(&files);
+            }
+            for path in files {
+                println!("{}", path);
+            }
+            Ok(())
+        }
+        Command::Catalog => {
+            let catalog = load_catalog()?;
+            if json {
+                return print_json(&catalog.entries());
+            }
+            for entry in catalog.entries() {
+                let paths: Vec<&str> = entry
+                    .global_paths
+                    .iter()
+                    .chain(entry.project_paths.iter())
+                    .chain(entry.shared_project_paths.iter())
+                    .map(String::as_str)
+                    .collect();
+                println!("{}\t{}\t{}", entry.id, entry.name, paths.join(", "));
+            }
+            Ok(())
+        }
+        Command::Discover {
+            project,
+            accept,
+            include_low,
+        } => {
+            let discovered = service.discover_clients(project.as_deref())?;
+            if accept {
+                let accepted: Vec<ClientConfig> = discovered
+                    .into_iter()
+                    .filter(|item| include_low || item.confidence > DiscoveryConfidence::Low)
+                    .map(|item| item.client)
+                    .collect();
+                let result = service.accept_discovered_clients(accepted)?;
+                if json {
+                    return print_json(&result);
+                }
+                println!("added: {}, updated: {}", result.added, result.updated);
+                return Ok(());
+            }
+            if json {
+                return print_json(&discovered);
+            }
+            for item in discovered {
+                let confidence = match item.confidence {
+                    DiscoveryConfidence::High => "high",
+                    DiscoveryConfidence::Medium => "medium",
+                    DiscoveryConfidence::Low => "low",
+                };
+                println!(
+                    "{}\t{}{}\t{}",
+                    item.client.id,
+                    confidence,
+                    if item.existing { ",existing" } else { "" },
+                    item.client.config_file_paths.join(", ")
+                );
+            }
+            Ok(())
+        }
+        Command::Snapshot(command) => run_snapshot(&service, command, json),
+        Command::Templates => {
+            let templates = service.get_templates()?;
+            if json {
+                return print_json(&templates);
+            }
+            for template in templates {
+                println!("{}\t{}", template.id, template.name);
+            }
+            Ok(())
+        }
+        Command::Apply {
+            project: project_path,
+            template: template_ref,
+        } => {
+            let template_id = resolve_template_id(&service, &template_ref)?;
+            let result = service.apply_template(&project_path, &template_id)?;
+            if json {
+                return print_json(&result);
+            }
+            if let Some(backup_id) = &result.backup_id {
+                println!("backup: {}", backup_id);
+            }
+            for file in result.modified_files {
+                println!("written: {}", file);
+            }
+            Ok(())
+        }
+        Command::Prompts(command) => run_prompts(&service, command, json),
+        Command::Config(command) => run_config(&service, command, json),
+    }
+}
+
+fn run_snapshot(service: &VaultService, command: SnapshotCommand, json: bool) -> CliResult<()> {
+    match command {
+        SnapshotCommand::List { client } => {
+            let client = resolve_client(service, &client)?;
+            let list = service.get_snapshots(&client.id)?;
+            if json {
+                return print_json(&list);
+            }
+            for item in list {
+                println!(
+                    "{}\t{}\t{}{}\t{}",
+                    item.id,
+                    format_local_time(&item.created_at),
+                    if item.is_auto { "auto" } else { "manual" },
+                    if item.pinned { ",pinned" } else { "" },
+                    item.name
+                );
+            }
+            Ok(())
+        }
+        SnapshotCommand::Create { client, name, auto } => {
+            let client = resolve_client(service, &client)?;
+            let name = name.unwrap_or_else(|| {
+                format!("CLI Snapshot {}", Local::now().format("%Y-%m-%d %H:%M"))
+            });
+            let created =
+                service.create_snapshot(&client.id, name, String::new(), auto, &NoopEventSink)?;
+            if json {
+                return print_json(&created);
+            }
+            println!("{}\t{}", created.id, created.name);
+            Ok(())
+        }
+        SnapshotCommand::Search {
+            text,
+            regex,
+            client,
+            labels,
+            since,
+            until,
+            limit,
+        } => {
+            let client_id = match client {
+                Some(client) => Some(resolve_client(service, &client)?.id),
+                None => None,
+            };
+            let query = SnapshotSearchQuery {
+                client_id,
+                labels,
+                text,
+                regex,
+                from: since.and_then(|date| local_date_time(date, NaiveTime::MIN)),
+                to: until
+                    .and_then(|date| local_date_time(date, NaiveTime::from_hms_opt(23, 59, 59)?)),
+                limit,
+            };
+            let hits = service.search_snapshots(&query)?;
+            if json {
+                return print_json(&hits);
+            }
+            for hit in hits {
+                let mut matched: Vec<String> = hit
+                    .matched_fields
+                    .iter()
+                    .map(|field| format!("{:?}", field).to_lowercase())
+                    .collect();
+                matched.extend(hit.matched_files);
+                println!(
+                    "{}\t{}\t{}\t{}\t{}",
+                    hit.client_id,
+                    hit.snapshot.id,
+                    format_local_time(&hit.snapshot.created_at),
+                    hit.snapshot.name,
+                    matched.join(",")
+                );
+            }
+            Ok(())
+        }
+        SnapshotCommand::Export {
+            client,
+            snapshots,
+            output,
+        } => {
+            let client = resolve_client(service, &client)?;
+            let snapshot_ids = if snapshots.is_empty() {
+                None
+            } else {
+                let list = service.get_snapshots(&client.id)?;
+                let ids = snapshots
+                    .iter()
+                    .map(|reference| Ok(resolve_snapshot(&list, reference)?.id.clone()))
+                    .collect::<CliResult<Vec<_>>>()?;
+                Some(ids)
+            };
+            let content = service.export_snapshot_archive(&client.id, snapshot_ids)?;
+            write_output(output, content)
+        }
+        SnapshotCommand::Import { input, client } => {
+            let raw = read_input(&input)?;
+            let target = match client {
+                Some(client) => Some(resolve_client(service, &client)?.id),
+                None => None,
+            };
+            let result =
+                service.import_snapshot_archive(&raw, target.as_deref(), &NoopEventSink)?;
+            if json {
+                return print_json(&result);
+            }
+            println!(
+                "imported {} of {} snapshots into {} ({} already present, {} duplicate content, {} pruned)",
+                result.imported,
+                result.total,
+                result.client_id,
+                result.skipped_existing,
+                result.skipped_duplicate,
+                result.pruned
+            );
+            Ok(())
+        }
+        SnapshotCommand::Pin { client, snapshot } => {
+            set_pinned(service, &client, &snapshot, true, json)
+        }
+        SnapshotCommand::Unpin { client, snapshot } => {
+            set_pinned(service, &client, &snapshot, false, json)
+        }
+        SnapshotCommand::Diff { client, from, to } => {
+            let client = resolve_client(service, &client)?;
+            let list = service.get_snapshots(&client.id)?;
+            let from_id = &resolve_snapshot(&list, &from)?.id;
+            let diff = match to {
+                Some(to) => service.diff_snapshots(
+                    &client.id,
+                    from_id,
+                    &resolve_snapshot(&list, &to)?.id,
+                )?,
+                None => service.diff_snapshot_with_current(&client.id, from_id)?,
+            };
+            if json {
+                return print_json(&diff);
+            }
+            for file in diff.files {
+                print!("{}", file.unified);
+            }
+            Ok(())
+        }
+        SnapshotCommand::Restore {
+            client,
+            snapshot: snapshot_ref,
+            merge,
+            no_markers,
+        } => {
+            let client = resolve_client(service, &client)?;
+            let list = service.get_snapshots(&client.id)?;
+            let target = resolve_snapshot(&list, &snapshot_ref)?;
+            if merge {
+                let style = if no_markers {
+                    ConflictStyle::List
+                } else {
+                    ConflictStyle::Markers
+                };
+                let result = service.restore_snapshot_merged(
+                    &client.id,
+                    &target.id,
+                    style,
+                    &NoopEventSink,
+                )?;
+                if json {
+                    return print_json(&result);
+                }
+                for file in &result.files {
+                    println!("{:?}: {}", file.status, file.path);
+                    for conflict in &file.conflicts {
+                        println!(
+                            "  conflict at line {} ({} base lines)",
+                            conflict.base_start_line, conflict.base_line_count
+                        );
+                    }
+                }
+                if !result.written && result.has_conflicts() {
+                    println!("nothing written");
+                }
+                return Ok(());
+            }
+            let restored = service.restore_snapshot(&client.id, &target.id, &NoopEventSink)?;
+            let written: Vec<String> = snapshot_file_contents(&client, &restored)
+                .into_keys()
+                .collect();
+            if json {
+                return print_json(&written);
+            }
+            for path in written {
+                println!("restored: {}", path);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn run_prompts(service: &VaultService, command: PromptCommand, json: bool) -> CliResult<()> {
+    match command {
+        PromptCommand::Export { output } => {
+            let content = service.export_prompts()?;
+            write_output(output, content)
+        }
+        PromptCommand::Import { input } => {
+            let raw = read_input(&input)?;
+            let result = service.import_prompts(&raw, &NoopEventSink)?;
+            if json {
+                return print_json(&result);
+            }
+            println!(
+                "imported {} prompts ({} added, {} updated)",
+                result.total, result.added, result.updated
+            );
+            Ok(())
+        }
+        PromptCommand::Search { query, limit } => {
+            let hits = service.search_prompts(&query, limit)?;
+            if json {
+                return print_json(&hits);
+            }
+            for hit in hits {
+                println!("{}\t{}\t{:.3}", hit.prompt.id, hit.prompt.name, hit.score);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn run_config(service: &VaultService, command: ConfigCommand, json: bool) -> CliResult<()> {
+    match command {
+        ConfigCommand::Get { client, key, file } => {
+            let client = resolve_client(service, &client)?;
+            let key_path = key.as_deref().map(split_key_path).unwrap_or_default();
+            let result = service.read_config_value(&client.id, file, key_path)?;
+            if json {
+                return print_json(&result);
+            }
+            match result.value {
+                Some(serde_json::Value::String(text)) => println!("{}", text),
+                Some(value) => print_json(&value)?,
+                None => {}
+            }
+            Ok(())
+        }
+        ConfigCommand::Set {
+            client,
+            key,
+            value,
+            file,
+        } => {
+            let client = resolve_client(service, &client)?;
+            let value = serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value));
+            let result = service.patch_config_file(
+                &client.id,
+                file,
+                split_key_path(&key),
+                value,
+                &NoopEventSink,
+            )?;
+            if json {
+                return print_json(&result);
+            }
+            println!("written: {}", result.path);
+            if !result.formatting_preserved {
+                println!("note: the file was reformatted; comments may have been dropped");
+            }
+            Ok(())
+        }
+        ConfigCommand::Validate { client, file } => {
+            let client = resolve_client(service, &client)?;
+            let result = service.validate_config_file(&client.id, file, None)?;
+            if json {
+                return print_json(&result);
+            }
+            match &result.error {
+                None => println!("{}: ok", result.path),
+                Some(error) => match (error.line, error.column) {
+                    (Some(line), Some(column)) => {
+                        println!("{}:{}:{}: {}", result.path, line, column, error.message)
+                    }
+                    _ => println!("{}: {}", result.path, error.message),
+                },
+            }
+            if result.valid {
+                Ok(())
+            } else {
+                Err(ErrorCode::ConfigInvalidSyntax.into())
+            }
+        }
+    }
+}
+
+/// `permissions.allow.0` 形式的键路径
+fn split_key_path(key: &str) -> Vec<String> {
+    key.split('.')
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 按 ID 精确匹配客户端，找不到时退回大小写不敏感匹配
+fn resolve_client(service: &VaultService, id: &str) -> CliResult<ClientConfig> {
+    if let Some(client) = service.get_client(id)? {
+        return Ok(client);
+    }
+    service
+        .get_clients()?
+        .into_iter()
+        .find(|client| client.id.eq_ignore_ascii_case(id))
+        .ok_or_else(|| VaultError::client_not_found(id))
+}
+
+/// 固定或取消固定快照
+fn set_pinned(
+    service: &VaultService,
+    client: &str,
+    snapshot: &str,
+    pinned: bool,
+    json: bool,
+) -> CliResult<()> {
+    let client = resolve_client(service, client)?;
+    let list = service.get_snapshots(&client.id)?;
+    let target = &resolve_snapshot(&list, snapshot)?.id;
+    let updated = service.set_snapshot_pinned(&client.id, target, pinned, &NoopEventSink)?;
+    if json {
+        return print_json(&updated);
+    }
+    println!(
+        "{}\t{}",
+        updated.id,
+        if pinned { "pinned" } else { "unpinned" }
+    );
+    Ok(())
+}
+
+/// 按完整 ID 或唯一前缀查找快照
+fn resolve_snapshot<'a>(snapshots: &'a [Snapshot], reference: &str) -> CliResult<&'a Snapshot> {
+    if let Some(found) = snapshots.iter().find(|s| s.id == reference) {
+        return Ok(found);
+    }
+    let mut matches = snapshots.iter().filter(|s| s.id.starts_with(reference));
+    match (matches.next(), matches.next()) {
+        (Some(found), None) => Ok(found),
+        (Some(_), Some(_)) => {
+            Err(VaultError::new(ErrorCode::SnapshotAmbiguousId)
+                .with_param("snapshot_id", reference))
+        }
+        _ => Err(VaultError::new(ErrorCode::SnapshotNotFound).with_param("snapshot_id", reference)),
+    }
+}
+
+fn resolve_template_id(service: &VaultService, reference: &str) -> CliResult<String> {
+    let templates = service.get_templates()?;
+    templates
+        .iter()
+        .find(|tpl| tpl.id == reference)
+        .or_else(|| templates.iter().find(|tpl| tpl.name == reference))
+        .map(|tpl| tpl.id.clone())
+        .ok_or_else(|| {
+            VaultError::new(ErrorCode::TemplateNotFound).with_param("template_id", reference)
+        })
+}
+
+/// 写入输出文件，未指定时打印到标准输出
+fn write_output(output: Option<PathBuf>, content: String) -> CliResult<()> {
+    match output {
+        Some(path) => fs::write(&path, content).map_err(|e| VaultError::write_failed(&path, e)),
+        None => {
+            println!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+/// 读取输入文件，`-` 表示标准输入
+fn read_input(input: &Path) -> CliResult<String> {
+    if input.as_os_str() == "-" {
+        let mut buffer = String::new();
+        io::stdin()
+            .read_to_string(&mut buffer)
+            .map_err(|e| VaultError::read_failed(input, e))?;
+        Ok(buffer)
+    } else {
+        fs::read_to_string(input).map_err(|e| VaultError::read_failed(input, e))
+    }
+}
+
+/// 本地日期与时刻对应的 UTC 时间，夏令时跳过的时刻返回 `None`
+fn local_date_time(date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+    date.and_time(time)
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn format_local_time(time: &DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> CliResult<()> {
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| VaultError::serialize_failed("output", e))?;
+    println!("{}", content);
+    Ok(())
+}
@@ -0,0 +1,173 @@
+//! 已知 AI 工具的客户端目录
+//!
+//! 每个条目描述一个工具的全局指令文件、项目内指令文件、文件格式等信息。目录随应用内置，
+//! 用户可以在数据目录中放置 `client_catalog.json` 补充新工具或覆盖内置条目，无需修改代码。
+
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::models::ClientConfig;
+use crate::utils::path_utils::is_path_pattern;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const EMBEDDED_CATALOG: &str = include_str!("client_catalog.json");
+/// 数据目录中用户扩展的目录文件
+pub const USER_CATALOG_FILE_NAME: &str = "client_catalog.json";
+
+/// 指令文件的格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    #[default]
+    Markdown,
+    /// 带 YAML front matter 的 Markdown，例如 Cursor 规则
+    Mdc,
+    Json,
+    Toml,
+    Yaml,
+    Text,
+}
+
+impl ConfigFormat {
+    /// 按扩展名推断文件格式，无法识别时视为纯文本
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("md" | "markdown") => Self::Markdown,
+            Some("mdc") => Self::Mdc,
+            Some("json") => Self::Json,
+            Some("toml") => Self::Toml,
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Text,
+        }
+    }
+
+    /// 是否为可按键路径读写的结构化格式
+    pub fn is_structured(self) -> bool {
+        matches!(self, Self::Json | Self::Toml | Self::Yaml)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Mdc => "mdc",
+            Self::Json => "json",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+            Self::Text => "text",
+        }
+    }
+}
+
+/// 目录中的一个工具；路径可以是通配模式，规则见 [`PathPattern`](crate::utils::path_utils::PathPattern)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub name: String,
+    /// 全局指令文件，通常以 `~/` 开头
+    #[serde(default)]
+    pub global_paths: Vec<String>,
+    /// 相对项目根目录、只属于该工具的指令文件，首项为模板导入导出使用的位置
+    #[serde(default)]
+    pub project_paths: Vec<String>,
+    /// 相对项目根目录、多个工具共用的文件名，例如 `AGENTS.md`
+    #[serde(default)]
+    pub shared_project_paths: Vec<String>,
+    /// 说明工具已安装的目录或文件
+    #[serde(default)]
+    pub markers: Vec<String>,
+    #[serde(default)]
+    pub format: ConfigFormat,
+    /// 指令文件支持 `@path` 形式引用其他文件
+    #[serde(default)]
+    pub supports_imports: bool,
+    /// 子目录中的同名指令文件同样生效
+    #[serde(default)]
+    pub supports_nested: bool,
+    /// 首次启动时创建为内置客户端
+    #[serde(default)]
+    pub default_client: bool,
+}
+
+impl CatalogEntry {
+    /// 不含通配符的全局指令文件，可作为客户端的配置路径
+    pub fn fixed_global_paths(&self) -> Vec<String> {
+        self.global_paths
+            .iter()
+            .filter(|path| !is_path_pattern(path))
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientCatalog {
+    entries: Vec<CatalogEntry>,
+}
+
+impl ClientCatalog {
+    /// 仅包含内置条目的目录
+    pub fn embedded() -> Self {
+        let entries =
+            serde_json::from_str(EMBEDDED_CATALOG).expect("内置客户端目录必须是有效的 JSON");
+        Self { entries }
+    }
+
+    /// 内置目录叠加数据目录中的用户目录；同 ID 条目以用户目录为准
+    pub fn load(data_dir: &Path) -> VaultResult<Self> {
+        let mut catalog = Self::embedded();
+        let path = data_dir.join(USER_CATALOG_FILE_NAME);
+        if !path.exists() {
+            return Ok(catalog);
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| VaultError::read_failed(&path, e))?;
+        let entries: Vec<CatalogEntry> =
+            serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(&path, e))?;
+        for entry in entries {
+            if entry.id.trim().is_empty() {
+                return Err(VaultError::new(ErrorCode::ClientIdEmpty).with_path(&path));
+            }
+            match catalog.position(&entry.id) {
+                Some(index) => catalog.entries[index] = entry,
+                None => catalog.entries.push(entry),
+            }
+        }
+        Ok(catalog)
+    }
+
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+
+    /// 按 ID 查找条目，忽略大小写
+    pub fn get(&self, id: &str) -> Option<&CatalogEntry> {
+        self.position(id).map(|index| &self.entries[index])
+    }
+
+    /// 首次启动时创建的内置客户端
+    pub fn default_clients(&self) -> Vec<ClientConfig> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.default_client)
+            .map(|entry| {
+                ClientConfig::new_builtin(&entry.id, &entry.name, entry.fixed_global_paths(), true)
+            })
+            .filter(|client| !client.config_file_paths.is_empty())
+            .collect()
+    }
+
+    fn position(&self, id: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.id.eq_ignore_ascii_case(id))
+    }
+}
+
+/// 读取应用数据目录下的客户端目录
+pub fn load_catalog() -> VaultResult<ClientCatalog> {
+    ClientCatalog::load(&crate::storage::ensure_app_dir()?)
+}
@@ -1,3 +1,4 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
 use crate::models::{app_state::WindowBehavior, AppState, WindowState};
 use crate::utils::file_ops::atomic_write;
 use chrono::Utc;
@@ -9,7 +10,7 @@ use super::ensure_app_dir;
 const APP_STATE_FILE: &str = "app_state.json";
 
 #[tauri::command]
-pub fn get_app_state() -> Result<AppState, String> {
+pub fn get_app_state() -> VaultResult<AppState> {
     match load_state()? {
         Some(state) => Ok(state),
         None => Ok(AppState::default()),
@@ -17,9 +18,9 @@ pub fn get_app_state() -> Result<AppState, String> {
 }
 
 #[tauri::command]
-pub fn set_current_client(client_id: String) -> Result<AppState, String> {
+pub fn set_current_client(client_id: String) -> VaultResult<AppState> {
     if client_id.trim().is_empty() {
-        return Err("客户端 ID 不能为空".to_string());
+        return Err(ErrorCode::ClientIdEmpty.into());
     }
 
     let mut state = load_state()?.unwrap_or_default();
@@ -30,9 +31,11 @@ pub fn set_current_client(client_id: String) -> Result<AppState, String> {
 }
 
 #[tauri::command]
-pub fn save_window_state(x: i32, y: i32, width: u32, height: u32) -> Result<(), String> {
+pub fn save_window_state(x: i32, y: i32, width: u32, height: u32) -> VaultResult<()> {
     if width == 0 || height == 0 {
-        return Err("窗口尺寸无效".to_string());
+        return Err(VaultError::new(ErrorCode::AppStateInvalidWindowSize)
+            .with_param("width", width)
+            .with_param("height", height));
     }
     let mut state = load_state()?.unwrap_or_default();
     state.window_state = Some(WindowState {
@@ -46,15 +49,15 @@ pub fn save_window_state(x: i32, y: i32, width: u32, height: u32) -> Result<(),
 }
 
 #[tauri::command]
-pub fn get_window_state() -> Result<Option<WindowState>, String> {
+pub fn get_window_state() -> VaultResult<Option<WindowState>> {
     Ok(load_state()?.and_then(|state| state.window_state))
 }
 
 #[tauri::command]
-pub fn set_window_behavior(close_behavior: String) -> Result<(), String> {
+pub fn set_window_behavior(close_behavior: String) -> VaultResult<()> {
     let close = close_behavior.trim();
     if close.is_empty() {
-        return Err("关闭行为不能为空".to_string());
+        return Err(ErrorCode::AppStateCloseBehaviorEmpty.into());
     }
 
     let mut state = load_state()?.unwrap_or_default();
@@ -66,29 +69,29 @@ pub fn set_window_behavior(close_behavior: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn get_window_behavior() -> Result<Option<WindowBehavior>, String> {
+pub fn get_window_behavior() -> VaultResult<Option<WindowBehavior>> {
     Ok(load_state()?.and_then(|state| state.window_behavior))
 }
 
-fn load_state() -> Result<Option<AppState>, String> {
+fn load_state() -> VaultResult<Option<AppState>> {
     let path = state_file_path()?;
     if !path.exists() {
         return Ok(None);
     }
-    let raw = fs::read_to_string(&path).map_err(|e| format!("读取应用状态失败: {}", e))?;
+    let raw = fs::read_to_string(&path).map_err(|e| VaultError::read_failed(&path, e))?;
     let state: AppState =
-        serde_json::from_str(&raw).map_err(|e| format!("解析应用状态失败: {}", e))?;
+        serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(&path, e))?;
     Ok(Some(state))
 }
 
-fn save_state(state: &AppState) -> Result<(), String> {
+fn save_state(state: &AppState) -> VaultResult<()> {
     let path = state_file_path()?;
-    let content =
-        serde_json::to_string_pretty(state).map_err(|e| format!("序列化应用状态失败: {}", e))?;
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| VaultError::serialize_failed("app_state", e))?;
     atomic_write(&path, &content)
 }
 
-fn state_file_path() -> Result<PathBuf, String> {
+fn state_file_path() -> VaultResult<PathBuf> {
     let mut dir = ensure_app_dir()?;
     dir.push(APP_STATE_FILE);
     Ok(dir)
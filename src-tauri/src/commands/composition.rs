@@ -0,0 +1,56 @@
+use crate::commands::event_sink::TauriEventSink;
+use crate::error::VaultResult;
+use crate::models::{Composition, CompositionItem, CompositionPreview};
+use crate::service::VaultService;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn get_compositions(service: State<'_, VaultService>) -> VaultResult<Vec<Composition>> {
+    service.get_compositions()
+}
+
+#[tauri::command]
+pub fn create_composition(
+    service: State<'_, VaultService>,
+    name: String,
+    client_id: String,
+    config_path: Option<String>,
+    items: Vec<CompositionItem>,
+) -> VaultResult<Composition> {
+    service.create_composition(name, client_id, config_path, items)
+}
+
+#[tauri::command]
+pub fn update_composition(
+    service: State<'_, VaultService>,
+    id: String,
+    name: String,
+    client_id: String,
+    config_path: Option<String>,
+    items: Vec<CompositionItem>,
+) -> VaultResult<Composition> {
+    service.update_composition(&id, name, client_id, config_path, items)
+}
+
+#[tauri::command]
+pub fn delete_composition(service: State<'_, VaultService>, id: String) -> VaultResult<()> {
+    service.delete_composition(&id)
+}
+
+#[tauri::command]
+pub fn preview_composition(
+    service: State<'_, VaultService>,
+    id: String,
+) -> VaultResult<CompositionPreview> {
+    service.preview_composition(&id)
+}
+
+#[tauri::command]
+pub fn render_composition(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
+    id: String,
+) -> VaultResult<Composition> {
+    let sink = TauriEventSink::new(app_handle);
+    service.render_composition(&id, &sink)
+}
@@ -1,94 +1,69 @@
-use crate::storage::client_repository::ClientRepository;
-use std::fs;
-use std::io::ErrorKind;
-use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use tauri::State;
-
-fn lock_repo<'a>(
-    state: &'a State<'_, Arc<Mutex<ClientRepository>>>,
-) -> Result<std::sync::MutexGuard<'a, ClientRepository>, String> {
-    state
-        .lock()
-        .map_err(|e| format!("获取客户端仓库锁失败: {}", e))
-}
+use super::event_sink::TauriEventSink;
+use crate::error::{ErrorCode, VaultResult};
+use crate::models::{ConfigPatchResult, ConfigValidation, ConfigValue};
+use crate::service::VaultService;
+use serde_json::Value;
+use tauri::{AppHandle, State};
 
 #[tauri::command]
-pub fn get_user_home_dir() -> Result<String, String> {
+pub fn get_user_home_dir() -> VaultResult<String> {
     dirs::home_dir()
         .and_then(|path| path.to_str().map(|s| s.to_string()))
-        .ok_or_else(|| "无法获取用户主目录".to_string())
+        .ok_or_else(|| ErrorCode::HomeDirUnavailable.into())
 }
 
 #[tauri::command]
 pub fn read_config_file(
-    repository: State<'_, Arc<Mutex<ClientRepository>>>,
+    service: State<'_, VaultService>,
     client_id: String,
     config_path: Option<String>,
-) -> Result<String, String> {
-    let repo = lock_repo(&repository)?;
-    let client = repo
-        .get_by_id(&client_id)?
-        .ok_or_else(|| "未找到指定客户端".to_string())?;
-    let sanitized_path = sanitize_requested_path(config_path)?;
-    let resolved = client
-        .resolve_config_path(sanitized_path.as_deref())
-        .map_err(|err| format!("{}", err))?;
-    let path = expand_tilde(&resolved);
-    match fs::read_to_string(&path) {
-        Ok(content) => Ok(content),
-        Err(err) if err.kind() == ErrorKind::NotFound => Ok(String::new()),
-        Err(err) => Err(format!("读取配置文件失败: {}", err)),
-    }
+) -> VaultResult<String> {
+    service.read_config_file(&client_id, config_path)
 }
 
 #[tauri::command]
 pub fn write_config_file(
-    repository: State<'_, Arc<Mutex<ClientRepository>>>,
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
     client_id: String,
     config_path: Option<String>,
     content: String,
-) -> Result<(), String> {
-    let repo = lock_repo(&repository)?;
-    let client = repo
-        .get_by_id(&client_id)?
-        .ok_or_else(|| "未找到指定客户端".to_string())?;
-    let sanitized_path = sanitize_requested_path(config_path)?;
-    let resolved = client
-        .resolve_config_path(sanitized_path.as_deref())
-        .map_err(|err| format!("{}", err))?;
-    let path = expand_tilde(&resolved);
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
-    }
-    fs::write(&path, content).map_err(|e| format!("写入配置文件失败: {}", e))
+) -> VaultResult<()> {
+    let sink = TauriEventSink::new(app_handle);
+    service.write_config_file(&client_id, config_path, content, &sink)
 }
 
-pub(crate) fn expand_tilde(path: &str) -> PathBuf {
-    if path.starts_with("~/") {
-        if let Some(home) = dirs::home_dir() {
-            if let Some(stripped) = path.strip_prefix("~/") {
-                return home.join(stripped);
-            }
-        }
-    } else if path == "~" {
-        if let Some(home) = dirs::home_dir() {
-            return home;
-        }
-    }
-    Path::new(path).to_path_buf()
+/// 读取 JSON、TOML、YAML 配置文件中键路径上的值，键路径为空时返回整个文档
+#[tauri::command]
+pub fn read_config_value(
+    service: State<'_, VaultService>,
+    client_id: String,
+    config_path: Option<String>,
+    key_path: Vec<String>,
+) -> VaultResult<ConfigValue> {
+    service.read_config_value(&client_id, config_path, key_path)
 }
 
-fn sanitize_requested_path(path: Option<String>) -> Result<Option<String>, String> {
-    match path {
-        Some(value) => {
-            let trimmed = value.trim();
-            if trimmed.is_empty() {
-                Err("配置文件路径不能为空".to_string())
-            } else {
-                Ok(Some(trimmed.to_string()))
-            }
-        }
-        None => Ok(None),
-    }
+/// 校验编辑中的内容或磁盘上的文件是否存在语法错误
+#[tauri::command]
+pub fn validate_config_file(
+    service: State<'_, VaultService>,
+    client_id: String,
+    config_path: Option<String>,
+    content: Option<String>,
+) -> VaultResult<ConfigValidation> {
+    service.validate_config_file(&client_id, config_path, content)
+}
+
+#[tauri::command]
+pub fn patch_config_file(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
+    client_id: String,
+    config_path: Option<String>,
+    key_path: Vec<String>,
+    value: Value,
+) -> VaultResult<ConfigPatchResult> {
+    let sink = TauriEventSink::new(app_handle);
+    service.patch_config_file(&client_id, config_path, key_path, value, &sink)
 }
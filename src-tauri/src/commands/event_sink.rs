@@ -0,0 +1,112 @@
+use crate::app_menu;
+use crate::file_watcher::ConfigFileWatcher;
+use crate::service::VaultEventSink;
+use crate::tray;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+const CONFIG_RELOAD_SILENT_EVENT: &str = "config-reload-silent";
+
+#[derive(Serialize, Clone)]
+struct ConfigReloadPayload {
+    client_id: String,
+    path: String,
+}
+
+/// 将服务层事件转换为 Tauri 侧的动作：登记自身写入、通知前端、刷新菜单
+pub struct TauriEventSink<R: Runtime> {
+    app_handle: AppHandle<R>,
+    /// 本次写入登记过的文件，写入结束后未写成的文件改为登记回滚后的内容
+    expected_paths: Mutex<Vec<PathBuf>>,
+}
+
+impl<R: Runtime> TauriEventSink<R> {
+    pub fn new(app_handle: AppHandle<R>) -> Self {
+        Self {
+            app_handle,
+            expected_paths: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn watcher(&self) -> Option<Arc<Mutex<ConfigFileWatcher>>> {
+        self.app_handle
+            .try_state::<Arc<Mutex<ConfigFileWatcher>>>()
+            .map(|state| Arc::clone(state.inner()))
+    }
+}
+
+impl<R: Runtime> VaultEventSink for TauriEventSink<R> {
+    fn config_files_writing(&self, _client_id: &str, files: &[(PathBuf, &str)]) {
+        let Some(watcher_state) = self.watcher() else {
+            return;
+        };
+        let Ok(watcher) = watcher_state.lock() else {
+            eprintln!("[FileWatcher] 获取文件监听器失败");
+            return;
+        };
+        for (path, content) in files {
+            watcher.expect_write(path, content);
+        }
+        if let Ok(mut expected) = self.expected_paths.lock() {
+            expected.extend(files.iter().map(|(path, _)| path.clone()));
+        }
+    }
+
+    fn config_files_removing(&self, _client_id: &str, paths: &[PathBuf]) {
+        let Some(watcher_state) = self.watcher() else {
+            return;
+        };
+        let Ok(watcher) = watcher_state.lock() else {
+            eprintln!("[FileWatcher] 获取文件监听器失败");
+            return;
+        };
+        for path in paths {
+            watcher.expect_removal(path);
+        }
+        if let Ok(mut expected) = self.expected_paths.lock() {
+            expected.extend(paths.iter().cloned());
+        }
+    }
+
+    fn config_files_written(&self, client_id: &str, paths: &[PathBuf]) {
+        let expected = self
+            .expected_paths
+            .lock()
+            .map(|mut expected| std::mem::take(&mut *expected))
+            .unwrap_or_default();
+        let unwritten: Vec<&PathBuf> = expected
+            .iter()
+            .filter(|path| !paths.contains(path))
+            .collect();
+        if !unwritten.is_empty() {
+            if let Some(watcher_state) = self.watcher() {
+                if let Ok(watcher) = watcher_state.lock() {
+                    for path in unwritten {
+                        watcher.expect_current(path);
+                    }
+                }
+            }
+        }
+
+        for path in paths {
+            let payload = ConfigReloadPayload {
+                client_id: client_id.to_string(),
+                path: path.to_string_lossy().to_string(),
+            };
+            if let Err(err) = self.app_handle.emit(CONFIG_RELOAD_SILENT_EVENT, payload) {
+                eprintln!("[Snapshot] 发送配置刷新事件失败: {}", err);
+            }
+        }
+    }
+
+    fn snapshots_changed(&self, _client_id: &str) {
+        if let Err(err) = tray::refresh_tray_menu(&self.app_handle) {
+            eprintln!("[Snapshot] 刷新托盘菜单失败: {}", err);
+        }
+        if let Err(err) = app_menu::refresh_app_menu(&self.app_handle) {
+            eprintln!("[Snapshot] 刷新应用菜单失败: {}", err);
+        }
+    }
+}
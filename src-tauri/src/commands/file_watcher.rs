@@ -1,7 +1,11 @@
-use super::config_file::expand_tilde;
+use crate::error::{ErrorCode, VaultError, VaultResult};
 use crate::file_watcher::{ConfigFileWatcher, LEGACY_CLIENT_ID};
+use crate::models::ClientConfig;
+use crate::service::VaultService;
+use crate::utils::path_utils::resolve_path;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, Runtime, State};
 
 #[tauri::command]
 pub fn start_watching_config(
@@ -10,7 +14,7 @@ pub fn start_watching_config(
     file_path: Option<String>,
     app_handle: AppHandle,
     watcher: State<'_, Arc<Mutex<ConfigFileWatcher>>>,
-) -> Result<(), String> {
+) -> VaultResult<()> {
     let client_id = client_id
         .filter(|value| !value.trim().is_empty())
         .unwrap_or_else(|| LEGACY_CLIENT_ID.to_string());
@@ -23,7 +27,7 @@ pub fn start_watching_config(
     }
 
     if provided_paths.is_empty() {
-        return Err("未提供任何配置文件路径".to_string());
+        return Err(ErrorCode::WatcherNoPaths.into());
     }
 
     let mut expanded_paths = Vec::with_capacity(provided_paths.len());
@@ -31,31 +35,115 @@ pub fn start_watching_config(
         if raw_path.trim().is_empty() {
             continue;
         }
-        let expanded = expand_tilde(&raw_path);
-        if !expanded.exists() {
-            return Err(format!("文件不存在: {}", raw_path));
-        }
-        expanded_paths.push(expanded);
+        expanded_paths.push(resolve_path(&raw_path));
     }
 
     if expanded_paths.is_empty() {
-        return Err("提供的配置文件路径均无效".to_string());
+        return Err(ErrorCode::WatcherNoPaths.into());
     }
 
     let mut watcher_guard = watcher
         .lock()
-        .map_err(|e| format!("锁定文件监听器失败: {}", e))?;
+        .map_err(|_| VaultError::lock_poisoned("file_watcher"))?;
     watcher_guard.watch_files(client_id, expanded_paths, app_handle)?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn stop_watching_config(
-    watcher: State<'_, Arc<Mutex<ConfigFileWatcher>>>,
-) -> Result<(), String> {
+pub fn stop_watching_config(watcher: State<'_, Arc<Mutex<ConfigFileWatcher>>>) -> VaultResult<()> {
     let mut watcher_guard = watcher
         .lock()
-        .map_err(|e| format!("锁定文件监听器失败: {}", e))?;
+        .map_err(|_| VaultError::lock_poisoned("file_watcher"))?;
     watcher_guard.stop();
     Ok(())
 }
+
+/// 停止监听单个客户端，其他客户端不受影响
+#[tauri::command]
+pub fn unwatch_client_config(
+    client_id: String,
+    watcher: State<'_, Arc<Mutex<ConfigFileWatcher>>>,
+) -> VaultResult<()> {
+    let mut watcher_guard = watcher
+        .lock()
+        .map_err(|_| VaultError::lock_poisoned("file_watcher"))?;
+    watcher_guard.unwatch_client(&client_id);
+    Ok(())
+}
+
+/// 同时监听全部客户端的配置文件，返回监听中的客户端
+#[tauri::command]
+pub fn watch_all_clients(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
+    watcher: State<'_, Arc<Mutex<ConfigFileWatcher>>>,
+) -> VaultResult<Vec<String>> {
+    let clients = service.get_clients()?;
+    let mut watcher_guard = watcher
+        .lock()
+        .map_err(|_| VaultError::lock_poisoned("file_watcher"))?;
+    for client_id in watcher_guard.client_ids() {
+        if client_id != LEGACY_CLIENT_ID && !clients.iter().any(|client| client.id == client_id) {
+            watcher_guard.unwatch_client(&client_id);
+        }
+    }
+    let mut watched = Vec::new();
+    for client in &clients {
+        if watch_client(&mut watcher_guard, client, &app_handle) {
+            watched.push(client.id.clone());
+        }
+    }
+    Ok(watched)
+}
+
+/// 客户端新增或修改后按其当前配置路径更新监听
+pub(crate) fn refresh_client_watch<R: Runtime>(app_handle: &AppHandle<R>, client: &ClientConfig) {
+    let Some(watcher) = app_handle.try_state::<Arc<Mutex<ConfigFileWatcher>>>() else {
+        return;
+    };
+    match watcher.lock() {
+        Ok(mut watcher) => {
+            watch_client(&mut watcher, client, app_handle);
+        }
+        Err(_) => eprintln!("[FileWatcher] 获取文件监听器失败"),
+    };
+}
+
+/// 客户端删除后停止监听其配置文件
+pub(crate) fn remove_client_watch<R: Runtime>(app_handle: &AppHandle<R>, client_id: &str) {
+    let Some(watcher) = app_handle.try_state::<Arc<Mutex<ConfigFileWatcher>>>() else {
+        return;
+    };
+    match watcher.lock() {
+        Ok(mut watcher) => watcher.unwatch_client(client_id),
+        Err(_) => eprintln!("[FileWatcher] 获取文件监听器失败"),
+    };
+}
+
+/// 监听客户端的配置文件（包括尚未创建的文件），没有配置路径时停止监听该客户端
+fn watch_client<R: Runtime>(
+    watcher: &mut ConfigFileWatcher,
+    client: &ClientConfig,
+    app_handle: &AppHandle<R>,
+) -> bool {
+    let paths: Vec<PathBuf> = client
+        .config_file_paths
+        .iter()
+        .filter(|path| !path.trim().is_empty())
+        .map(resolve_path)
+        .collect();
+    if paths.is_empty() {
+        watcher.unwatch_client(&client.id);
+        return false;
+    }
+    match watcher.watch_files(client.id.clone(), paths, app_handle.clone()) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!(
+                "[FileWatcher] 监听客户端配置文件失败 ({}): {}",
+                client.id, err
+            );
+            false
+        }
+    }
+}
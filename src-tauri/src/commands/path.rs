@@ -1,28 +1,29 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
 use std::path::PathBuf;
 
 use tauri::command;
 
-use crate::utils::path_utils::{expand_tilde, normalize_path};
+use crate::utils::path_utils::{normalize_path, resolve_path};
 
 #[command]
-pub fn expand_path(path: String) -> Result<String, String> {
+pub fn expand_path(path: String) -> VaultResult<String> {
     let normalized = expand_and_normalize(&path)?;
     Ok(normalized.to_string_lossy().into_owned())
 }
 
 #[command]
-pub fn get_filename(path: String) -> Result<String, String> {
+pub fn get_filename(path: String) -> VaultResult<String> {
     let normalized = expand_and_normalize(&path)?;
     normalized
         .file_name()
         .and_then(|name| name.to_str())
         .map(|name| name.to_string())
-        .ok_or_else(|| "无法解析文件名".to_string())
+        .ok_or_else(|| VaultError::new(ErrorCode::PathInvalid).with_path(&normalized))
 }
 
 /// 返回相对于用户主目录的路径（`~/` 开头），若不在主目录下则返回绝对路径。
 #[command]
-pub fn get_relative_path(path: String) -> Result<String, String> {
+pub fn get_relative_path(path: String) -> VaultResult<String> {
     let normalized = expand_and_normalize(&path)?;
 
     if let Some(home) = dirs::home_dir() {
@@ -45,10 +46,10 @@ pub fn get_relative_path(path: String) -> Result<String, String> {
     Ok(normalized.to_string_lossy().into_owned())
 }
 
-fn expand_and_normalize(path: &str) -> Result<PathBuf, String> {
+fn expand_and_normalize(path: &str) -> VaultResult<PathBuf> {
     if path.trim().is_empty() {
-        return Err("路径不能为空".to_string());
+        return Err(ErrorCode::PathEmpty.into());
     }
-    let expanded = expand_tilde(path);
+    let expanded = resolve_path(path);
     Ok(normalize_path(expanded))
 }
@@ -1,363 +1,246 @@
 use crate::app_menu;
-use crate::commands::config_file::expand_tilde;
-use crate::file_watcher::ConfigFileWatcher;
-use crate::models::{ClientConfig, Snapshot, SnapshotConfig};
-use crate::storage::{
-    client_repository::ClientRepository, snapshot_repository::SnapshotRepository,
+use crate::auto_snapshot::AutoSnapshotScheduler;
+use crate::commands::event_sink::TauriEventSink;
+use crate::error::{ErrorCause, ErrorCode, VaultError, VaultResult};
+use crate::models::{
+    ConflictStyle, MergeRestoreResult, RetentionPolicy, Snapshot, SnapshotConfig, SnapshotDiff,
+    SnapshotImportResult, SnapshotSearchHit, SnapshotSearchQuery,
 };
+use crate::service::VaultService;
 use crate::tray;
-use crate::utils::file_ops::atomic_write;
-use serde::Serialize;
-use std::collections::HashMap;
-use std::fs;
-use std::io::ErrorKind;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter, Runtime, State};
-
-const CONFIG_RELOAD_SILENT_EVENT: &str = "config-reload-silent";
-
-#[derive(Serialize, Clone)]
-struct ConfigReloadPayload {
-    client_id: String,
-    path: String,
-}
-
-fn lock_snapshot_repo<'a>(
-    state: &'a State<'_, Arc<Mutex<SnapshotRepository>>>,
-) -> Result<std::sync::MutexGuard<'a, SnapshotRepository>, String> {
-    lock_snapshot_arc(state.inner())
-}
-
-fn lock_client_repo<'a>(
-    state: &'a State<'_, Arc<Mutex<ClientRepository>>>,
-) -> Result<std::sync::MutexGuard<'a, ClientRepository>, String> {
-    lock_client_arc(state.inner())
-}
-
-fn lock_snapshot_arc<'a>(
-    repo: &'a Arc<Mutex<SnapshotRepository>>,
-) -> Result<std::sync::MutexGuard<'a, SnapshotRepository>, String> {
-    repo.lock()
-        .map_err(|e| format!("获取快照仓库锁失败: {}", e))
-}
-
-fn lock_client_arc<'a>(
-    repo: &'a Arc<Mutex<ClientRepository>>,
-) -> Result<std::sync::MutexGuard<'a, ClientRepository>, String> {
-    repo.lock()
-        .map_err(|e| format!("获取客户端仓库锁失败: {}", e))
-}
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub fn create_snapshot(
-    snapshot_repository: State<'_, Arc<Mutex<SnapshotRepository>>>,
-    client_repository: State<'_, Arc<Mutex<ClientRepository>>>,
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
     client_id: String,
     name: String,
     content: String,
     is_auto: bool,
-) -> Result<Snapshot, String> {
-    let client = {
-        let repo = lock_client_repo(&client_repository)?;
-        repo.get_by_id(&client_id)?
-            .ok_or_else(|| "未找到指定客户端".to_string())?
-    };
-
-    if client.config_file_paths.is_empty() {
-        return Err("客户端未配置任何配置文件路径,无法创建快照".to_string());
-    }
+) -> VaultResult<Snapshot> {
+    let sink = TauriEventSink::new(app_handle);
+    service.create_snapshot(&client_id, name, content, is_auto, &sink)
+}
 
-    let file_contents = read_client_config_files(&client)?;
-    let legacy_content = client
-        .default_config_path()
-        .and_then(|path| file_contents.get(path).cloned())
-        .unwrap_or_else(|| content.clone());
+#[tauri::command]
+pub fn get_snapshots(
+    service: State<'_, VaultService>,
+    client_id: String,
+) -> VaultResult<SnapshotConfig> {
+    service.get_snapshot_config(&client_id)
+}
 
-    let repo = lock_snapshot_repo(&snapshot_repository)?;
-    repo.create_snapshot(
-        &client_id,
-        name,
-        legacy_content,
-        Some(file_contents),
-        is_auto,
-    )
+#[tauri::command]
+pub fn diff_snapshots(
+    service: State<'_, VaultService>,
+    client_id: String,
+    from_snapshot_id: String,
+    to_snapshot_id: String,
+) -> VaultResult<SnapshotDiff> {
+    service.diff_snapshots(&client_id, &from_snapshot_id, &to_snapshot_id)
 }
 
 #[tauri::command]
-pub fn get_snapshots(
-    repository: State<'_, Arc<Mutex<SnapshotRepository>>>,
+pub fn diff_snapshot_with_current(
+    service: State<'_, VaultService>,
     client_id: String,
-) -> Result<SnapshotConfig, String> {
-    let repo = lock_snapshot_repo(&repository)?;
-    repo.get_config(&client_id)
+    snapshot_id: String,
+) -> VaultResult<SnapshotDiff> {
+    service.diff_snapshot_with_current(&client_id, &snapshot_id)
 }
 
 #[tauri::command]
 pub fn restore_snapshot(
     app_handle: AppHandle,
-    snapshot_repository: State<'_, Arc<Mutex<SnapshotRepository>>>,
-    client_repository: State<'_, Arc<Mutex<ClientRepository>>>,
-    watcher_state: State<'_, Arc<Mutex<ConfigFileWatcher>>>,
+    service: State<'_, VaultService>,
     client_id: String,
     snapshot_id: String,
-) -> Result<(), String> {
-    restore_snapshot_core(
-        app_handle,
-        Arc::clone(snapshot_repository.inner()),
-        Arc::clone(client_repository.inner()),
-        Arc::clone(watcher_state.inner()),
-        client_id,
-        snapshot_id,
-    )
+) -> VaultResult<()> {
+    let sink = TauriEventSink::new(app_handle);
+    service
+        .restore_snapshot(&client_id, &snapshot_id, &sink)
+        .map(|_| ())
 }
 
-pub fn restore_snapshot_from_tray<R: Runtime>(
-    app_handle: AppHandle<R>,
-    snapshot_repository: Arc<Mutex<SnapshotRepository>>,
-    client_repository: Arc<Mutex<ClientRepository>>,
-    watcher_state: Arc<Mutex<ConfigFileWatcher>>,
+#[tauri::command]
+pub fn restore_snapshot_merged(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
     client_id: String,
     snapshot_id: String,
-) -> Result<(), String> {
-    restore_snapshot_core(
-        app_handle,
-        snapshot_repository,
-        client_repository,
-        watcher_state,
-        client_id,
-        snapshot_id,
+    conflict_style: Option<ConflictStyle>,
+) -> VaultResult<MergeRestoreResult> {
+    let sink = TauriEventSink::new(app_handle);
+    service.restore_snapshot_merged(
+        &client_id,
+        &snapshot_id,
+        conflict_style.unwrap_or_default(),
+        &sink,
     )
 }
 
 #[tauri::command]
 pub fn delete_snapshot(
-    repository: State<'_, Arc<Mutex<SnapshotRepository>>>,
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
     client_id: String,
     snapshot_id: String,
-) -> Result<(), String> {
-    let repo = lock_snapshot_repo(&repository)?;
-    repo.delete_snapshot(&client_id, &snapshot_id)
+) -> VaultResult<()> {
+    let sink = TauriEventSink::new(app_handle);
+    service.delete_snapshot(&client_id, &snapshot_id, &sink)
 }
 
 #[tauri::command]
 pub fn rename_snapshot(
-    repository: State<'_, Arc<Mutex<SnapshotRepository>>>,
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
     client_id: String,
     snapshot_id: String,
     new_name: String,
-) -> Result<(), String> {
-    let repo = lock_snapshot_repo(&repository)?;
-    repo.rename_snapshot(&client_id, &snapshot_id, new_name)
+) -> VaultResult<()> {
+    let sink = TauriEventSink::new(app_handle);
+    service.rename_snapshot(&client_id, &snapshot_id, new_name, &sink)
 }
 
 #[tauri::command]
-pub fn set_max_snapshots(
-    repository: State<'_, Arc<Mutex<SnapshotRepository>>>,
+pub fn pin_snapshot(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
     client_id: String,
-    max: usize,
-) -> Result<(), String> {
-    let repo = lock_snapshot_repo(&repository)?;
-    repo.set_max_snapshots(&client_id, max)
+    snapshot_id: String,
+) -> VaultResult<Snapshot> {
+    let sink = TauriEventSink::new(app_handle);
+    service.set_snapshot_pinned(&client_id, &snapshot_id, true, &sink)
 }
 
 #[tauri::command]
-pub fn set_max_auto_snapshots(
-    repository: State<'_, Arc<Mutex<SnapshotRepository>>>,
+pub fn unpin_snapshot(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
     client_id: String,
-    max: usize,
-) -> Result<(), String> {
-    let repo = lock_snapshot_repo(&repository)?;
-    repo.set_max_auto_snapshots(&client_id, max)
+    snapshot_id: String,
+) -> VaultResult<Snapshot> {
+    let sink = TauriEventSink::new(app_handle);
+    service.set_snapshot_pinned(&client_id, &snapshot_id, false, &sink)
 }
 
 #[tauri::command]
-pub fn set_max_manual_snapshots(
-    repository: State<'_, Arc<Mutex<SnapshotRepository>>>,
+pub fn set_snapshot_notes(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
     client_id: String,
-    max: usize,
-) -> Result<(), String> {
-    let repo = lock_snapshot_repo(&repository)?;
-    repo.set_max_manual_snapshots(&client_id, max)
+    snapshot_id: String,
+    notes: String,
+) -> VaultResult<Snapshot> {
+    let sink = TauriEventSink::new(app_handle);
+    service.set_snapshot_notes(&client_id, &snapshot_id, notes, &sink)
 }
 
 #[tauri::command]
-pub fn refresh_tray_menu(app_handle: tauri::AppHandle) -> Result<(), String> {
-    tray::refresh_tray_menu(&app_handle).map_err(|err| err.to_string())
+pub fn set_snapshot_labels(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
+    client_id: String,
+    snapshot_id: String,
+    labels: Vec<String>,
+) -> VaultResult<Snapshot> {
+    let sink = TauriEventSink::new(app_handle);
+    service.set_snapshot_labels(&client_id, &snapshot_id, labels, &sink)
 }
 
 #[tauri::command]
-pub fn refresh_app_menu(app_handle: tauri::AppHandle) -> Result<(), String> {
-    app_menu::refresh_app_menu(&app_handle).map_err(|err| err.to_string())
+pub fn search_snapshots(
+    service: State<'_, VaultService>,
+    query: SnapshotSearchQuery,
+) -> VaultResult<Vec<SnapshotSearchHit>> {
+    service.search_snapshots(&query)
 }
 
-fn restore_snapshot_core<R: Runtime>(
-    app_handle: AppHandle<R>,
-    snapshot_repository: Arc<Mutex<SnapshotRepository>>,
-    client_repository: Arc<Mutex<ClientRepository>>,
-    watcher_state: Arc<Mutex<ConfigFileWatcher>>,
+#[tauri::command]
+pub fn export_snapshot_archive(
+    service: State<'_, VaultService>,
     client_id: String,
-    snapshot_id: String,
-) -> Result<(), String> {
-    let snapshot = {
-        let repo = lock_snapshot_arc(&snapshot_repository)?;
-        repo.restore_snapshot(&client_id, &snapshot_id)?
-    };
-
-    let client = {
-        let repo = lock_client_arc(&client_repository)?;
-        repo.get_by_id(&client_id)?
-            .ok_or_else(|| "未找到指定客户端".to_string())?
-    };
-
-    let previous_watch_path = pause_watcher(&watcher_state)?;
-
-    let write_result = if snapshot.is_multi_file() {
-        restore_multi_file_snapshot(snapshot.get_file_contents())
-    } else {
-        let target_path = client
-            .resolve_config_path(None)
-            .map_err(|err| format!("解析配置文件路径失败: {}", err))?;
-        write_files_atomically(vec![(target_path, snapshot.content.clone())])
-    };
-
-    let resume_result = resume_watcher(&watcher_state, &app_handle, previous_watch_path);
-
-    match write_result {
-        Ok(updated_paths) => {
-            if let Err(err) = resume_result {
-                return Err(err);
-            }
-            emit_config_reload_events(&app_handle, &client_id, &updated_paths);
-            Ok(())
-        }
-        Err(err) => {
-            if let Err(resume_err) = resume_result {
-                eprintln!("[Snapshot] 恢复失败后重启监听器失败: {}", resume_err);
-            }
-            Err(err)
-        }
-    }
+    snapshot_ids: Option<Vec<String>>,
+) -> VaultResult<String> {
+    service.export_snapshot_archive(&client_id, snapshot_ids)
 }
 
-fn read_client_config_files(client: &ClientConfig) -> Result<HashMap<String, String>, String> {
-    let mut contents = HashMap::new();
-    for path in &client.config_file_paths {
-        let expanded = expand_tilde(path);
-        let content = match fs::read_to_string(&expanded) {
-            Ok(value) => value,
-            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
-            Err(err) => {
-                return Err(format!("读取配置文件失败: {} ({})", path, err));
-            }
-        };
-        contents.insert(path.clone(), content);
-    }
-    Ok(contents)
+#[tauri::command]
+pub fn import_snapshot_archive(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
+    json_data: String,
+    target_client_id: Option<String>,
+) -> VaultResult<SnapshotImportResult> {
+    let sink = TauriEventSink::new(app_handle);
+    service.import_snapshot_archive(&json_data, target_client_id.as_deref(), &sink)
 }
 
-fn restore_multi_file_snapshot(contents: HashMap<String, String>) -> Result<Vec<String>, String> {
-    if contents.is_empty() {
-        return Err("快照未包含任何配置文件内容".to_string());
-    }
-    let mut entries: Vec<(String, String)> = contents.into_iter().collect();
-    entries.sort_by(|a, b| a.0.cmp(&b.0));
-    write_files_atomically(entries)
+#[tauri::command]
+pub fn set_max_snapshots(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
+    client_id: String,
+    max: usize,
+) -> VaultResult<()> {
+    let sink = TauriEventSink::new(app_handle);
+    service.set_max_snapshots(&client_id, max, &sink)
 }
 
-struct WrittenFile {
-    path: PathBuf,
-    existed: bool,
-    original_content: String,
+#[tauri::command]
+pub fn set_max_auto_snapshots(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
+    client_id: String,
+    max: usize,
+) -> VaultResult<()> {
+    let sink = TauriEventSink::new(app_handle);
+    service.set_max_auto_snapshots(&client_id, max, &sink)
 }
 
-fn write_files_atomically(entries: Vec<(String, String)>) -> Result<Vec<String>, String> {
-    let mut written: Vec<WrittenFile> = Vec::new();
-    let mut updated_paths = Vec::new();
-
-    for (path, content) in entries {
-        let expanded = expand_tilde(&path);
-        let read_result = fs::read_to_string(&expanded);
-        let (existed, original_content) = match read_result {
-            Ok(value) => (true, value),
-            Err(err) if err.kind() == ErrorKind::NotFound => (false, String::new()),
-            Err(err) => {
-                rollback_written_files(&written);
-                return Err(format!("读取配置文件失败: {} ({})", path, err));
-            }
-        };
-
-        if let Err(err) = atomic_write(&expanded, &content) {
-            rollback_written_files(&written);
-            return Err(format!("写入配置文件失败: {} ({})", path, err));
-        }
-
-        updated_paths.push(expanded.to_string_lossy().to_string());
-        written.push(WrittenFile {
-            path: expanded,
-            existed,
-            original_content,
-        });
-    }
-
-    Ok(updated_paths)
+#[tauri::command]
+pub fn set_max_manual_snapshots(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
+    client_id: String,
+    max: usize,
+) -> VaultResult<()> {
+    let sink = TauriEventSink::new(app_handle);
+    service.set_max_manual_snapshots(&client_id, max, &sink)
 }
 
-fn rollback_written_files(written: &[WrittenFile]) {
-    for file in written.iter().rev() {
-        if file.existed {
-            if let Err(err) = atomic_write(&file.path, &file.original_content) {
-                eprintln!("[Snapshot] 回滚写入失败: {} ({})", file.path.display(), err);
-            }
-        } else if file.path.exists() {
-            if let Err(err) = fs::remove_file(&file.path) {
-                eprintln!(
-                    "[Snapshot] 删除临时文件失败: {} ({})",
-                    file.path.display(),
-                    err
-                );
-            }
-        }
-    }
+#[tauri::command]
+pub fn set_snapshot_retention(
+    app_handle: AppHandle,
+    service: State<'_, VaultService>,
+    client_id: String,
+    policy: Option<RetentionPolicy>,
+) -> VaultResult<()> {
+    let sink = TauriEventSink::new(app_handle);
+    service.set_snapshot_retention(&client_id, policy, &sink)
 }
 
-fn pause_watcher(watcher_state: &Arc<Mutex<ConfigFileWatcher>>) -> Result<Option<PathBuf>, String> {
-    let mut watcher = watcher_state
-        .lock()
-        .map_err(|e| format!("获取文件监听器失败: {}", e))?;
-    let watched = watcher.current_path();
-    watcher.stop();
-    Ok(watched)
+/// 按当前界面语言设置后端自动快照的名称前缀
+#[tauri::command]
+pub fn set_auto_snapshot_labels(
+    scheduler: State<'_, AutoSnapshotScheduler>,
+    auto_prefix: String,
+    before_quit_prefix: String,
+) {
+    scheduler.set_labels(&auto_prefix, &before_quit_prefix);
 }
 
-fn resume_watcher<R: Runtime>(
-    watcher_state: &Arc<Mutex<ConfigFileWatcher>>,
-    app_handle: &AppHandle<R>,
-    path: Option<PathBuf>,
-) -> Result<(), String> {
-    if let Some(path) = path {
-        let mut watcher = watcher_state
-            .lock()
-            .map_err(|e| format!("获取文件监听器失败: {}", e))?;
-        watcher
-            .watch_file(path, app_handle.clone())
-            .map_err(|err| format!("重新启动文件监听器失败: {}", err))?;
-    }
-    Ok(())
+#[tauri::command]
+pub fn refresh_tray_menu(app_handle: tauri::AppHandle) -> VaultResult<()> {
+    tray::refresh_tray_menu(&app_handle).map_err(|err| {
+        VaultError::new(ErrorCode::MenuRefreshFailed).with_cause(ErrorCause::other(err))
+    })
 }
 
-fn emit_config_reload_events<R: Runtime>(
-    app_handle: &AppHandle<R>,
-    client_id: &str,
-    paths: &[String],
-) {
-    for path in paths {
-        let payload = ConfigReloadPayload {
-            client_id: client_id.to_string(),
-            path: path.clone(),
-        };
-        if let Err(err) = app_handle.emit(CONFIG_RELOAD_SILENT_EVENT, payload) {
-            eprintln!("[Snapshot] 发送配置刷新事件失败: {}", err);
-        }
-    }
+#[tauri::command]
+pub fn refresh_app_menu(app_handle: tauri::AppHandle) -> VaultResult<()> {
+    app_menu::refresh_app_menu(&app_handle).map_err(|err| {
+        VaultError::new(ErrorCode::MenuRefreshFailed).with_cause(ErrorCause::other(err))
+    })
 }
@@ -0,0 +1,357 @@
+//! 全局错误类型
+//!
+//! 所有仓库、服务与 Tauri 命令都返回 [`VaultError`]。序列化后前端拿到的是
+//! `{ code, message, params, cause }`：`code` 是稳定的错误码，`params` 是相关参数，
+//! `message` 只是默认的中文描述，界面应优先根据 `code` 自行本地化。
+
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+pub type VaultResult<T> = Result<T, VaultError>;
+
+macro_rules! error_codes {
+    ($($variant:ident => $code:literal, $message:literal;)+) => {
+        /// 稳定的错误码，取值一经发布不再修改
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum ErrorCode {
+            $($variant,)+
+        }
+
+        impl ErrorCode {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $code,)+
+                }
+            }
+
+            pub fn default_message(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $message,)+
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    IoReadFailed => "io.read_failed", "读取文件失败";
+    IoWriteFailed => "io.write_failed", "写入文件失败";
+    IoCreateDirFailed => "io.create_dir_failed", "创建目录失败";
+    IoRemoveFailed => "io.remove_failed", "删除文件失败";
+    IoCopyFailed => "io.copy_failed", "复制文件失败";
+    DataDirUnavailable => "io.data_dir_unavailable", "无法定位应用数据目录";
+    HomeDirUnavailable => "io.home_dir_unavailable", "无法获取用户主目录";
+    ParseFailed => "data.parse_failed", "解析数据失败";
+    SerializeFailed => "data.serialize_failed", "序列化数据失败";
+    LockPoisoned => "internal.lock_poisoned", "获取锁失败";
+    PathEmpty => "path.empty", "路径不能为空";
+    PathInvalid => "path.invalid", "无法解析路径";
+    DatabaseFailed => "storage.database_failed", "数据库操作失败";
+    GitFailed => "storage.git_failed", "Git 快照仓库操作失败";
+
+    ClientNotFound => "client.not_found", "未找到指定客户端";
+    ClientAlreadyExists => "client.already_exists", "客户端 ID 已存在";
+    ClientBuiltinUndeletable => "client.builtin_undeletable", "内置客户端不允许删除";
+    ClientIdEmpty => "client.id_empty", "客户端 ID 不能为空";
+    ClientNameEmpty => "client.name_empty", "客户端名称不能为空";
+    ClientNoConfigPaths => "client.no_config_paths", "客户端未配置任何配置文件路径";
+    ClientConfigPathEmpty => "client.config_path_empty", "配置文件路径不能为空";
+    ClientConfigPathNotFound => "client.config_path_not_found", "指定的配置文件路径未在客户端配置中找到";
+    ClientActivePathNotListed => "client.active_path_not_listed", "激活的配置文件路径必须包含在路径列表中";
+    ClientConfigContentMissing => "client.config_content_missing", "缺少配置文件的内容";
+    ClientConfigPathPattern => "client.config_path_pattern", "通配模式不能作为单个配置文件读写，请指定其匹配的文件";
+    ConfigInvalidSyntax => "config.invalid_syntax", "配置文件存在语法错误";
+    ConfigNotStructured => "config.not_structured", "该配置文件不是 JSON、TOML 或 YAML 格式";
+    ConfigKeyPathEmpty => "config.key_path_empty", "键路径不能为空";
+    ConfigKeyPathConflict => "config.key_path_conflict", "键路径经过的值不是对象或数组";
+    ConfigValueUnsupported => "config.value_unsupported", "该格式无法表示此值";
+
+    PromptNotFound => "prompt.not_found", "未找到指定提示词";
+    PromptNameEmpty => "prompt.name_empty", "提示词名称不能为空";
+    PromptRevisionNotFound => "prompt.revision_not_found", "未找到指定的提示词版本";
+
+    TemplateNotFound => "template.not_found", "指定模板不存在";
+    TemplateApplyFailed => "template.apply_failed", "应用模板失败";
+    CompositionNotFound => "composition.not_found", "未找到指定组合";
+    CompositionNameEmpty => "composition.name_empty", "组合名称不能为空";
+    CompositionEmpty => "composition.empty", "组合中至少需要一个提示词";
+    VariableUnknown => "variable.unknown", "内容引用了未定义的变量";
+    VariableMissingValue => "variable.missing_value", "变量没有可用的取值";
+    VariableNameInvalid => "variable.name_invalid", "变量名只能包含字母、数字、下划线、点与连字符";
+    VariableReserved => "variable.reserved", "变量名与内置变量重名";
+    VariableDuplicate => "variable.duplicate", "变量名重复";
+    VariableNotDefined => "variable.not_defined", "项目覆盖的变量未在库中定义";
+    ProjectPathEmpty => "project.path_empty", "项目路径不能为空";
+    ProjectNoConfigFiles => "project.no_config_files", "指定项目中未找到可导入的配置文件";
+    ProjectSelectionCancelled => "project.selection_cancelled", "用户取消了项目目录选择";
+    ProjectNotDirectory => "project.not_directory", "项目路径不存在或不是目录";
+    BackupNotFound => "backup.not_found", "指定备份不存在";
+    BackupMetadataMissing => "backup.metadata_missing", "备份元数据缺失";
+
+    SnapshotNotFound => "snapshot.not_found", "未找到指定快照";
+    SnapshotAmbiguousId => "snapshot.ambiguous_id", "快照 ID 前缀不唯一";
+    SnapshotIdEmpty => "snapshot.id_empty", "快照 ID 不能为空";
+    SnapshotNameEmpty => "snapshot.name_empty", "快照名称不能为空";
+    SnapshotUnchanged => "snapshot.unchanged", "内容未变化,跳过快照创建";
+    SnapshotInvalidLimit => "snapshot.invalid_limit", "最大快照数量必须大于 0";
+    SnapshotInvalidRetention => "snapshot.invalid_retention", "保留策略的各档时长必须逐档递增";
+    SnapshotEmpty => "snapshot.empty", "快照未包含任何配置文件内容";
+    SnapshotArchiveInvalid => "snapshot.archive_invalid", "快照归档格式无效";
+    SnapshotArchiveUnsupported => "snapshot.archive_unsupported", "快照归档版本过新，请升级应用后再导入";
+    SnapshotArchiveCorrupted => "snapshot.archive_corrupted", "快照归档内容与清单不一致";
+    SnapshotInvalidPattern => "snapshot.invalid_pattern", "快照搜索的正则表达式无效";
+
+    ImportEmpty => "import.empty", "导入数据不能为空";
+    ImportInvalidJson => "import.invalid_json", "解析导入 JSON 失败";
+    ImportNotArray => "import.not_array", "导入数据必须是数组";
+    ImportEntryNotObject => "import.entry_not_object", "导入条目必须是对象";
+    ImportFieldMissing => "import.field_missing", "导入条目缺少必填字段";
+    ImportFieldEmpty => "import.field_empty", "导入条目的字段不能为空";
+    ImportFieldInvalid => "import.field_invalid", "导入条目的字段格式错误";
+
+    WatcherNoPaths => "watcher.no_paths", "未提供任何可监听的配置文件路径";
+    WatcherStartFailed => "watcher.start_failed", "创建文件监听器失败";
+    WatcherWatchFailed => "watcher.watch_failed", "监听文件失败";
+
+    AppStateInvalidWindowSize => "app_state.invalid_window_size", "窗口尺寸无效";
+    AppStateCloseBehaviorEmpty => "app_state.close_behavior_empty", "关闭行为不能为空";
+    MenuRefreshFailed => "menu.refresh_failed", "刷新菜单失败";
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// 底层错误的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CauseKind {
+    Io,
+    Serde,
+    Database,
+    Git,
+    Other,
+}
+
+/// 底层 io/serde 错误的可序列化描述
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorCause {
+    pub kind: CauseKind,
+    /// io 错误为 [`io::ErrorKind`] 名称（如 `NotFound`），serde 错误为错误类别（如 `syntax`），
+    /// 数据库错误为 SQLite 错误码（如 `DatabaseBusy`），Git 错误为 libgit2 错误码（如 `NotFound`）
+    pub detail: Option<String>,
+    pub message: String,
+}
+
+impl ErrorCause {
+    pub fn other(err: impl fmt::Display) -> Self {
+        Self {
+            kind: CauseKind::Other,
+            detail: None,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<io::Error> for ErrorCause {
+    fn from(err: io::Error) -> Self {
+        Self {
+            kind: CauseKind::Io,
+            detail: Some(format!("{:?}", err.kind())),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ErrorCause {
+    fn from(err: serde_json::Error) -> Self {
+        let category = match err.classify() {
+            serde_json::error::Category::Io => "io",
+            serde_json::error::Category::Syntax => "syntax",
+            serde_json::error::Category::Data => "data",
+            serde_json::error::Category::Eof => "eof",
+        };
+        Self {
+            kind: CauseKind::Serde,
+            detail: Some(category.to_string()),
+            message: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for ErrorCause {
+    fn from(err: rusqlite::Error) -> Self {
+        Self {
+            kind: CauseKind::Database,
+            detail: err.sqlite_error_code().map(|code| format!("{:?}", code)),
+            message: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "git-snapshots")]
+impl From<git2::Error> for ErrorCause {
+    fn from(err: git2::Error) -> Self {
+        Self {
+            kind: CauseKind::Git,
+            detail: Some(format!("{:?}", err.code())),
+            message: err.message().to_string(),
+        }
+    }
+}
+
+impl From<VaultError> for ErrorCause {
+    fn from(err: VaultError) -> Self {
+        Self::other(err)
+    }
+}
+
+/// 应用统一错误：稳定错误码 + 参数 + 底层原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VaultError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub params: BTreeMap<&'static str, String>,
+    pub cause: Option<ErrorCause>,
+}
+
+impl VaultError {
+    pub fn new(code: ErrorCode) -> Self {
+        Self {
+            code,
+            message: code.default_message().to_string(),
+            params: BTreeMap::new(),
+            cause: None,
+        }
+    }
+
+    pub fn with_param(mut self, key: &'static str, value: impl fmt::Display) -> Self {
+        self.params.insert(key, value.to_string());
+        self
+    }
+
+    pub fn with_path(self, path: impl AsRef<Path>) -> Self {
+        self.with_param("path", path.as_ref().display())
+    }
+
+    pub fn with_cause(mut self, cause: impl Into<ErrorCause>) -> Self {
+        self.cause = Some(cause.into());
+        self
+    }
+
+    pub fn read_failed(path: impl AsRef<Path>, err: io::Error) -> Self {
+        Self::new(ErrorCode::IoReadFailed)
+            .with_path(path)
+            .with_cause(err)
+    }
+
+    pub fn write_failed(path: impl AsRef<Path>, err: io::Error) -> Self {
+        Self::new(ErrorCode::IoWriteFailed)
+            .with_path(path)
+            .with_cause(err)
+    }
+
+    pub fn create_dir_failed(path: impl AsRef<Path>, err: io::Error) -> Self {
+        Self::new(ErrorCode::IoCreateDirFailed)
+            .with_path(path)
+            .with_cause(err)
+    }
+
+    pub fn remove_failed(path: impl AsRef<Path>, err: io::Error) -> Self {
+        Self::new(ErrorCode::IoRemoveFailed)
+            .with_path(path)
+            .with_cause(err)
+    }
+
+    pub fn parse_failed(path: impl AsRef<Path>, err: serde_json::Error) -> Self {
+        Self::new(ErrorCode::ParseFailed)
+            .with_path(path)
+            .with_cause(err)
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn database_failed(err: rusqlite::Error) -> Self {
+        Self::new(ErrorCode::DatabaseFailed).with_cause(err)
+    }
+
+    #[cfg(feature = "git-snapshots")]
+    pub fn git_failed(err: git2::Error) -> Self {
+        Self::new(ErrorCode::GitFailed).with_cause(err)
+    }
+
+    /// `target` 描述被序列化的数据，例如 `prompts`
+    pub fn serialize_failed(target: &'static str, err: serde_json::Error) -> Self {
+        Self::new(ErrorCode::SerializeFailed)
+            .with_param("target", target)
+            .with_cause(err)
+    }
+
+    pub fn lock_poisoned(resource: &'static str) -> Self {
+        Self::new(ErrorCode::LockPoisoned).with_param("resource", resource)
+    }
+
+    pub fn client_not_found(client_id: impl fmt::Display) -> Self {
+        Self::new(ErrorCode::ClientNotFound).with_param("client_id", client_id)
+    }
+
+    pub fn snapshot_not_found(
+        client_id: impl fmt::Display,
+        snapshot_id: impl fmt::Display,
+    ) -> Self {
+        Self::new(ErrorCode::SnapshotNotFound)
+            .with_param("client_id", client_id)
+            .with_param("snapshot_id", snapshot_id)
+    }
+
+    /// 导入数据中下标为 `index`（从 0 开始）的条目的字段错误，`index` 参数按从 1 开始的序号展示
+    pub fn import_field(
+        code: ErrorCode,
+        entity: &'static str,
+        index: usize,
+        field: impl fmt::Display,
+    ) -> Self {
+        Self::new(code)
+            .with_param("entity", entity)
+            .with_param("index", index + 1)
+            .with_param("field", field)
+    }
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if !self.params.is_empty() {
+            let params = self
+                .params
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(f, " ({})", params)?;
+        }
+        if let Some(cause) = &self.cause {
+            write!(f, ": {}", cause.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VaultError {}
+
+impl From<ErrorCode> for VaultError {
+    fn from(code: ErrorCode) -> Self {
+        Self::new(code)
+    }
+}
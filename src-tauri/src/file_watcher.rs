@@ -1,14 +1,40 @@
+use crate::auto_snapshot::AutoSnapshotScheduler;
+use crate::error::{ErrorCause, ErrorCode, VaultError, VaultResult};
+use crate::storage::BlobStore;
+use crate::utils::path_utils::PathPattern;
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde_json::json;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
-use tauri::{AppHandle, Emitter, Runtime};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 
 pub const LEGACY_CLIENT_ID: &str = "__legacy_config_client__";
 
+/// 同一文件的连续事件（编辑器保存、原子替换）合并为一次通知
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// 应用自身写入登记的有效期，过期后同样内容的变化也会通知前端
+const SELF_WRITE_TTL: Duration = Duration::from_secs(5);
+
+/// 监听配置文件变化并通知前端
+///
+/// 所有客户端共用一个底层监听器，可以同时监听多个客户端；事件按路径找到所属客户端后分别通知。
+/// 监听的是配置文件所在目录并按文件名过滤，因此编辑器先写临时文件再重命名覆盖时不会丢失监听，
+/// 尚不存在的文件也可以监听：目录不存在时监听最近的已存在上级目录，目录创建后自动切换。
+/// 路径也可以是通配模式，此时监听模式的基准目录（需要时递归监听），新出现的匹配文件同样通知。
+///
+/// 监听器始终保持运行：应用自身写入配置文件前通过 [`ConfigFileWatcher::expect_write`]
+/// 登记写入内容的哈希，防抖结束时文件内容与登记一致的事件会被丢弃，不会被当作外部修改。
 pub struct ConfigFileWatcher {
     watcher: Option<RecommendedWatcher>,
     watched_paths: HashMap<String, Vec<PathBuf>>,
+    /// 底层监听器实际监听的目录，值为是否递归监听
+    armed_dirs: BTreeMap<PathBuf, bool>,
+    events: WatchEvents,
 }
 
 impl ConfigFileWatcher {
@@ -16,25 +42,66 @@ impl ConfigFileWatcher {
         Self {
             watcher: None,
             watched_paths: HashMap::new(),
+            armed_dirs: BTreeMap::new(),
+            events: WatchEvents::default(),
         }
     }
 
-    pub fn current_path(&self) -> Option<PathBuf> {
-        self.watched_paths
-            .values()
-            .next()
-            .and_then(|paths| paths.first().cloned())
+    /// 登记应用即将写入 `path` 的内容，写入产生的事件不再通知前端
+    pub fn expect_write(&self, path: &Path, content: &str) {
+        self.expect(path, Some(BlobStore::hash(content)));
     }
 
+    /// 登记应用即将删除 `path`，删除产生的事件不再通知前端
+    pub fn expect_removal(&self, path: &Path) {
+        self.expect(path, None);
+    }
+
+    fn expect(&self, path: &Path, hash: Option<String>) {
+        let Ok(mut expected) = self.events.expected.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        expected.retain(|_, writes| {
+            writes.retain(|write| write.expires_at > now);
+            !writes.is_empty()
+        });
+        expected
+            .entry(normalize_path(path))
+            .or_default()
+            .push(ExpectedWrite {
+                hash,
+                expires_at: now + SELF_WRITE_TTL,
+            });
+    }
+
+    /// 以磁盘上的现有内容替换对 `path` 的写入登记，用于写入失败或已回滚的文件
+    pub fn expect_current(&self, path: &Path) {
+        if let Ok(mut expected) = self.events.expected.lock() {
+            expected.remove(&normalize_path(path));
+        }
+        if let Ok(content) = fs::read_to_string(path) {
+            self.expect_write(path, &content);
+        }
+    }
+
+    /// 当前监听中的客户端
+    pub fn client_ids(&self) -> Vec<String> {
+        self.watched_paths.keys().cloned().collect()
+    }
+
+    /// 开始监听客户端的配置文件，已在监听的客户端改为监听新的路径列表；不影响其他客户端
+    ///
+    /// 文件不必已经存在，创建后会收到 `created` 事件；`paths` 中的通配模式匹配到新文件时同样如此。
     pub fn watch_files<R: Runtime>(
         &mut self,
         client_id: String,
         paths: Vec<PathBuf>,
         app_handle: AppHandle<R>,
-    ) -> Result<(), String> {
+    ) -> VaultResult<()> {
         let unique_paths = Self::dedup_paths(paths);
         if unique_paths.is_empty() {
-            return Err("未提供任何可监听的配置文件路径".to_string());
+            return Err(ErrorCode::WatcherNoPaths.into());
         }
 
         if let Some(existing) = self.watched_paths.get(&client_id) {
@@ -43,89 +110,174 @@ impl ConfigFileWatcher {
             }
         }
 
-        self.stop();
-
-        let fallback_path = unique_paths
-            .first()
-            .map(|path| path.to_string_lossy().to_string())
-            .unwrap_or_default();
-        let event_client_id = client_id.clone();
-        let fallback_for_event = fallback_path.clone();
-
-        let mut watcher =
-            notify::recommended_watcher(move |res: Result<Event, notify::Error>| match res {
-                Ok(event) => {
-                    if matches!(
-                        event.kind,
-                        EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
-                    ) {
-                        let mut affected_paths: Vec<String> = event
-                            .paths
-                            .iter()
-                            .map(|p| p.to_string_lossy().to_string())
-                            .collect();
-
-                        if affected_paths.is_empty() && !fallback_for_event.is_empty() {
-                            affected_paths.push(fallback_for_event.clone());
-                        }
-
-                        for changed_path in affected_paths {
-                            let payload = json!({
-                                "client_id": event_client_id.clone(),
-                                "path": changed_path,
-                            });
-                            if let Err(err) = app_handle.emit("config-file-changed", payload) {
-                                eprintln!(
-                                    "[FileWatcher] Failed to emit config-file-changed event: {}",
-                                    err
-                                );
-                            }
-                        }
-                    }
-                }
-                Err(err) => {
-                    eprintln!("[FileWatcher] 文件监听器错误: {}", err);
-                }
-            })
-            .map_err(|e| format!("创建文件监听器失败: {}", e))?;
-
-        for path in &unique_paths {
-            watcher
-                .watch(path, RecursiveMode::NonRecursive)
-                .map_err(|e| format!("监听文件失败: {} ({})", path.display(), e))?;
+        if self.watcher.is_none() {
+            self.watcher = Some(self.events.create_watcher(app_handle)?);
         }
-
-        self.watcher = Some(watcher);
-        self.watched_paths.insert(client_id, unique_paths);
-        return Ok(());
+        let previous = self.watched_paths.insert(client_id.clone(), unique_paths);
+        if let Err(err) = self.arm() {
+            match previous {
+                Some(previous) => self.watched_paths.insert(client_id, previous),
+                None => self.watched_paths.remove(&client_id),
+            };
+            if let Err(rearm_err) = self.arm() {
+                eprintln!("[FileWatcher] 恢复监听失败: {}", rearm_err);
+            }
+            self.sync_files(false);
+            return Err(err);
+        }
+        self.sync_files(false);
+        Ok(())
     }
 
     pub fn watch_file<R: Runtime>(
         &mut self,
         path: PathBuf,
         app_handle: AppHandle<R>,
-    ) -> Result<(), String> {
+    ) -> VaultResult<()> {
         self.watch_files(LEGACY_CLIENT_ID.to_string(), vec![path], app_handle)
     }
 
+    /// 停止监听单个客户端，仍被其他客户端使用的目录继续监听
+    pub fn unwatch_client(&mut self, client_id: &str) {
+        if self.watched_paths.remove(client_id).is_none() {
+            return;
+        }
+        if let Err(err) = self.arm() {
+            eprintln!("[FileWatcher] 更新监听目录失败: {}", err);
+        }
+        self.sync_files(false);
+    }
+
+    /// 停止监听全部客户端
     pub fn stop(&mut self) {
-        if let Some(mut watcher) = self.watcher.take() {
-            let watched: Vec<PathBuf> = self
-                .watched_paths
-                .values()
-                .flat_map(|paths| paths.iter().cloned())
-                .collect();
-            for path in watched {
-                if let Err(err) = watcher.unwatch(&path) {
+        self.watched_paths.clear();
+        if let Err(err) = self.arm() {
+            eprintln!("[FileWatcher] 更新监听目录失败: {}", err);
+        }
+        self.watcher = None;
+        self.sync_files(false);
+    }
+
+    /// 目录被创建、删除或重命名后重新选择监听目录，返回存在状态因此发生变化的文件
+    fn rearm(&mut self) -> Vec<PathBuf> {
+        if let Err(err) = self.arm() {
+            eprintln!("[FileWatcher] 重新监听目录失败: {}", err);
+        }
+        self.sync_files(true)
+    }
+
+    /// 让底层监听器监听的目录与当前文件列表一致；单个目录失败不影响其他目录，返回第一个错误
+    fn arm(&mut self) -> VaultResult<()> {
+        let mut desired: BTreeMap<PathBuf, bool> = BTreeMap::new();
+        for path in self.watched_paths.values().flatten() {
+            let (dir, recursive) = match PathPattern::parse(&path.to_string_lossy()) {
+                // 通配模式监听基准目录，需要匹配子目录中的文件时递归监听
+                Some(pattern) if pattern.base().is_dir() => {
+                    (Some(pattern.base().to_path_buf()), pattern.is_recursive())
+                }
+                Some(pattern) => (watch_dir(pattern.base()), false),
+                None => (watch_dir(path), false),
+            };
+            if let Some(dir) = dir {
+                *desired.entry(dir).or_default() |= recursive;
+            }
+        }
+        let Some(watcher) = self.watcher.as_mut() else {
+            self.armed_dirs.clear();
+            return Ok(());
+        };
+
+        for (dir, recursive) in &self.armed_dirs {
+            if desired.get(dir) == Some(recursive) {
+                continue;
+            }
+            // 目录已被删除时底层监听随之失效，不必报告
+            if let Err(err) = watcher.unwatch(dir) {
+                if dir.exists() {
                     eprintln!(
-                        "[FileWatcher] 停止监听文件失败: {} ({})",
-                        path.display(),
+                        "[FileWatcher] 停止监听目录失败: {} ({})",
+                        dir.display(),
                         err
                     );
                 }
             }
         }
-        self.watched_paths.clear();
+        self.armed_dirs
+            .retain(|dir, recursive| desired.get(dir) == Some(recursive));
+
+        let mut first_error = None;
+        for (dir, recursive) in desired {
+            if self.armed_dirs.contains_key(&dir) {
+                continue;
+            }
+            let mode = if recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            match watcher.watch(&dir, mode) {
+                Ok(()) => {
+                    self.armed_dirs.insert(dir, recursive);
+                }
+                Err(err) => {
+                    first_error.get_or_insert_with(|| {
+                        VaultError::new(ErrorCode::WatcherWatchFailed)
+                            .with_path(&dir)
+                            .with_cause(ErrorCause::other(err))
+                    });
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// 按当前监听列表重建文件索引，供监听回调查找事件所属的客户端
+    ///
+    /// 已在索引中的文件保留上次确认的存在状态，返回其中实际存在状态已经不同的文件。
+    /// `discovered` 为 `true` 时，通配模式新匹配到的文件视为之前不存在，随后补发 `created` 事件。
+    fn sync_files(&self, discovered: bool) -> Vec<PathBuf> {
+        let Ok(mut files) = self.events.files.lock() else {
+            return Vec::new();
+        };
+        let previous = std::mem::take(&mut *files);
+        let mut patterns: Vec<WatchedPattern> = Vec::new();
+        for (client_id, paths) in &self.watched_paths {
+            for path in paths {
+                let Some(pattern) = PathPattern::parse(&path.to_string_lossy()) else {
+                    track_file(&mut files, &previous, path, client_id, path.exists());
+                    continue;
+                };
+                for file in pattern.files() {
+                    track_file(&mut files, &previous, &file, client_id, !discovered);
+                }
+                // 上次存在、已不再匹配到的文件继续保留，以便补发删除事件
+                let vanished: Vec<PathBuf> = previous
+                    .values()
+                    .filter(|file| file.exists && pattern.matches(&file.path))
+                    .map(|file| file.path.clone())
+                    .collect();
+                for file in vanished {
+                    track_file(&mut files, &previous, &file, client_id, false);
+                }
+                match patterns
+                    .iter_mut()
+                    .find(|watched| watched.pattern == pattern)
+                {
+                    Some(watched) => {
+                        watched.clients.insert(client_id.clone());
+                    }
+                    None => patterns.push(WatchedPattern::new(pattern, client_id)),
+                }
+            }
+        }
+        if let Ok(mut watched_patterns) = self.events.patterns.lock() {
+            *watched_patterns = patterns;
+        }
+        files
+            .values()
+            .filter(|file| file.exists != file.path.exists())
+            .map(|file| file.path.clone())
+            .collect()
     }
 
     fn dedup_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
@@ -143,3 +295,380 @@ impl ConfigFileWatcher {
         left.len() == right.len() && left.iter().zip(right).all(|(a, b)| a == b)
     }
 }
+
+/// 文件变化的类型，作为 `config-file-changed` 事件的 `kind` 字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+impl ChangeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Deleted => "deleted",
+            ChangeKind::Renamed => "renamed",
+        }
+    }
+}
+
+struct WatchedFile {
+    /// 客户端配置中的路径，通知前端时使用
+    path: PathBuf,
+    clients: BTreeSet<String>,
+    /// 最近一次确认时文件是否存在，用于在防抖结束时判断变化类型
+    exists: bool,
+}
+
+/// 通配模式及使用它的客户端
+struct WatchedPattern {
+    /// 客户端配置中的模式，通知前端时使用
+    pattern: PathPattern,
+    /// 基准目录解析符号链接后的模式，用于匹配事件路径
+    resolved: PathPattern,
+    clients: BTreeSet<String>,
+}
+
+impl WatchedPattern {
+    fn new(pattern: PathPattern, client_id: &str) -> Self {
+        let resolved = fs::canonicalize(pattern.base())
+            .map(|base| pattern.with_base(base))
+            .unwrap_or_else(|_| pattern.clone());
+        Self {
+            pattern,
+            resolved,
+            clients: BTreeSet::from([client_id.to_string()]),
+        }
+    }
+
+    /// 事件路径匹配时返回通知前端使用的路径
+    fn match_path(&self, path: &Path) -> Option<PathBuf> {
+        if self.pattern.matches(path) {
+            return Some(path.to_path_buf());
+        }
+        if !self.resolved.matches(path) {
+            return None;
+        }
+        path.strip_prefix(self.resolved.base())
+            .ok()
+            .map(|relative| self.pattern.base().join(relative))
+    }
+
+    /// 基准目录的上级目录，或递归模式下基准目录中的子目录；它们变化后需要重新展开模式
+    fn covers_dir(&self, path: &Path) -> bool {
+        [&self.pattern, &self.resolved].iter().any(|pattern| {
+            pattern.base().starts_with(path)
+                || (pattern.is_recursive() && path.starts_with(pattern.base()) && path.is_dir())
+        })
+    }
+}
+
+struct ExpectedWrite {
+    /// 预期内容的哈希，`None` 表示预期文件被删除
+    hash: Option<String>,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct PendingChange {
+    /// 文件被重命名移走
+    renamed: bool,
+    /// 重命名后的路径，平台未报告时为空
+    new_path: Option<PathBuf>,
+    deadline: Option<Instant>,
+}
+
+/// 监听回调与 [`ConfigFileWatcher`] 共享的状态，回调中不需要获取监听器本身的锁
+#[derive(Clone, Default)]
+struct WatchEvents {
+    /// 应用自身写入的预期内容哈希
+    expected: Arc<Mutex<HashMap<PathBuf, Vec<ExpectedWrite>>>>,
+    /// 等待防抖结束的文件，以及需要重新选择监听目录的目录
+    pending: Arc<Mutex<HashMap<PathBuf, PendingChange>>>,
+    /// 监听中的文件及使用它的客户端，多个客户端可能共用同一个文件
+    files: Arc<Mutex<HashMap<PathBuf, WatchedFile>>>,
+    /// 监听中的通配模式；需要同时持有时先获取 `files` 的锁
+    patterns: Arc<Mutex<Vec<WatchedPattern>>>,
+}
+
+impl WatchEvents {
+    fn create_watcher<R: Runtime>(
+        &self,
+        app_handle: AppHandle<R>,
+    ) -> VaultResult<RecommendedWatcher> {
+        let events = self.clone();
+        notify::recommended_watcher(move |res: Result<Event, notify::Error>| match res {
+            Ok(event) => events.handle(&app_handle, event),
+            Err(err) => {
+                eprintln!("[FileWatcher] 文件监听器错误: {}", err);
+            }
+        })
+        .map_err(|e| {
+            VaultError::new(ErrorCode::WatcherStartFailed).with_cause(ErrorCause::other(e))
+        })
+    }
+
+    /// 在监听线程中执行，只记录事件，读取文件与发送通知都在防抖线程中完成
+    fn handle<R: Runtime>(&self, app_handle: &AppHandle<R>, event: Event) {
+        match event.kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                let (from, to) = (&event.paths[0], &event.paths[1]);
+                self.queue(app_handle, from, true, Some(to.clone()));
+                self.queue(app_handle, to, false, None);
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From | RenameMode::Any)) => {
+                for path in &event.paths {
+                    self.queue(app_handle, path, true, None);
+                }
+            }
+            EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_) => {
+                for path in &event.paths {
+                    self.queue(app_handle, path, false, None);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 监听中的文件、通配模式匹配的新文件，或相关目录（目录变化时需要重新选择监听目录）
+    fn is_relevant(&self, path: &Path) -> bool {
+        let Ok(files) = self.files.lock() else {
+            return false;
+        };
+        if files
+            .iter()
+            .any(|(key, file)| key.starts_with(path) || file.path.starts_with(path))
+        {
+            return true;
+        }
+        let Ok(patterns) = self.patterns.lock() else {
+            return false;
+        };
+        patterns
+            .iter()
+            .any(|watched| watched.match_path(path).is_some() || watched.covers_dir(path))
+    }
+
+    /// 记录一次文件事件；到期前再次变化会顺延，每个路径最多一个等待线程
+    fn queue<R: Runtime>(
+        &self,
+        app_handle: &AppHandle<R>,
+        path: &Path,
+        renamed: bool,
+        new_path: Option<PathBuf>,
+    ) {
+        let path = normalize_path(path);
+        if !self.is_relevant(&path) {
+            return;
+        }
+        let Ok(mut pending) = self.pending.lock() else {
+            eprintln!("[FileWatcher] 获取防抖队列失败");
+            return;
+        };
+        let waiting = pending.contains_key(&path);
+        let change = pending.entry(path.clone()).or_default();
+        change.renamed |= renamed;
+        if new_path.is_some() {
+            change.new_path = new_path;
+        }
+        change.deadline = Some(Instant::now() + DEBOUNCE);
+        if waiting {
+            return;
+        }
+        drop(pending);
+
+        let events = self.clone();
+        let app_handle = app_handle.clone();
+        thread::spawn(move || loop {
+            let wait = {
+                let Ok(mut pending) = events.pending.lock() else {
+                    return;
+                };
+                let Some(deadline) = pending.get(&path).and_then(|change| change.deadline) else {
+                    return;
+                };
+                let wait = deadline.saturating_duration_since(Instant::now());
+                if wait.is_zero() {
+                    let change = pending.remove(&path);
+                    drop(pending);
+                    if let Some(change) = change {
+                        events.flush(&app_handle, &path, change);
+                    }
+                    return;
+                }
+                wait
+            };
+            thread::sleep(wait);
+        });
+    }
+
+    /// 防抖结束：按文件前后的存在状态判断变化类型，不是应用自身写入时通知前端并安排自动快照
+    fn flush<R: Runtime>(&self, app_handle: &AppHandle<R>, path: &Path, change: PendingChange) {
+        let Some((reported_path, clients, kind)) = self.settle(path, &change) else {
+            if !self.is_watched_file(path) {
+                self.rearm(app_handle);
+            }
+            return;
+        };
+        if kind != ChangeKind::Renamed && self.is_self_write(path) {
+            return;
+        }
+
+        let scheduler = app_handle.try_state::<AutoSnapshotScheduler>();
+        for client_id in clients {
+            let payload = json!({
+                "client_id": client_id,
+                "path": reported_path.to_string_lossy(),
+                "kind": kind.as_str(),
+                "new_path": change.new_path.as_ref().map(|path| path.to_string_lossy()),
+            });
+            if let Err(err) = app_handle.emit("config-file-changed", payload) {
+                eprintln!(
+                    "[FileWatcher] Failed to emit config-file-changed event: {}",
+                    err
+                );
+            }
+            if let Some(scheduler) = scheduler.as_ref() {
+                scheduler.schedule(app_handle, &client_id);
+            }
+        }
+    }
+
+    fn is_watched_file(&self, path: &Path) -> bool {
+        self.files
+            .lock()
+            .map(|files| files.contains_key(path))
+            .unwrap_or(false)
+    }
+
+    /// 更新文件的存在状态并得出变化类型；不是监听中的文件或前后都不存在时返回 `None`
+    ///
+    /// 通配模式新匹配到的文件先以“之前不存在”的状态加入索引。
+    fn settle(
+        &self,
+        path: &Path,
+        change: &PendingChange,
+    ) -> Option<(PathBuf, Vec<String>, ChangeKind)> {
+        let mut files = self.files.lock().ok()?;
+        if !files.contains_key(path) {
+            let patterns = self.patterns.lock().ok()?;
+            let mut matched = patterns.iter().filter_map(|watched| {
+                watched
+                    .match_path(path)
+                    .map(|reported| (reported, &watched.clients))
+            });
+            let (reported, clients) = matched.next()?;
+            let mut clients = clients.clone();
+            for (_, others) in matched {
+                clients.extend(others.iter().cloned());
+            }
+            files.insert(
+                path.to_path_buf(),
+                WatchedFile {
+                    path: reported,
+                    clients,
+                    exists: false,
+                },
+            );
+        }
+        let file = files.get_mut(path)?;
+        let existed = file.exists;
+        file.exists = file.path.exists();
+        let kind = match (existed, file.exists) {
+            (false, true) => ChangeKind::Created,
+            (true, true) => ChangeKind::Modified,
+            (true, false) if change.renamed => ChangeKind::Renamed,
+            (true, false) => ChangeKind::Deleted,
+            (false, false) => return None,
+        };
+        Some((
+            file.path.clone(),
+            file.clients.iter().cloned().collect(),
+            kind,
+        ))
+    }
+
+    /// 上级目录发生变化：重新选择监听目录，并为因此出现或消失的文件补发事件
+    fn rearm<R: Runtime>(&self, app_handle: &AppHandle<R>) {
+        let Some(watcher) = app_handle.try_state::<Arc<Mutex<ConfigFileWatcher>>>() else {
+            return;
+        };
+        let changed = match watcher.lock() {
+            Ok(mut watcher) => watcher.rearm(),
+            Err(_) => {
+                eprintln!("[FileWatcher] 获取文件监听器失败");
+                return;
+            }
+        };
+        for path in changed {
+            self.queue(app_handle, &path, false, None);
+        }
+    }
+
+    /// 文件当前内容（或已不存在）是否与未过期的登记一致；不一致时说明文件已被外部修改，清除登记
+    fn is_self_write(&self, path: &Path) -> bool {
+        let Ok(mut expected) = self.expected.lock() else {
+            return false;
+        };
+        let Some(writes) = expected.get_mut(path) else {
+            return false;
+        };
+        let now = Instant::now();
+        writes.retain(|write| write.expires_at > now);
+        let current = fs::read_to_string(path)
+            .ok()
+            .map(|content| BlobStore::hash(&content));
+        let matched = writes.iter().any(|write| write.hash == current);
+        if !matched {
+            expected.remove(path);
+        }
+        matched
+    }
+}
+
+/// 把文件加入索引；已在索引中的文件沿用上次确认的存在状态，否则使用 `exists`
+fn track_file(
+    files: &mut HashMap<PathBuf, WatchedFile>,
+    previous: &HashMap<PathBuf, WatchedFile>,
+    path: &Path,
+    client_id: &str,
+    exists: bool,
+) {
+    let key = normalize_path(path);
+    // 上级目录创建后规范化结果可能改变，按原始路径找回旧状态
+    let exists = previous
+        .get(&key)
+        .or_else(|| previous.values().find(|file| file.path == path))
+        .map_or(exists, |file| file.exists);
+    files
+        .entry(key)
+        .or_insert_with(|| WatchedFile {
+            path: path.to_path_buf(),
+            clients: BTreeSet::new(),
+            exists,
+        })
+        .clients
+        .insert(client_id.to_string());
+}
+
+/// 文件所在目录；目录尚不存在时取最近的已存在上级目录
+fn watch_dir(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// 统一事件路径与登记路径的形式；macOS 上事件中的路径已解析符号链接
+fn normalize_path(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
@@ -1,33 +1,32 @@
 pub mod app_menu;
+pub mod auto_snapshot;
+pub mod client_catalog;
 pub mod commands;
+pub mod error;
 pub mod file_watcher;
 pub mod models;
+pub mod service;
 pub mod storage;
 pub mod tray;
 pub mod utils;
 
 use std::sync::{Arc, Mutex};
 
+use auto_snapshot::AutoSnapshotScheduler;
 use commands::ensure_app_dir;
 use file_watcher::ConfigFileWatcher;
-use storage::client_repository::ClientRepository;
-use storage::prompt_repository::PromptRepository;
+use service::VaultService;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let data_dir = ensure_app_dir().expect("初始化应用目录失败");
-    let prompt_repository = Arc::new(Mutex::new(
-        PromptRepository::new(data_dir.clone()).expect("初始化提示词存储失败"),
-    ));
-    let client_repository = Arc::new(Mutex::new(
-        ClientRepository::new(data_dir).expect("初始化客户端存储失败"),
-    ));
+    let service = VaultService::open(data_dir).expect("初始化数据存储失败");
     let file_watcher = Arc::new(Mutex::new(ConfigFileWatcher::new()));
 
     tauri::Builder::default()
-        .manage(prompt_repository)
-        .manage(client_repository)
+        .manage(service)
         .manage(file_watcher)
+        .manage(AutoSnapshotScheduler::new())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .invoke_handler(tauri::generate_handler![
@@ -36,10 +35,20 @@ pub fn run() {
             commands::template::update_template,
             commands::template::delete_template,
             commands::template::import_template_from_project,
+            commands::template::get_variables,
+            commands::template::save_variables,
             commands::project::select_project_directory,
             commands::project::apply_template,
             commands::project::get_project_config,
             commands::project::get_project_history,
+            commands::project::get_project_variables,
+            commands::project::set_project_variables,
+            commands::composition::get_compositions,
+            commands::composition::create_composition,
+            commands::composition::update_composition,
+            commands::composition::delete_composition,
+            commands::composition::preview_composition,
+            commands::composition::render_composition,
             commands::backup::create_backup,
             commands::backup::list_backups,
             commands::backup::restore_backup,
@@ -50,6 +59,11 @@ pub fn run() {
             commands::prompt::create_prompt,
             commands::prompt::update_prompt,
             commands::prompt::delete_prompt,
+            commands::prompt::search_prompts,
+            commands::prompt::list_prompt_revisions,
+            commands::prompt::diff_prompt_revisions,
+            commands::prompt::revert_prompt,
+            commands::prompt::render_prompt,
             commands::prompt::export_prompts,
             commands::prompt::import_prompts,
             commands::client::get_all_clients,
@@ -57,15 +71,24 @@ pub fn run() {
             commands::client::add_custom_client,
             commands::client::update_client,
             commands::client::delete_client,
+            commands::client::list_client_config_files,
+            commands::client::get_client_catalog,
+            commands::client::discover_clients,
+            commands::client::accept_discovered_clients,
             commands::client::export_clients,
             commands::client::import_clients,
             commands::config_file::read_config_file,
             commands::config_file::write_config_file,
+            commands::config_file::read_config_value,
+            commands::config_file::validate_config_file,
+            commands::config_file::patch_config_file,
             commands::path::expand_path,
             commands::path::get_filename,
             commands::path::get_relative_path,
             commands::file_watcher::start_watching_config,
             commands::file_watcher::stop_watching_config,
+            commands::file_watcher::unwatch_client_config,
+            commands::file_watcher::watch_all_clients,
             commands::app_state::get_app_state,
             commands::app_state::set_current_client
         ])
@@ -2,35 +2,24 @@
 
 use std::sync::{Arc, Mutex};
 
+use systemprompt_vault::auto_snapshot::AutoSnapshotScheduler;
 use systemprompt_vault::file_watcher::ConfigFileWatcher;
-use systemprompt_vault::storage::{
-    client_repository::ClientRepository, prompt_repository::PromptRepository,
-    snapshot_repository::SnapshotRepository,
-};
+use systemprompt_vault::service::VaultService;
 use systemprompt_vault::{app_menu, commands, tray};
-use tauri::{Manager, PhysicalPosition, PhysicalSize, WebviewWindow};
+use tauri::{Manager, PhysicalPosition, PhysicalSize, WebviewWindow, WindowEvent};
 
 const DEFAULT_WINDOW_WIDTH: u32 = 1200;
 const DEFAULT_WINDOW_HEIGHT: u32 = 1200;
 
 fn main() {
     let data_dir = commands::ensure_app_dir().expect("初始化应用目录失败");
-    let prompt_repository = Arc::new(Mutex::new(
-        PromptRepository::new(data_dir.clone()).expect("初始化提示词存储失败"),
-    ));
-    let client_repository = Arc::new(Mutex::new(
-        ClientRepository::new(data_dir.clone()).expect("初始化客户端存储失败"),
-    ));
-    let snapshot_repository = Arc::new(Mutex::new(
-        SnapshotRepository::new(data_dir).expect("初始化快照存储失败"),
-    ));
+    let service = VaultService::open(data_dir).expect("初始化数据存储失败");
     let file_watcher = Arc::new(Mutex::new(ConfigFileWatcher::new()));
 
     tauri::Builder::default()
-        .manage(prompt_repository)
-        .manage(client_repository)
-        .manage(snapshot_repository)
+        .manage(service)
         .manage(file_watcher)
+        .manage(AutoSnapshotScheduler::new())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .setup(|app| {
@@ -59,16 +48,38 @@ fn main() {
             }
             Ok(())
         })
+        .on_window_event(|window, event| {
+            // 关闭主窗口即退出应用时同步创建快照；隐藏到托盘时监听仍在运行，由防抖调度处理
+            if window.label() == "main"
+                && matches!(event, WindowEvent::CloseRequested { .. })
+                && close_exits_app()
+            {
+                let handle = window.app_handle();
+                handle
+                    .state::<AutoSnapshotScheduler>()
+                    .snapshot_before_exit(handle);
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             commands::template::get_templates,
             commands::template::create_template,
             commands::template::update_template,
             commands::template::delete_template,
             commands::template::import_template_from_project,
+            commands::template::get_variables,
+            commands::template::save_variables,
             commands::project::select_project_directory,
             commands::project::apply_template,
             commands::project::get_project_config,
             commands::project::get_project_history,
+            commands::project::get_project_variables,
+            commands::project::set_project_variables,
+            commands::composition::get_compositions,
+            commands::composition::create_composition,
+            commands::composition::update_composition,
+            commands::composition::delete_composition,
+            commands::composition::preview_composition,
+            commands::composition::render_composition,
             commands::backup::create_backup,
             commands::backup::list_backups,
             commands::backup::restore_backup,
@@ -79,6 +90,11 @@ fn main() {
             commands::prompt::create_prompt,
             commands::prompt::update_prompt,
             commands::prompt::delete_prompt,
+            commands::prompt::search_prompts,
+            commands::prompt::list_prompt_revisions,
+            commands::prompt::diff_prompt_revisions,
+            commands::prompt::revert_prompt,
+            commands::prompt::render_prompt,
             commands::prompt::export_prompts,
             commands::prompt::import_prompts,
             commands::client::get_all_clients,
@@ -86,16 +102,25 @@ fn main() {
             commands::client::add_custom_client,
             commands::client::update_client,
             commands::client::delete_client,
+            commands::client::list_client_config_files,
+            commands::client::get_client_catalog,
+            commands::client::discover_clients,
+            commands::client::accept_discovered_clients,
             commands::client::export_clients,
             commands::client::import_clients,
             commands::config_file::read_config_file,
             commands::config_file::write_config_file,
+            commands::config_file::read_config_value,
+            commands::config_file::validate_config_file,
+            commands::config_file::patch_config_file,
             commands::config_file::get_user_home_dir,
             commands::path::expand_path,
             commands::path::get_filename,
             commands::path::get_relative_path,
             commands::file_watcher::start_watching_config,
             commands::file_watcher::stop_watching_config,
+            commands::file_watcher::unwatch_client_config,
+            commands::file_watcher::watch_all_clients,
             commands::app_state::get_app_state,
             commands::app_state::set_current_client,
             commands::app_state::save_window_state,
@@ -104,12 +129,24 @@ fn main() {
             commands::app_state::get_window_behavior,
             commands::snapshot::create_snapshot,
             commands::snapshot::get_snapshots,
+            commands::snapshot::diff_snapshots,
+            commands::snapshot::diff_snapshot_with_current,
             commands::snapshot::restore_snapshot,
+            commands::snapshot::restore_snapshot_merged,
             commands::snapshot::delete_snapshot,
             commands::snapshot::rename_snapshot,
+            commands::snapshot::pin_snapshot,
+            commands::snapshot::unpin_snapshot,
+            commands::snapshot::set_snapshot_notes,
+            commands::snapshot::set_snapshot_labels,
+            commands::snapshot::search_snapshots,
+            commands::snapshot::export_snapshot_archive,
+            commands::snapshot::import_snapshot_archive,
             commands::snapshot::set_max_snapshots,
             commands::snapshot::set_max_auto_snapshots,
             commands::snapshot::set_max_manual_snapshots,
+            commands::snapshot::set_snapshot_retention,
+            commands::snapshot::set_auto_snapshot_labels,
             commands::snapshot::refresh_tray_menu,
             commands::snapshot::refresh_app_menu,
         ])
@@ -117,8 +154,19 @@ fn main() {
         .expect("SystemPromptVault Tauri 运行失败");
 }
 
+/// 按保存的关闭行为判断关闭主窗口是否退出应用；未设置时为隐藏到托盘，读取失败时按退出处理
+fn close_exits_app() -> bool {
+    match commands::app_state::get_window_behavior() {
+        Ok(behavior) => behavior.is_some_and(|behavior| !behavior.hides_to_tray()),
+        Err(err) => {
+            eprintln!("读取窗口关闭行为失败: {}", err);
+            true
+        }
+    }
+}
+
 fn restore_window_state(window: &WebviewWindow) -> Result<(), String> {
-    let saved = match commands::app_state::get_window_state()? {
+    let saved = match commands::app_state::get_window_state().map_err(|err| err.to_string())? {
         Some(state) => state,
         None => return Ok(()),
     };
@@ -27,6 +27,15 @@ pub struct WindowBehavior {
     pub close_behavior: String,
 }
 
+impl WindowBehavior {
+    /// 关闭主窗口时隐藏到托盘而不是退出，与前端的默认值一致
+    pub const TRAY: &'static str = "tray";
+
+    pub fn hides_to_tray(&self) -> bool {
+        self.close_behavior == Self::TRAY
+    }
+}
+
 impl Default for AppState {
     fn default() -> Self {
         Self {
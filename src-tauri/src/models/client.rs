@@ -1,6 +1,29 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::utils::path_utils::{is_listed_path, is_path_pattern};
 use serde::{Deserialize, Deserializer, Serialize};
 
+/// 内置客户端早期保存的默认路径及其现在的写法：客户端 ID、旧路径、新路径
+///
+/// 内置目录改为按环境变量解析后，已保存且仍等于旧默认值的路径在加载时迁移，
+/// 设置了 `CLAUDE_CONFIG_DIR` 等变量的用户随之读写工具实际使用的文件。
+const LEGACY_DEFAULT_PATHS: [(&str, &str, &str); 3] = [
+    (
+        "Claude",
+        "~/.claude/CLAUDE.md",
+        "$CLAUDE_CONFIG_DIR/CLAUDE.md",
+    ),
+    ("Codex", "~/.codex/AGENTS.md", "$CODEX_HOME/AGENTS.md"),
+    (
+        "OpenCode",
+        "~/.config/opencode/AGENTS.md",
+        "$XDG_CONFIG_HOME/opencode/AGENTS.md",
+    ),
+];
+
 /// 客户端配置，描述提示词文件来源
+///
+/// `config_file_paths` 中可以包含通配模式（如 `~/.claude/commands/**/*.md`）或以分隔符结尾的目录，
+/// 读取、快照与监听时展开为当时匹配的文件；`active_config_path` 总是具体文件。
 #[derive(Debug, Clone, Serialize)]
 pub struct ClientConfig {
     pub id: String,
@@ -94,20 +117,16 @@ impl ClientConfig {
         config
     }
 
-    fn ensure_active_path(&mut self) {
-        if self.config_file_paths.is_empty() {
-            self.active_config_path = None;
-            return;
-        }
-
+    /// 激活路径不再列于配置中时改用第一个不含通配符的路径
+    pub(crate) fn ensure_active_path(&mut self) {
         let has_valid_active = self
             .active_config_path
             .as_ref()
-            .map(|target| self.config_file_paths.iter().any(|path| path == target))
-            .unwrap_or(false);
+            .is_some_and(|target| self.has_config_path(target));
 
         if !has_valid_active {
-            self.active_config_path = self.config_file_paths.first().cloned();
+            let first = self.fixed_config_paths().next().cloned();
+            self.active_config_path = first;
         }
     }
 
@@ -145,52 +164,133 @@ impl ClientConfig {
         )
     }
 
-    /// 获取当前默认配置路径（优先 active，其次首个路径）
+    /// 获取当前默认配置路径（优先 active，其次首个不含通配符的路径）
     pub fn default_config_path(&self) -> Option<&str> {
         self.active_config_path
             .as_deref()
-            .or_else(|| self.config_file_paths.first().map(|path| path.as_str()))
+            .or_else(|| self.fixed_config_paths().next().map(|path| path.as_str()))
     }
 
     /// 根据外部输入解析最终使用的配置路径
-    pub fn resolve_config_path(&self, override_path: Option<&str>) -> Result<String, String> {
+    pub fn resolve_config_path(&self, override_path: Option<&str>) -> VaultResult<String> {
         if let Some(path) = override_path {
+            if is_path_pattern(path) {
+                return Err(VaultError::new(ErrorCode::ClientConfigPathPattern)
+                    .with_param("client_id", &self.id)
+                    .with_path(path));
+            }
             if !self.has_config_path(path) {
-                return Err("指定的配置文件路径未在客户端配置中找到".to_string());
+                return Err(VaultError::new(ErrorCode::ClientConfigPathNotFound)
+                    .with_param("client_id", &self.id)
+                    .with_path(path));
             }
             return Ok(path.to_string());
         }
 
         self.default_config_path()
             .map(|path| path.to_string())
-            .ok_or_else(|| "客户端未配置任何配置文件路径".to_string())
+            .ok_or_else(|| {
+                VaultError::new(ErrorCode::ClientNoConfigPaths).with_param("client_id", &self.id)
+            })
     }
 
+    /// `path` 是列出的具体文件，或某个通配模式匹配的文件
     pub fn has_config_path(&self, path: &str) -> bool {
-        self.config_file_paths.iter().any(|item| item == path)
+        is_listed_path(&self.config_file_paths, path)
+    }
+
+    /// 配置路径中的通配模式与目录
+    pub fn path_patterns(&self) -> Vec<String> {
+        self.config_file_paths
+            .iter()
+            .filter(|path| is_path_pattern(path))
+            .cloned()
+            .collect()
+    }
+
+    /// 把内置客户端中仍为旧默认值的路径（含激活路径）改为当前写法，返回是否有改动
+    pub(crate) fn migrate_legacy_default_paths(&mut self) -> bool {
+        if !self.is_builtin {
+            return false;
+        }
+        let mut changed = false;
+        for (id, legacy, current) in LEGACY_DEFAULT_PATHS {
+            if self.id != id {
+                continue;
+            }
+            let paths = self
+                .config_file_paths
+                .iter_mut()
+                .chain(self.active_config_path.as_mut());
+            for path in paths.filter(|path| path.as_str() == legacy) {
+                *path = current.to_string();
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn fixed_config_paths(&self) -> impl Iterator<Item = &String> {
+        self.config_file_paths
+            .iter()
+            .filter(|path| !is_path_pattern(path))
     }
 }
 
-/// 默认内置客户端列表
-pub fn default_clients() -> Vec<ClientConfig> {
-    vec![
-        ClientConfig::new_builtin(
-            "Claude",
-            "Claude",
-            vec!["~/.claude/CLAUDE.md".to_string()],
-            true,
-        ),
-        ClientConfig::new_builtin(
-            "Codex",
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(id: &str, paths: &[&str], is_builtin: bool) -> ClientConfig {
+        let paths = paths.iter().map(|path| path.to_string()).collect();
+        if is_builtin {
+            ClientConfig::new_builtin(id, id, paths, true)
+        } else {
+            ClientConfig::new_custom(id, id, paths, true)
+        }
+    }
+
+    #[test]
+    fn migrates_legacy_default_paths_of_builtin_clients() {
+        let mut claude = client("Claude", &["~/.claude/CLAUDE.md", "~/notes.md"], true);
+        assert!(claude.migrate_legacy_default_paths());
+        assert_eq!(
+            claude.config_file_paths,
+            ["$CLAUDE_CONFIG_DIR/CLAUDE.md", "~/notes.md"]
+        );
+        assert_eq!(
+            claude.active_config_path.as_deref(),
+            Some("$CLAUDE_CONFIG_DIR/CLAUDE.md")
+        );
+        assert!(!claude.migrate_legacy_default_paths());
+    }
+
+    #[test]
+    fn keeps_active_path_pointing_elsewhere() {
+        let mut codex = client("Codex", &["~/.codex/AGENTS.md", "~/work/AGENTS.md"], true);
+        codex.active_config_path = Some("~/work/AGENTS.md".to_string());
+        assert!(codex.migrate_legacy_default_paths());
+        assert_eq!(
+            codex.config_file_paths,
+            ["$CODEX_HOME/AGENTS.md", "~/work/AGENTS.md"]
+        );
+        assert_eq!(
+            codex.active_config_path.as_deref(),
+            Some("~/work/AGENTS.md")
+        );
+    }
+
+    #[test]
+    fn leaves_custom_and_edited_paths_alone() {
+        let mut custom = client("Claude", &["~/.claude/CLAUDE.md"], false);
+        assert!(!custom.migrate_legacy_default_paths());
+        assert_eq!(custom.config_file_paths, ["~/.claude/CLAUDE.md"]);
+
+        let mut other = client(
             "Codex",
-            vec!["~/.codex/AGENTS.md".to_string()],
-            true,
-        ),
-        ClientConfig::new_builtin(
-            "Gemini",
-            "Gemini",
-            vec!["~/.gemini/GEMINI.md".to_string()],
+            &["~/.claude/CLAUDE.md", "~/.codex/AGENTS.md.bak"],
             true,
-        ),
-    ]
+        );
+        assert!(!other.migrate_legacy_default_paths());
+    }
 }
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 组合中的一项：引用库中的提示词，或一段固定的分隔文本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CompositionItem {
+    Prompt { prompt_id: String },
+    Separator { text: String },
+}
+
+/// 渲染结果中一段连续行的来源，行号从 1 开始且包含 `end_line`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompositionSource {
+    pub item_index: usize,
+    /// 分隔文本为 `None`
+    pub prompt_id: Option<String>,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// 由有序的提示词与分隔文本拼装而成、绑定到客户端配置文件的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Composition {
+    pub id: String,
+    pub name: String,
+    pub client_id: String,
+    /// 为空时写入客户端当前激活的配置文件
+    pub config_path: Option<String>,
+    pub items: Vec<CompositionItem>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_rendered_at: Option<DateTime<Utc>>,
+    /// 最近一次写入的内容中每段行的来源
+    #[serde(default)]
+    pub sources: Vec<CompositionSource>,
+    /// 最近一次自动重新渲染失败的原因，成功渲染后清空
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+impl Composition {
+    pub fn new(
+        name: impl Into<String>,
+        client_id: impl Into<String>,
+        config_path: Option<String>,
+        items: Vec<CompositionItem>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name: name.into(),
+            client_id: client_id.into(),
+            config_path,
+            items,
+            created_at: now,
+            updated_at: now,
+            last_rendered_at: None,
+            sources: Vec::new(),
+            last_error: None,
+        }
+    }
+
+    pub fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+
+    pub fn references(&self, prompt_id: &str) -> bool {
+        self.items.iter().any(
+            |item| matches!(item, CompositionItem::Prompt { prompt_id: id } if id == prompt_id),
+        )
+    }
+}
+
+/// 组合渲染后的内容，未写入文件
+#[derive(Debug, Clone, Serialize)]
+pub struct CompositionPreview {
+    pub content: String,
+    pub sources: Vec<CompositionSource>,
+}
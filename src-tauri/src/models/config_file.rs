@@ -0,0 +1,32 @@
+use crate::client_catalog::ConfigFormat;
+use crate::utils::structured::ConfigSyntaxError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 结构化配置文件中按键路径读取的值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigValue {
+    pub path: String,
+    pub format: ConfigFormat,
+    pub key_path: Vec<String>,
+    /// 键路径不存在时为 `None`，键路径为空时为整个文档
+    pub value: Option<Value>,
+}
+
+/// 配置内容的语法校验结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigValidation {
+    pub path: String,
+    pub format: ConfigFormat,
+    pub valid: bool,
+    pub error: Option<ConfigSyntaxError>,
+}
+
+/// 按键路径修改配置文件的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigPatchResult {
+    pub path: String,
+    pub format: ConfigFormat,
+    /// 为 `false` 时文件被整体重新序列化，原有注释与排版可能丢失
+    pub formatting_preserved: bool,
+}
@@ -0,0 +1,66 @@
+use serde::Serialize;
+
+/// 文件在两个版本之间的变化；不存在与内容为空视为同一状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// 差异中的一行，行号从 1 开始；新增行没有旧行号，删除行没有新行号
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+}
+
+/// 统一差异格式中的一个 `@@ -old_start,old_lines +new_start,new_lines @@` 块
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDiff {
+    pub path: String,
+    pub change: FileChangeKind,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    pub hunks: Vec<DiffHunk>,
+    /// 同一差异的统一格式文本，便于直接展示或写入补丁文件
+    pub unified: String,
+}
+
+/// 两个快照之间、或快照与当前文件之间的差异，只包含有变化的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    pub client_id: String,
+    pub from_snapshot: String,
+    /// 为空表示与磁盘上的当前文件比较
+    pub to_snapshot: Option<String>,
+    pub files: Vec<FileDiff>,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
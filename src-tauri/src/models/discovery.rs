@@ -0,0 +1,26 @@
+use super::ClientConfig;
+use serde::{Deserialize, Serialize};
+
+/// 发现结果的可信程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscoveryConfidence {
+    /// 只发现了工具的安装目录，建议的路径尚不存在
+    Low,
+    /// 找到的文件名多个工具通用，例如项目中的 `AGENTS.md`
+    Medium,
+    /// 找到了工具专用位置的指令文件
+    High,
+}
+
+/// 扫描到的一个 AI 工具，`client` 可直接交给 `accept_discovered_clients` 接受
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveredClient {
+    /// 建议的客户端配置，只包含尚未被任何客户端使用的路径
+    pub client: ClientConfig,
+    pub confidence: DiscoveryConfidence,
+    /// 实际存在的文件，其余路径为建议创建的位置
+    pub found_paths: Vec<String>,
+    /// 同 ID 的客户端已存在，接受时把路径追加到该客户端
+    pub existing: bool,
+}
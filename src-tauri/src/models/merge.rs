@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// 合并恢复遇到冲突时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStyle {
+    /// 以 `<<<<<<<`/`=======`/`>>>>>>>` 标记写入冲突，与 git 相同
+    #[default]
+    Markers,
+    /// 存在冲突时不写入任何文件，只返回冲突列表
+    List,
+}
+
+/// 单个文件的合并结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeFileStatus {
+    /// 当前文件与快照一致，无需写入
+    Unchanged,
+    /// 当前文件自基准快照后未被修改，直接使用快照内容
+    Restored,
+    /// 快照自基准后没有变化，保留当前文件
+    KeptCurrent,
+    /// 双方的修改互不重叠，已自动合并
+    Merged,
+    /// 快照之后新增且未再修改的文件，已删除
+    Removed,
+    Conflicted,
+}
+
+/// 双方修改了基准中同一段内容，行号从 1 开始
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeConflict {
+    pub base_start_line: usize,
+    pub base_line_count: usize,
+    pub base: String,
+    /// 当前文件中的内容
+    pub ours: String,
+    /// 所恢复快照中的内容
+    pub theirs: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedFile {
+    pub path: String,
+    pub status: MergeFileStatus,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeRestoreResult {
+    pub snapshot_id: String,
+    /// 作为共同基准的最新快照；没有其他快照时以空内容为基准
+    pub base_snapshot_id: Option<String>,
+    pub files: Vec<MergedFile>,
+    /// 是否写入了文件；列表模式下存在冲突时为 `false`
+    pub written: bool,
+}
+
+impl MergeRestoreResult {
+    pub fn has_conflicts(&self) -> bool {
+        self.files
+            .iter()
+            .any(|file| file.status == MergeFileStatus::Conflicted)
+    }
+}
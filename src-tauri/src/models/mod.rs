@@ -1,15 +1,40 @@
 pub mod app_state;
 pub mod client;
+pub mod composition;
+pub mod config_file;
+pub mod diff;
+pub mod discovery;
+pub mod merge;
 pub mod operations;
 pub mod project;
 pub mod prompt;
+pub mod search;
 pub mod snapshot;
+pub mod snapshot_archive;
 pub mod template;
+pub mod variable;
 
 pub use app_state::{AppState, WindowState};
-pub use client::{default_clients, ClientConfig};
-pub use operations::{ApplyResult, Backup, HistoryEntry, ProjectConfig};
+pub use client::ClientConfig;
+pub use composition::{Composition, CompositionItem, CompositionPreview, CompositionSource};
+pub use config_file::{ConfigPatchResult, ConfigValidation, ConfigValue};
+pub use diff::{DiffHunk, DiffLine, DiffLineKind, FileChangeKind, FileDiff, SnapshotDiff};
+pub use discovery::{DiscoveredClient, DiscoveryConfidence};
+pub use merge::{ConflictStyle, MergeConflict, MergeFileStatus, MergeRestoreResult, MergedFile};
+pub use operations::{
+    ApplyResult, Backup, ClientExportData, ClientImportResult, HistoryEntry, ImportResult,
+    ProjectConfig, ProjectConfigFile,
+};
 pub use project::Project;
-pub use prompt::Prompt;
-pub use snapshot::{Snapshot, SnapshotConfig};
+pub use prompt::{Prompt, PromptRevision, PromptRevisionDiff};
+pub use search::{PromptSearchHit, SearchField, SearchMatch, SearchSnippet};
+pub use snapshot::{
+    RetentionPolicy, Snapshot, SnapshotConfig, SnapshotMatchField, SnapshotSearchHit,
+    SnapshotSearchQuery,
+};
+pub use snapshot_archive::{
+    SnapshotArchive, SnapshotArchiveEntry, SnapshotArchiveManifest, SnapshotImportResult,
+    SNAPSHOT_ARCHIVE_FORMAT, SNAPSHOT_ARCHIVE_VERSION,
+};
 pub use template::Template;
+pub use variable::VariableDefinition;
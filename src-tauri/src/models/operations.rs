@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApplyResult {
@@ -9,9 +10,16 @@ pub struct ApplyResult {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectConfig {
-    pub claude: Option<String>,
-    pub codex: Option<String>,
-    pub gemini: Option<String>,
+    pub files: Vec<ProjectConfigFile>,
+}
+
+/// 项目中按客户端目录找到的一个指令文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectConfigFile {
+    pub client_id: String,
+    /// 相对项目根目录的路径，统一使用 `/` 分隔
+    pub path: String,
+    pub content: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,3 +38,28 @@ pub struct HistoryEntry {
     pub timestamp: String,
     pub backup_id: Option<String>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub total: usize,
+    pub added: usize,
+    pub updated: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientExportData {
+    pub id: String,
+    pub name: String,
+    pub config_file_paths: Vec<String>,
+    pub active_config_path: Option<String>,
+    pub auto_tag: bool,
+    pub is_builtin: bool,
+    pub config_contents: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientImportResult {
+    pub total: usize,
+    pub added: usize,
+    pub updated: usize,
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +8,9 @@ pub struct Project {
     pub path: PathBuf,
     pub last_template: Option<String>,
     pub last_applied_at: Option<DateTime<Utc>>,
+    /// 覆盖库中同名变量的取值
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
 }
 
 impl Project {
@@ -15,6 +19,7 @@ impl Project {
             path,
             last_template: None,
             last_applied_at: None,
+            variables: BTreeMap::new(),
         }
     }
 
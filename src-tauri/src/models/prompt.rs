@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 /// 单条提示词模型，负责描述提示词的基础信息
@@ -58,3 +59,62 @@ impl Prompt {
         self.tags.iter().any(|t| t == tag)
     }
 }
+
+/// 提示词的一个历史版本，保存修改后的完整内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptRevision {
+    pub id: String,
+    pub prompt_id: String,
+    pub name: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    /// 名称、内容与标签的 SHA-256，用于跳过未变化的版本
+    pub hash: String,
+    /// 修改者（系统用户名）
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+impl PromptRevision {
+    /// 以提示词当前状态生成版本，时间取提示词的修改时间
+    pub fn from_prompt(prompt: &Prompt, author: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            prompt_id: prompt.id.clone(),
+            name: prompt.name.clone(),
+            content: prompt.content.clone(),
+            tags: prompt.tags.clone(),
+            created_at: prompt.updated_at,
+            hash: Self::compute_hash(&prompt.name, &prompt.content, &prompt.tags),
+            author,
+        }
+    }
+
+    pub fn compute_hash(name: &str, content: &str, tags: &[String]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(name.as_bytes());
+        hasher.update(b"\n");
+        for tag in tags {
+            hasher.update(tag.as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.update(b"\n");
+        hasher.update(content.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// 两个提示词版本之间的差异
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptRevisionDiff {
+    pub prompt_id: String,
+    pub from_revision: String,
+    pub to_revision: String,
+    pub old_name: String,
+    pub new_name: String,
+    pub added_tags: Vec<String>,
+    pub removed_tags: Vec<String>,
+    /// 内容的统一格式 diff，内容相同时为空
+    pub content_diff: String,
+}
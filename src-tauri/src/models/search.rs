@@ -0,0 +1,40 @@
+use super::Prompt;
+use serde::Serialize;
+
+/// 命中所在的提示词字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchField {
+    Name,
+    Tag,
+    Content,
+}
+
+/// 一次命中在字段文本中的字符区间 `[start, end)`（按 Unicode 字符计数）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchMatch {
+    pub field: SearchField,
+    /// 字段为标签时，命中的是第几个标签
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag_index: Option<usize>,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 内容摘录，`highlights` 为命中在 `text` 中的字符区间
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchSnippet {
+    pub text: String,
+    /// 摘录在完整内容中的起始字符位置，大于 0 时前面还有内容
+    pub start: usize,
+    pub truncated: bool,
+    pub highlights: Vec<[usize; 2]>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptSearchHit {
+    pub prompt: Prompt,
+    pub score: f64,
+    pub matches: Vec<SearchMatch>,
+    pub snippet: Option<SearchSnippet>,
+}
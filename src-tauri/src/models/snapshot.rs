@@ -1,20 +1,41 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snapshot {
     pub id: String,
     pub name: String,
+    /// 旧版单文件正文，仅在从 blob 加载正文后填充
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub content: String,
     pub client_id: String,
     pub created_at: DateTime<Utc>,
     pub is_auto: bool,
+    /// 固定的快照不会被自动裁剪，也不计入数量上限
+    #[serde(default)]
+    pub pinned: bool,
+    /// Markdown 格式的备注
+    #[serde(default)]
+    pub notes: String,
+    /// 标签，如 `baseline`、`before-upgrade`
+    #[serde(default)]
+    pub labels: BTreeSet<String>,
     #[serde(default)]
     pub content_hash: String,
+    /// 各配置文件正文，仅在从 blob 加载正文后填充
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub multi_file_contents: Option<HashMap<String, String>>,
+    /// 配置文件路径 -> blob 哈希
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, String>,
+    /// 旧版单文件正文的 blob 哈希
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_blob: Option<String>,
+    /// 创建快照时客户端配置中的通配模式，恢复时删除这些模式匹配、但快照中没有的文件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_patterns: Vec<String>,
 }
 
 impl Snapshot {
@@ -33,13 +54,48 @@ impl Snapshot {
             client_id: client_id.into(),
             created_at: Utc::now(),
             is_auto,
+            pinned: false,
+            notes: String::new(),
+            labels: BTreeSet::new(),
             content_hash: content_hash.into(),
             multi_file_contents,
+            files: BTreeMap::new(),
+            content_blob: None,
+            path_patterns: Vec::new(),
         }
     }
 
     pub fn is_multi_file(&self) -> bool {
         self.multi_file_contents.is_some()
+            || !self.files.is_empty()
+            || !self.path_patterns.is_empty()
+    }
+
+    /// 正文仍内联在快照中（旧版数据或尚未写入 blob）
+    pub fn has_inline_bodies(&self) -> bool {
+        self.multi_file_contents.is_some() || !self.content.is_empty()
+    }
+
+    /// 该快照引用的全部 blob 哈希（去重）
+    pub fn blob_refs(&self) -> BTreeSet<String> {
+        self.files
+            .values()
+            .chain(self.content_blob.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// 去掉正文只保留 blob 引用，用于落盘与列表展示
+    pub fn strip_bodies(&mut self) {
+        self.content.clear();
+        self.multi_file_contents = None;
+    }
+
+    /// 是否带有指定标签（不区分大小写）
+    pub fn has_label(&self, label: &str) -> bool {
+        self.labels
+            .iter()
+            .any(|own| own.eq_ignore_ascii_case(label.trim()))
     }
 
     pub fn get_file_contents(&self) -> HashMap<String, String> {
@@ -64,6 +120,9 @@ pub struct SnapshotConfig {
     pub max_auto_snapshots: usize,
     #[serde(default = "SnapshotConfig::default_max_manual_snapshots")]
     pub max_manual_snapshots: usize,
+    /// 时间分档保留策略，设置后其覆盖的快照类型不再受数量上限约束
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionPolicy>,
     #[serde(default)]
     pub snapshots: Vec<Snapshot>,
 }
@@ -75,6 +134,7 @@ impl SnapshotConfig {
             max_snapshots: Self::default_max_snapshots(),
             max_auto_snapshots: Self::default_max_auto_snapshots(),
             max_manual_snapshots: Self::default_max_manual_snapshots(),
+            retention: None,
             snapshots: Vec::new(),
         }
     }
@@ -97,3 +157,101 @@ impl Default for SnapshotConfig {
         Self::new(String::new())
     }
 }
+
+/// 按快照年龄分档保留：越旧的快照保留得越稀疏，每档只留最新的一个
+///
+/// 各时长均从当前时间起算且逐档递增，超出每日档的快照按周保留。
+/// 默认只作用于自动快照，手动快照仍按 `max_manual_snapshots` 裁剪；固定的快照始终保留。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// 该时长（小时）内的快照全部保留
+    #[serde(default = "RetentionPolicy::default_keep_all_hours")]
+    pub keep_all_hours: u32,
+    /// 该时长（小时）内每小时保留一个
+    #[serde(default = "RetentionPolicy::default_hourly_hours")]
+    pub hourly_hours: u32,
+    /// 该时长（天）内每天保留一个
+    #[serde(default = "RetentionPolicy::default_daily_days")]
+    pub daily_days: u32,
+    /// 更早的快照每周保留一个；设置后超过该周数的快照被删除，`None` 表示不限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly_weeks: Option<u32>,
+    /// 为 `true` 时未固定的手动快照也按分档清理（与自动快照分开分档），不再受数量上限约束
+    #[serde(default)]
+    pub include_manual: bool,
+}
+
+impl RetentionPolicy {
+    pub fn default_keep_all_hours() -> u32 {
+        1
+    }
+
+    pub fn default_hourly_hours() -> u32 {
+        24
+    }
+
+    pub fn default_daily_days() -> u32 {
+        30
+    }
+
+    /// 各档时长是否逐档递增
+    pub fn is_valid(&self) -> bool {
+        let daily_hours = u64::from(self.daily_days) * 24;
+        self.keep_all_hours <= self.hourly_hours
+            && u64::from(self.hourly_hours) <= daily_hours
+            && self
+                .weekly_weeks
+                .is_none_or(|weeks| u64::from(weeks) * 7 * 24 >= daily_hours)
+    }
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_all_hours: Self::default_keep_all_hours(),
+            hourly_hours: Self::default_hourly_hours(),
+            daily_days: Self::default_daily_days(),
+            weekly_weeks: None,
+            include_manual: false,
+        }
+    }
+}
+
+/// 跨客户端检索快照的条件，各条件同时满足才算命中
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SnapshotSearchQuery {
+    /// 只检索指定客户端，`None` 时检索全部客户端
+    pub client_id: Option<String>,
+    /// 快照须带有全部这些标签
+    pub labels: Vec<String>,
+    /// 在名称、备注与文件内容中查找的文本，默认为不区分大小写的子串
+    pub text: Option<String>,
+    /// 将 `text` 视为正则表达式
+    pub regex: bool,
+    /// 创建时间下限（含）
+    pub from: Option<DateTime<Utc>>,
+    /// 创建时间上限（含）
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+/// 文本条件命中的快照字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotMatchField {
+    Name,
+    Notes,
+    Content,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotSearchHit {
+    pub client_id: String,
+    /// 只含元数据，正文需通过 `get_snapshot` 读取
+    pub snapshot: Snapshot,
+    /// 文本条件命中的字段，未指定文本时为空
+    pub matched_fields: Vec<SnapshotMatchField>,
+    /// 内容命中的配置文件路径
+    pub matched_files: Vec<String>,
+}
@@ -0,0 +1,55 @@
+use super::{Snapshot, SnapshotConfig};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 快照归档的格式标识
+pub const SNAPSHOT_ARCHIVE_FORMAT: &str = "systemprompt-vault/snapshots";
+
+/// 当前写出的归档版本，导入时拒绝更高的版本
+pub const SNAPSHOT_ARCHIVE_VERSION: u32 = 1;
+
+/// 单个 JSON 文件形式的快照归档，快照正文内联保存，不依赖本机 blob
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotArchive {
+    pub manifest: SnapshotArchiveManifest,
+    /// 导出完整历史时附带的快照配置（数量上限与保留策略），其中 `snapshots` 为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<SnapshotConfig>,
+    pub snapshots: Vec<Snapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotArchiveManifest {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: DateTime<Utc>,
+    /// 导出时快照所属的客户端
+    pub client_id: String,
+    pub entries: Vec<SnapshotArchiveEntry>,
+}
+
+/// 清单中的一条快照记录，导入时用于校验归档内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotArchiveEntry {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub is_auto: bool,
+    pub content_hash: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotImportResult {
+    /// 快照导入到的客户端
+    pub client_id: String,
+    pub total: usize,
+    pub imported: usize,
+    /// 目标客户端已有相同 ID 的快照
+    pub skipped_existing: usize,
+    /// 目标客户端已有相同内容的快照
+    pub skipped_duplicate: usize,
+    /// 导入后按数量上限与保留策略裁剪掉的快照数
+    pub pruned: usize,
+}
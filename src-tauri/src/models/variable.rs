@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// 用户自定义的模板变量，在模板与提示词中以 `{{name}}` 引用
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariableDefinition {
+    pub name: String,
+    /// 项目未覆盖时使用的取值；为空时必须由项目提供
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
@@ -0,0 +1,521 @@
+use super::config_files::{client_config_files, read_client_config_files, read_optional_file};
+use super::{ErrorCode, VaultError, VaultEventSink, VaultResult, VaultService};
+use crate::client_catalog::ConfigFormat;
+use crate::models::{ClientConfig, ClientExportData, ClientImportResult};
+use crate::utils::file_ops::atomic_write;
+use crate::utils::path_utils::{is_listed_path, is_path_pattern, resolve_path};
+use crate::utils::structured;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+const ENTITY: &str = "client";
+
+impl VaultService {
+    pub fn get_clients(&self) -> VaultResult<Vec<ClientConfig>> {
+        self.lock_clients()?.get_all()
+    }
+
+    pub fn get_client(&self, id: &str) -> VaultResult<Option<ClientConfig>> {
+        self.lock_clients()?.get_by_id(id)
+    }
+
+    /// 获取客户端，不存在时返回 [`ErrorCode::ClientNotFound`]
+    pub fn require_client(&self, id: &str) -> VaultResult<ClientConfig> {
+        self.get_client(id)?
+            .ok_or_else(|| VaultError::client_not_found(id))
+    }
+
+    pub fn add_custom_client(
+        &self,
+        id: String,
+        name: String,
+        config_file_paths: Vec<String>,
+    ) -> VaultResult<ClientConfig> {
+        if id.trim().is_empty() {
+            return Err(ErrorCode::ClientIdEmpty.into());
+        }
+        if name.trim().is_empty() {
+            return Err(ErrorCode::ClientNameEmpty.into());
+        }
+
+        let sanitized_paths = sanitize_config_paths(config_file_paths)?;
+
+        let mut repo = self.lock_clients()?;
+        if repo.get_by_id(&id)?.is_some() {
+            return Err(VaultError::new(ErrorCode::ClientAlreadyExists).with_param("client_id", id));
+        }
+
+        let client = ClientConfig::new_custom(id, name, sanitized_paths, false);
+        repo.save(client.clone())?;
+        Ok(client)
+    }
+
+    pub fn update_client(
+        &self,
+        id: &str,
+        name: Option<String>,
+        config_file_paths: Option<Vec<String>>,
+        active_config_path: Option<String>,
+        auto_tag: Option<bool>,
+    ) -> VaultResult<ClientConfig> {
+        let mut repo = self.lock_clients()?;
+        let mut client = repo
+            .get_by_id(id)?
+            .ok_or_else(|| VaultError::client_not_found(id))?;
+
+        if let Some(new_name) = name {
+            if new_name.trim().is_empty() {
+                return Err(ErrorCode::ClientNameEmpty.into());
+            }
+            client.name = new_name;
+        }
+
+        let sanitized_active = sanitize_optional_path(active_config_path)?;
+
+        if let Some(paths) = config_file_paths {
+            client.config_file_paths = sanitize_config_paths(paths)?;
+            client.ensure_active_path();
+        }
+
+        if let Some(active_path) = sanitized_active {
+            if !client.has_config_path(&active_path) {
+                return Err(VaultError::new(ErrorCode::ClientActivePathNotListed)
+                    .with_param("client_id", id)
+                    .with_path(active_path));
+            }
+            client.active_config_path = Some(active_path);
+        }
+
+        if let Some(auto_tag) = auto_tag {
+            client.auto_tag = auto_tag;
+        }
+
+        repo.save(client.clone())?;
+        Ok(client)
+    }
+
+    pub fn delete_client(&self, id: &str) -> VaultResult<bool> {
+        let mut repo = self.lock_clients()?;
+        let client = repo
+            .get_by_id(id)?
+            .ok_or_else(|| VaultError::client_not_found(id))?;
+        if client.is_builtin {
+            return Err(VaultError::new(ErrorCode::ClientBuiltinUndeletable)
+                .with_param("client_id", client.id));
+        }
+
+        repo.delete(id)
+    }
+
+    /// 客户端当前的全部配置文件，通配模式展开为此刻匹配的文件
+    pub fn list_client_config_files(&self, id: &str) -> VaultResult<Vec<String>> {
+        Ok(client_config_files(&self.require_client(id)?))
+    }
+
+    /// 导出全部客户端及其配置文件的当前内容
+    pub fn export_clients(&self) -> VaultResult<Vec<ClientExportData>> {
+        let clients = self.get_clients()?;
+
+        let mut exports = Vec::with_capacity(clients.len());
+        for client in clients {
+            let contents = read_client_config_files(&client)?;
+            exports.push(ClientExportData {
+                id: client.id,
+                name: client.name,
+                config_file_paths: client.config_file_paths,
+                active_config_path: client.active_config_path,
+                auto_tag: client.auto_tag,
+                is_builtin: client.is_builtin,
+                config_contents: contents,
+            });
+        }
+        Ok(exports)
+    }
+
+    /// 导入客户端，`overwrite_ids` 为空时覆盖全部已存在的客户端
+    pub fn import_clients(
+        &self,
+        json_data: &str,
+        overwrite_ids: Option<Vec<String>>,
+    ) -> VaultResult<ClientImportResult> {
+        let clients = parse_and_validate_clients(json_data)?;
+        let total = clients.len();
+        if total == 0 {
+            return Ok(ClientImportResult {
+                total: 0,
+                added: 0,
+                updated: 0,
+            });
+        }
+
+        let overwrite_filter = overwrite_ids.map(|ids| {
+            ids.into_iter()
+                .filter_map(|id| {
+                    let trimmed = id.trim();
+                    if trimmed.is_empty() {
+                        None
+                    } else {
+                        Some(trimmed.to_string())
+                    }
+                })
+                .collect::<HashSet<_>>()
+        });
+
+        let mut repo = self.lock_clients()?;
+        let mut added = 0;
+        let mut updated = 0;
+
+        for entry in clients {
+            let sanitized_id = entry.id.trim().to_string();
+            let sanitized_name = entry.name.trim().to_string();
+            let paths = sanitize_config_paths(entry.config_file_paths.clone())?;
+            let active_path = sanitize_optional_path(entry.active_config_path.clone())?;
+            if let Some(ref active) = active_path {
+                if !is_listed_path(&paths, active) {
+                    return Err(VaultError::new(ErrorCode::ClientActivePathNotListed)
+                        .with_param("client_id", &sanitized_id)
+                        .with_path(active));
+                }
+            }
+
+            let mut client = ClientConfig::new_custom(
+                sanitized_id.clone(),
+                sanitized_name,
+                paths,
+                entry.auto_tag,
+            );
+            client.active_config_path = active_path;
+
+            let existing = repo.get_by_id(&sanitized_id)?;
+            if existing.is_some() {
+                if let Some(filter) = overwrite_filter.as_ref() {
+                    if !filter.contains(&sanitized_id) {
+                        continue;
+                    }
+                }
+            }
+
+            write_client_config_files(&client, &entry.config_contents)?;
+            repo.save(client)?;
+
+            if existing.is_some() {
+                updated += 1;
+            } else {
+                added += 1;
+            }
+        }
+
+        Ok(ClientImportResult {
+            total,
+            added,
+            updated,
+        })
+    }
+
+    /// 读取客户端配置文件，`config_path` 为空时使用默认路径
+    pub fn read_config_file(
+        &self,
+        client_id: &str,
+        config_path: Option<String>,
+    ) -> VaultResult<String> {
+        let resolved = self.resolve_client_path(client_id, config_path)?;
+        read_optional_file(&resolved)
+    }
+
+    /// 原样写入编辑器中的配置文件内容，写入前通知 `sink`
+    ///
+    /// 内容不做变量替换：编辑器中的 `{{ ... }}` 可能属于配置本身（如 GitHub Actions 表达式），
+    /// 提示词需要先经 [`VaultService::render_prompt`] 替换后再放入编辑器。
+    pub fn write_config_file(
+        &self,
+        client_id: &str,
+        config_path: Option<String>,
+        content: String,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<()> {
+        let resolved = self.resolve_client_path(client_id, config_path)?;
+        // 结构化配置文件语法错误时拒绝写入，避免损坏的文件被保存并进入快照
+        let format = ConfigFormat::from_path(&resolved);
+        structured::validate(format, &content)
+            .map_err(|err| err.into_error(format).with_path(&resolved))?;
+        let path = resolve_path(&resolved);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| VaultError::create_dir_failed(parent, e))?;
+        }
+        // 内容来自编辑器本身，写入后无需让界面重新加载；只有写入失败时才通知，撤销写入前的登记
+        sink.config_files_writing(client_id, &[(path.clone(), content.as_str())]);
+        atomic_write(&path, &content).inspect_err(|_| sink.config_files_written(client_id, &[]))
+    }
+
+    pub(super) fn resolve_client_path(
+        &self,
+        client_id: &str,
+        config_path: Option<String>,
+    ) -> VaultResult<String> {
+        let client = self.require_client(client_id)?;
+        let sanitized_path = sanitize_optional_path(config_path)?;
+        match client.resolve_config_path(sanitized_path.as_deref()) {
+            // 只配置了通配模式时使用其匹配到的第一个文件
+            Err(err) if sanitized_path.is_none() => {
+                client_config_files(&client).into_iter().next().ok_or(err)
+            }
+            result => result,
+        }
+    }
+}
+
+fn sanitize_config_paths(paths: Vec<String>) -> VaultResult<Vec<String>> {
+    let paths: Vec<String> = paths
+        .into_iter()
+        .map(|path| path.trim().to_string())
+        .collect();
+
+    if paths.is_empty() {
+        return Err(ErrorCode::ClientNoConfigPaths.into());
+    }
+
+    if paths.iter().any(|path| path.is_empty()) {
+        return Err(ErrorCode::ClientConfigPathEmpty.into());
+    }
+
+    Ok(paths)
+}
+
+fn sanitize_optional_path(path: Option<String>) -> VaultResult<Option<String>> {
+    match path {
+        Some(value) => {
+            let trimmed = value.trim();
+            if trimmed.is_empty() {
+                Err(ErrorCode::ClientConfigPathEmpty.into())
+            } else {
+                Ok(Some(trimmed.to_string()))
+            }
+        }
+        None => Ok(None),
+    }
+}
+
+fn parse_and_validate_clients(json_data: &str) -> VaultResult<Vec<ClientExportData>> {
+    let trimmed = json_data.trim();
+    if trimmed.is_empty() {
+        return Err(ErrorCode::ImportEmpty.into());
+    }
+
+    let value: Value = serde_json::from_str(trimmed)
+        .map_err(|e| VaultError::new(ErrorCode::ImportInvalidJson).with_cause(e))?;
+    let entries = value
+        .as_array()
+        .ok_or_else(|| VaultError::new(ErrorCode::ImportNotArray).with_param("entity", ENTITY))?;
+    validate_client_entries(entries)?;
+
+    let clients: Vec<ClientExportData> = serde_json::from_value(value)
+        .map_err(|e| VaultError::new(ErrorCode::ImportInvalidJson).with_cause(e))?;
+    validate_client_models(&clients)?;
+    Ok(clients)
+}
+
+fn validate_client_entries(entries: &[Value]) -> VaultResult<()> {
+    for (index, entry) in entries.iter().enumerate() {
+        let obj = entry.as_object().ok_or_else(|| {
+            VaultError::new(ErrorCode::ImportEntryNotObject)
+                .with_param("entity", ENTITY)
+                .with_param("index", index + 1)
+        })?;
+
+        ensure_client_string_field(obj, "id", index)?;
+        ensure_client_string_field(obj, "name", index)?;
+
+        match obj.get("config_file_paths") {
+            Some(Value::Array(paths)) if !paths.is_empty() => {
+                for path_value in paths {
+                    match path_value.as_str() {
+                        Some(path) if path.trim().is_empty() => {
+                            return Err(field_error(
+                                ErrorCode::ImportFieldEmpty,
+                                index,
+                                "config_file_paths",
+                            ));
+                        }
+                        Some(_) => {}
+                        None => {
+                            return Err(field_error(
+                                ErrorCode::ImportFieldInvalid,
+                                index,
+                                "config_file_paths",
+                            ));
+                        }
+                    }
+                }
+            }
+            Some(Value::Array(_)) => {
+                return Err(field_error(
+                    ErrorCode::ImportFieldEmpty,
+                    index,
+                    "config_file_paths",
+                ))
+            }
+            Some(_) => {
+                return Err(field_error(
+                    ErrorCode::ImportFieldInvalid,
+                    index,
+                    "config_file_paths",
+                ))
+            }
+            None => {
+                return Err(field_error(
+                    ErrorCode::ImportFieldMissing,
+                    index,
+                    "config_file_paths",
+                ))
+            }
+        }
+
+        match obj.get("config_contents") {
+            Some(Value::Object(map)) => {
+                for (path, value) in map {
+                    if !value.is_string() {
+                        return Err(field_error(
+                            ErrorCode::ImportFieldInvalid,
+                            index,
+                            "config_contents",
+                        )
+                        .with_path(path));
+                    }
+                }
+            }
+            Some(_) => {
+                return Err(field_error(
+                    ErrorCode::ImportFieldInvalid,
+                    index,
+                    "config_contents",
+                ))
+            }
+            None => {
+                return Err(field_error(
+                    ErrorCode::ImportFieldMissing,
+                    index,
+                    "config_contents",
+                ))
+            }
+        }
+
+        match obj.get("auto_tag") {
+            Some(Value::Bool(_)) => {}
+            Some(_) => {
+                return Err(field_error(
+                    ErrorCode::ImportFieldInvalid,
+                    index,
+                    "auto_tag",
+                ))
+            }
+            None => {
+                return Err(field_error(
+                    ErrorCode::ImportFieldMissing,
+                    index,
+                    "auto_tag",
+                ))
+            }
+        }
+
+        if let Some(value) = obj.get("active_config_path") {
+            if !(value.is_string() || value.is_null()) {
+                return Err(field_error(
+                    ErrorCode::ImportFieldInvalid,
+                    index,
+                    "active_config_path",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn ensure_client_string_field(
+    obj: &serde_json::Map<String, Value>,
+    field: &str,
+    index: usize,
+) -> VaultResult<()> {
+    match obj.get(field) {
+        Some(Value::String(value)) if !value.trim().is_empty() => Ok(()),
+        Some(Value::String(_)) => Err(field_error(ErrorCode::ImportFieldEmpty, index, field)),
+        Some(_) => Err(field_error(ErrorCode::ImportFieldInvalid, index, field)),
+        None => Err(field_error(ErrorCode::ImportFieldMissing, index, field)),
+    }
+}
+
+fn validate_client_models(clients: &[ClientExportData]) -> VaultResult<()> {
+    for (index, client) in clients.iter().enumerate() {
+        if client.id.trim().is_empty() {
+            return Err(field_error(ErrorCode::ImportFieldEmpty, index, "id"));
+        }
+        if client.name.trim().is_empty() {
+            return Err(field_error(ErrorCode::ImportFieldEmpty, index, "name"));
+        }
+        if client.config_file_paths.is_empty()
+            || client
+                .config_file_paths
+                .iter()
+                .any(|path| path.trim().is_empty())
+        {
+            return Err(field_error(
+                ErrorCode::ImportFieldEmpty,
+                index,
+                "config_file_paths",
+            ));
+        }
+        if let Some(active) = client.active_config_path.as_ref() {
+            if active.trim().is_empty() {
+                return Err(field_error(
+                    ErrorCode::ImportFieldEmpty,
+                    index,
+                    "active_config_path",
+                ));
+            }
+            if !is_listed_path(&client.config_file_paths, active) {
+                return Err(VaultError::new(ErrorCode::ClientActivePathNotListed)
+                    .with_param("entity", ENTITY)
+                    .with_param("index", index + 1)
+                    .with_path(active));
+            }
+        }
+        // 通配模式导出的是当时匹配到的文件，不要求与模式本身对应的内容
+        for path in &client.config_file_paths {
+            if !is_path_pattern(path) && !client.config_contents.contains_key(path) {
+                return Err(VaultError::new(ErrorCode::ClientConfigContentMissing)
+                    .with_param("entity", ENTITY)
+                    .with_param("index", index + 1)
+                    .with_path(path));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn field_error(code: ErrorCode, index: usize, field: &str) -> VaultError {
+    VaultError::import_field(code, ENTITY, index, field)
+}
+
+fn write_client_config_files(
+    client: &ClientConfig,
+    contents: &HashMap<String, String>,
+) -> VaultResult<()> {
+    for path in &client.config_file_paths {
+        if is_path_pattern(path) {
+            continue;
+        }
+        let data = contents.get(path).ok_or_else(|| {
+            VaultError::new(ErrorCode::ClientConfigContentMissing)
+                .with_param("client_id", &client.id)
+                .with_path(path)
+        })?;
+        atomic_write(resolve_path(path), data)?;
+    }
+    // 通配模式匹配的文件按导出时的内容写回
+    for (path, data) in contents {
+        if !client.config_file_paths.contains(path) && client.has_config_path(path) {
+            atomic_write(resolve_path(path), data)?;
+        }
+    }
+    Ok(())
+}
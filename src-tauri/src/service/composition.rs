@@ -0,0 +1,366 @@
+use super::variables::VariableContext;
+use super::{ErrorCode, VaultError, VaultEventSink, VaultResult, VaultService};
+use crate::models::{Composition, CompositionItem, CompositionPreview, CompositionSource};
+use crate::storage::{load_store, AppConfig};
+use crate::utils::file_ops::atomic_write;
+use crate::utils::path_utils::resolve_path;
+use chrono::Utc;
+use std::path::PathBuf;
+
+impl VaultService {
+    pub fn get_compositions(&self) -> VaultResult<Vec<Composition>> {
+        let store = load_store()?;
+        Ok(store.config().compositions.clone())
+    }
+
+    pub fn create_composition(
+        &self,
+        name: String,
+        client_id: String,
+        config_path: Option<String>,
+        items: Vec<CompositionItem>,
+    ) -> VaultResult<Composition> {
+        let composition = Composition::new(name.trim(), client_id, config_path, items);
+        self.validate_composition(&composition)?;
+        let mut store = load_store()?;
+        store.config_mut().compositions.push(composition.clone());
+        store.save()?;
+        Ok(composition)
+    }
+
+    pub fn update_composition(
+        &self,
+        id: &str,
+        name: String,
+        client_id: String,
+        config_path: Option<String>,
+        items: Vec<CompositionItem>,
+    ) -> VaultResult<Composition> {
+        let mut store = load_store()?;
+        let target = find_composition_mut(store.config_mut(), id)?;
+        let mut updated = target.clone();
+        updated.name = name.trim().to_string();
+        updated.client_id = client_id;
+        updated.config_path = config_path;
+        updated.items = items;
+        self.validate_composition(&updated)?;
+        updated.touch();
+        *target = updated.clone();
+        store.save()?;
+        Ok(updated)
+    }
+
+    /// 删除组合本身，已写入的配置文件保持不变
+    pub fn delete_composition(&self, id: &str) -> VaultResult<()> {
+        let mut store = load_store()?;
+        let before = store.config().compositions.len();
+        store
+            .config_mut()
+            .compositions
+            .retain(|composition| composition.id != id);
+        if before == store.config().compositions.len() {
+            return Err(composition_not_found(id));
+        }
+        store.save()
+    }
+
+    /// 按当前提示词内容渲染组合，不写入文件
+    pub fn preview_composition(&self, id: &str) -> VaultResult<CompositionPreview> {
+        let store = load_store()?;
+        let composition = store
+            .config()
+            .compositions
+            .iter()
+            .find(|composition| composition.id == id)
+            .ok_or_else(|| composition_not_found(id))?;
+        self.render_composition_content(store.config(), composition)
+    }
+
+    /// 渲染组合并写入绑定的配置文件，写入前后通知 `sink`
+    pub fn render_composition(
+        &self,
+        id: &str,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<Composition> {
+        let mut store = load_store()?;
+        let composition = find_composition_mut(store.config_mut(), id)?.clone();
+        let target = self.composition_target(&composition)?;
+        let preview = self.render_composition_content(store.config(), &composition)?;
+        write_composition(&composition, target, &preview.content, sink)?;
+
+        let target = find_composition_mut(store.config_mut(), id)?;
+        mark_rendered(target, preview.sources);
+        let rendered = target.clone();
+        store.save()?;
+        Ok(rendered)
+    }
+
+    /// 成员提示词变化后重新写入引用它们且已渲染过的组合
+    ///
+    /// 提示词本身已保存成功，因此这里的失败只记录到组合的 `last_error` 中，不向调用方返回。
+    pub(super) fn refresh_compositions(&self, prompt_ids: &[String], sink: &dyn VaultEventSink) {
+        if let Err(err) = self.try_refresh_compositions(prompt_ids, sink) {
+            eprintln!("[Composition] 重新渲染组合失败: {}", err);
+        }
+    }
+
+    fn try_refresh_compositions(
+        &self,
+        prompt_ids: &[String],
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<()> {
+        let mut store = load_store()?;
+        let affected: Vec<Composition> = store
+            .config()
+            .compositions
+            .iter()
+            .filter(|composition| needs_refresh(composition, prompt_ids))
+            .cloned()
+            .collect();
+        if affected.is_empty() {
+            return Ok(());
+        }
+
+        for composition in affected {
+            let result = self.composition_target(&composition).and_then(|target| {
+                let preview = self.render_composition_content(store.config(), &composition)?;
+                write_composition(&composition, target, &preview.content, sink)?;
+                Ok(preview.sources)
+            });
+            let target = find_composition_mut(store.config_mut(), &composition.id)?;
+            match result {
+                Ok(sources) => mark_rendered(target, sources),
+                Err(err) => {
+                    eprintln!("[Composition] 组合 {} 渲染失败: {}", composition.id, err);
+                    target.last_error = Some(err.to_string());
+                }
+            }
+        }
+        store.save()
+    }
+
+    fn validate_composition(&self, composition: &Composition) -> VaultResult<()> {
+        if composition.name.is_empty() {
+            return Err(ErrorCode::CompositionNameEmpty.into());
+        }
+        if !composition
+            .items
+            .iter()
+            .any(|item| matches!(item, CompositionItem::Prompt { .. }))
+        {
+            return Err(ErrorCode::CompositionEmpty.into());
+        }
+        self.composition_target(composition)?;
+
+        let repo = self.lock_prompts()?;
+        for item in &composition.items {
+            if let CompositionItem::Prompt { prompt_id } = item {
+                if repo.get_by_id(prompt_id)?.is_none() {
+                    return Err(VaultError::new(ErrorCode::PromptNotFound)
+                        .with_param("prompt_id", prompt_id));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn composition_target(&self, composition: &Composition) -> VaultResult<PathBuf> {
+        let client = self.require_client(&composition.client_id)?;
+        let path = client.resolve_config_path(composition.config_path.as_deref())?;
+        Ok(resolve_path(path))
+    }
+
+    /// 依次拼接各项并记录行号；每项单独替换模板变量，保证行号与写出的内容一致
+    fn render_composition_content(
+        &self,
+        config: &AppConfig,
+        composition: &Composition,
+    ) -> VaultResult<CompositionPreview> {
+        let context = VariableContext::new(config, None, Some(&composition.client_id));
+        let repo = self.lock_prompts()?;
+        let mut builder = ContentBuilder::default();
+        for (item_index, item) in composition.items.iter().enumerate() {
+            let (prompt_id, text) = match item {
+                CompositionItem::Prompt { prompt_id } => {
+                    let prompt = repo.get_by_id(prompt_id)?.ok_or_else(|| {
+                        VaultError::new(ErrorCode::PromptNotFound)
+                            .with_param("prompt_id", prompt_id)
+                            .with_param("composition_id", &composition.id)
+                    })?;
+                    (Some(prompt_id.clone()), prompt.content)
+                }
+                CompositionItem::Separator { text } => (None, text.clone()),
+            };
+            let text = context.render(&text).map_err(|err| {
+                err.with_param("composition_id", &composition.id)
+                    .with_param("item_index", item_index)
+            })?;
+            builder.push(item_index, prompt_id, &text);
+        }
+        Ok(builder.finish())
+    }
+}
+
+/// 逐项拼接组合内容并记录每项所占的行号范围
+#[derive(Default)]
+struct ContentBuilder {
+    content: String,
+    sources: Vec<CompositionSource>,
+}
+
+impl ContentBuilder {
+    /// 追加一项，与上一项之间补齐换行；空文本不占行也不记录来源
+    fn push(&mut self, item_index: usize, prompt_id: Option<String>, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if !self.content.is_empty() && !self.content.ends_with('\n') {
+            self.content.push('\n');
+        }
+        let start_line = self.content.matches('\n').count() + 1;
+        self.content.push_str(text);
+        self.sources.push(CompositionSource {
+            item_index,
+            prompt_id,
+            start_line,
+            end_line: start_line + text.lines().count() - 1,
+        });
+    }
+
+    fn finish(self) -> CompositionPreview {
+        CompositionPreview {
+            content: self.content,
+            sources: self.sources,
+        }
+    }
+}
+
+/// 从未渲染过的组合还没有写入过目标文件，不应由提示词修改触发覆盖
+fn needs_refresh(composition: &Composition, prompt_ids: &[String]) -> bool {
+    composition.last_rendered_at.is_some() && prompt_ids.iter().any(|id| composition.references(id))
+}
+
+/// 写入组合的目标文件，写入前后通知 `sink` 以免被当作外部修改
+fn write_composition(
+    composition: &Composition,
+    target: PathBuf,
+    content: &str,
+    sink: &dyn VaultEventSink,
+) -> VaultResult<()> {
+    sink.config_files_writing(&composition.client_id, &[(target.clone(), content)]);
+    let result = atomic_write(&target, content);
+    let targets = [target];
+    let written: &[PathBuf] = if result.is_ok() { &targets } else { &[] };
+    sink.config_files_written(&composition.client_id, written);
+    result
+}
+
+fn mark_rendered(composition: &mut Composition, sources: Vec<CompositionSource>) {
+    composition.sources = sources;
+    composition.last_rendered_at = Some(Utc::now());
+    composition.last_error = None;
+}
+
+fn find_composition_mut<'a>(
+    config: &'a mut AppConfig,
+    id: &str,
+) -> VaultResult<&'a mut Composition> {
+    config
+        .compositions
+        .iter_mut()
+        .find(|composition| composition.id == id)
+        .ok_or_else(|| composition_not_found(id))
+}
+
+fn composition_not_found(id: &str) -> VaultError {
+    VaultError::new(ErrorCode::CompositionNotFound).with_param("composition_id", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(
+        item_index: usize,
+        prompt_id: Option<&str>,
+        start_line: usize,
+        end_line: usize,
+    ) -> CompositionSource {
+        CompositionSource {
+            item_index,
+            prompt_id: prompt_id.map(str::to_string),
+            start_line,
+            end_line,
+        }
+    }
+
+    fn composition(prompt_ids: &[&str]) -> Composition {
+        let items = prompt_ids
+            .iter()
+            .map(|id| CompositionItem::Prompt {
+                prompt_id: id.to_string(),
+            })
+            .collect();
+        Composition::new("c", "claude", None, items)
+    }
+
+    #[test]
+    fn builder_tracks_line_ranges_per_item() {
+        let mut builder = ContentBuilder::default();
+        builder.push(0, Some("a".into()), "one\ntwo");
+        builder.push(1, None, "---");
+        builder.push(2, Some("b".into()), "three\nfour\nfive\n");
+        builder.push(3, Some("c".into()), "six");
+        let preview = builder.finish();
+
+        assert_eq!(preview.content, "one\ntwo\n---\nthree\nfour\nfive\nsix");
+        assert_eq!(
+            preview.sources,
+            vec![
+                source(0, Some("a"), 1, 2),
+                source(1, None, 3, 3),
+                source(2, Some("b"), 4, 6),
+                source(3, Some("c"), 7, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn builder_skips_empty_items_without_shifting_lines() {
+        let mut builder = ContentBuilder::default();
+        builder.push(0, None, "");
+        builder.push(1, Some("a".into()), "one");
+        builder.push(2, None, "");
+        builder.push(3, Some("b".into()), "two");
+        let preview = builder.finish();
+
+        assert_eq!(preview.content, "one\ntwo");
+        assert_eq!(
+            preview.sources,
+            vec![source(1, Some("a"), 1, 1), source(3, Some("b"), 2, 2)]
+        );
+    }
+
+    #[test]
+    fn refresh_only_targets_rendered_compositions_referencing_the_prompt() {
+        let ids = vec!["a".to_string()];
+        let mut rendered = composition(&["a", "b"]);
+        mark_rendered(&mut rendered, Vec::new());
+        assert!(needs_refresh(&rendered, &ids));
+
+        let never_rendered = composition(&["a"]);
+        assert!(!needs_refresh(&never_rendered, &ids));
+
+        let mut unrelated = composition(&["b"]);
+        mark_rendered(&mut unrelated, Vec::new());
+        assert!(!needs_refresh(&unrelated, &ids));
+    }
+
+    #[test]
+    fn failed_refresh_keeps_the_composition_eligible() {
+        let mut failed = composition(&["a"]);
+        mark_rendered(&mut failed, Vec::new());
+        failed.last_error = Some("prompt missing".into());
+        assert!(needs_refresh(&failed, &["a".to_string()]));
+    }
+}
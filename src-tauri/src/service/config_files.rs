@@ -0,0 +1,119 @@
+use super::{VaultError, VaultResult};
+use crate::models::ClientConfig;
+use crate::utils::file_ops::atomic_write;
+use crate::utils::path_utils::{expand_path_entry, resolve_path};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// 客户端当前的全部配置文件：通配模式展开为此刻匹配的文件，普通路径即使不存在也保留
+pub fn client_config_files(client: &ClientConfig) -> Vec<String> {
+    let mut seen = HashSet::new();
+    client
+        .config_file_paths
+        .iter()
+        .flat_map(|entry| expand_path_entry(entry))
+        .filter(|path| seen.insert(path.clone()))
+        .collect()
+}
+
+/// 读取客户端全部配置文件，不存在的文件视为空内容
+pub fn read_client_config_files(client: &ClientConfig) -> VaultResult<HashMap<String, String>> {
+    let mut contents = HashMap::new();
+    for path in client_config_files(client) {
+        let content = read_optional_file(&path)?;
+        contents.insert(path, content);
+    }
+    Ok(contents)
+}
+
+pub(crate) fn read_optional_file(path: &str) -> VaultResult<String> {
+    match fs::read_to_string(resolve_path(path)) {
+        Ok(value) => Ok(value),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(VaultError::read_failed(path, err)),
+    }
+}
+
+struct WrittenFile {
+    path: PathBuf,
+    existed: bool,
+    original_content: String,
+}
+
+/// 依次写入多个文件并删除 `removals` 中的文件，任一失败时回滚已完成的改动
+///
+/// 返回实际写入或删除的文件，不存在的待删除文件会被跳过。
+pub(crate) fn write_files_atomically(
+    entries: Vec<(String, String)>,
+    removals: Vec<String>,
+) -> VaultResult<Vec<PathBuf>> {
+    let mut written: Vec<WrittenFile> = Vec::new();
+
+    for (path, content) in entries {
+        let expanded = resolve_path(&path);
+        let (existed, original_content) = match fs::read_to_string(&expanded) {
+            Ok(value) => (true, value),
+            Err(err) if err.kind() == ErrorKind::NotFound => (false, String::new()),
+            Err(err) => {
+                rollback_written_files(&written);
+                return Err(VaultError::read_failed(&path, err));
+            }
+        };
+
+        if let Err(err) = atomic_write(&expanded, &content) {
+            rollback_written_files(&written);
+            return Err(err);
+        }
+
+        written.push(WrittenFile {
+            path: expanded,
+            existed,
+            original_content,
+        });
+    }
+
+    for path in removals {
+        let expanded = resolve_path(&path);
+        let original_content = match fs::read_to_string(&expanded) {
+            Ok(value) => value,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => {
+                rollback_written_files(&written);
+                return Err(VaultError::read_failed(&path, err));
+            }
+        };
+
+        if let Err(err) = fs::remove_file(&expanded) {
+            rollback_written_files(&written);
+            return Err(VaultError::remove_failed(&path, err));
+        }
+
+        written.push(WrittenFile {
+            path: expanded,
+            existed: true,
+            original_content,
+        });
+    }
+
+    Ok(written.into_iter().map(|file| file.path).collect())
+}
+
+fn rollback_written_files(written: &[WrittenFile]) {
+    for file in written.iter().rev() {
+        if file.existed {
+            if let Err(err) = atomic_write(&file.path, &file.original_content) {
+                eprintln!("[Snapshot] 回滚写入失败: {} ({})", file.path.display(), err);
+            }
+        } else if file.path.exists() {
+            if let Err(err) = fs::remove_file(&file.path) {
+                eprintln!(
+                    "[Snapshot] 删除临时文件失败: {} ({})",
+                    file.path.display(),
+                    err
+                );
+            }
+        }
+    }
+}
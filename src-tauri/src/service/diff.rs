@@ -0,0 +1,110 @@
+use crate::models::{DiffHunk, DiffLine, DiffLineKind, FileChangeKind, FileDiff};
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 统一差异中每个块保留的上下文行数
+const CONTEXT_LINES: usize = 3;
+
+/// 按路径比较两组文件，返回有变化的文件，按路径排序
+pub(crate) fn diff_file_sets(
+    old_files: &BTreeMap<String, String>,
+    new_files: &BTreeMap<String, String>,
+    old_label: &str,
+    new_label: &str,
+) -> Vec<FileDiff> {
+    let paths: BTreeSet<&String> = old_files.keys().chain(new_files.keys()).collect();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let old = old_files.get(path).map(String::as_str).unwrap_or_default();
+            let new = new_files.get(path).map(String::as_str).unwrap_or_default();
+            diff_file(path, old, new, old_label, new_label)
+        })
+        .collect()
+}
+
+fn diff_file(
+    path: &str,
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+) -> Option<FileDiff> {
+    if old == new {
+        return None;
+    }
+    let change = if old.is_empty() {
+        FileChangeKind::Added
+    } else if new.is_empty() {
+        FileChangeKind::Removed
+    } else {
+        FileChangeKind::Modified
+    };
+
+    let diff = TextDiff::from_lines(old, new);
+    let mut added_lines = 0;
+    let mut removed_lines = 0;
+    let mut hunks = Vec::new();
+    for group in diff.grouped_ops(CONTEXT_LINES) {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+        let old_range = first.old_range().start..last.old_range().end;
+        let new_range = first.new_range().start..last.new_range().end;
+        let mut lines = Vec::new();
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let kind = match change.tag() {
+                    ChangeTag::Equal => DiffLineKind::Context,
+                    ChangeTag::Insert => {
+                        added_lines += 1;
+                        DiffLineKind::Added
+                    }
+                    ChangeTag::Delete => {
+                        removed_lines += 1;
+                        DiffLineKind::Removed
+                    }
+                };
+                lines.push(DiffLine {
+                    kind,
+                    content: change.value().trim_end_matches(['\n', '\r']).to_string(),
+                    old_line: change.old_index().map(|index| index + 1),
+                    new_line: change.new_index().map(|index| index + 1),
+                });
+            }
+        }
+        hunks.push(DiffHunk {
+            old_start: unified_start(&old_range),
+            old_lines: old_range.len(),
+            new_start: unified_start(&new_range),
+            new_lines: new_range.len(),
+            lines,
+        });
+    }
+
+    let unified = diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(
+            &format!("{} ({})", path, old_label),
+            &format!("{} ({})", path, new_label),
+        )
+        .to_string();
+    Some(FileDiff {
+        path: path.to_string(),
+        change,
+        added_lines,
+        removed_lines,
+        hunks,
+        unified,
+    })
+}
+
+/// 统一差异格式中空区间的起始行号指向其前一行
+fn unified_start(range: &std::ops::Range<usize>) -> usize {
+    if range.is_empty() {
+        range.start
+    } else {
+        range.start + 1
+    }
+}
@@ -0,0 +1,189 @@
+//! 扫描本机已有的 AI 工具指令文件，生成待确认的客户端配置
+
+use super::{ErrorCode, VaultError, VaultResult, VaultService};
+use crate::client_catalog::{load_catalog, CatalogEntry};
+use crate::models::{ClientConfig, ClientImportResult, DiscoveredClient, DiscoveryConfidence};
+use crate::utils::path_utils::{
+    contract_tilde, expand_file_pattern, expand_path_entry, resolve_path,
+};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+impl VaultService {
+    /// 扫描主目录与可选的项目根目录，按可信程度从高到低返回建议的客户端
+    ///
+    /// 已被任一客户端使用的路径不会再次建议；同 ID 客户端已存在时只建议新增的路径。
+    pub fn discover_clients(
+        &self,
+        project_root: Option<&str>,
+    ) -> VaultResult<Vec<DiscoveredClient>> {
+        let home = dirs::home_dir().ok_or(ErrorCode::HomeDirUnavailable)?;
+        let project_root = match project_root.map(str::trim).filter(|root| !root.is_empty()) {
+            Some(root) => {
+                let expanded = resolve_path(root);
+                if !expanded.is_dir() {
+                    return Err(VaultError::new(ErrorCode::ProjectNotDirectory).with_path(root));
+                }
+                Some(expanded)
+            }
+            None => None,
+        };
+
+        let clients = self.get_clients()?;
+        let used: HashSet<PathBuf> = clients
+            .iter()
+            .flat_map(|client| client.config_file_paths.iter())
+            .map(resolve_path)
+            .collect();
+
+        let catalog = load_catalog()?;
+        let mut discovered: Vec<DiscoveredClient> = catalog
+            .entries()
+            .iter()
+            .filter_map(|tool| scan_tool(tool, &home, project_root.as_deref(), &used))
+            .map(|(tool, confidence, paths, found_paths)| {
+                let existing = clients.iter().find(|client| client.id == tool.id);
+                let name = existing.map_or(tool.name.as_str(), |client| client.name.as_str());
+                DiscoveredClient {
+                    client: ClientConfig::new_custom(tool.id.as_str(), name, paths, true),
+                    confidence,
+                    found_paths,
+                    existing: existing.is_some(),
+                }
+            })
+            .collect();
+        discovered.sort_by_key(|item| (Reverse(item.confidence), item.client.id.clone()));
+        Ok(discovered)
+    }
+
+    /// 批量接受发现结果：新客户端以自定义客户端添加，已存在的客户端追加缺少的路径
+    pub fn accept_discovered_clients(
+        &self,
+        clients: Vec<ClientConfig>,
+    ) -> VaultResult<ClientImportResult> {
+        let mut repo = self.lock_clients()?;
+        let total = clients.len();
+        let mut added = 0;
+        let mut updated = 0;
+
+        for proposal in clients {
+            if proposal.id.trim().is_empty() {
+                return Err(ErrorCode::ClientIdEmpty.into());
+            }
+            let paths: Vec<String> = proposal
+                .config_file_paths
+                .into_iter()
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .collect();
+
+            match repo.get_by_id(&proposal.id)? {
+                Some(mut client) => {
+                    let before = client.config_file_paths.len();
+                    for path in paths {
+                        if !client.config_file_paths.contains(&path)
+                            && !client.has_config_path(&path)
+                        {
+                            client.config_file_paths.push(path);
+                        }
+                    }
+                    if client.config_file_paths.len() == before {
+                        continue;
+                    }
+                    client.ensure_active_path();
+                    repo.save(client)?;
+                    updated += 1;
+                }
+                None => {
+                    if proposal.name.trim().is_empty() {
+                        return Err(ErrorCode::ClientNameEmpty.into());
+                    }
+                    if paths.is_empty() {
+                        return Err(VaultError::new(ErrorCode::ClientNoConfigPaths)
+                            .with_param("client_id", &proposal.id));
+                    }
+                    let client = ClientConfig::new_custom(
+                        proposal.id,
+                        proposal.name,
+                        paths,
+                        proposal.auto_tag,
+                    );
+                    repo.save(client)?;
+                    added += 1;
+                }
+            }
+        }
+
+        Ok(ClientImportResult {
+            total,
+            added,
+            updated,
+        })
+    }
+}
+
+/// 扫描单个工具，返回可信程度、建议的路径与其中实际存在的文件
+fn scan_tool<'a>(
+    tool: &'a CatalogEntry,
+    home: &Path,
+    project_root: Option<&Path>,
+    used: &HashSet<PathBuf>,
+) -> Option<(
+    &'a CatalogEntry,
+    DiscoveryConfidence,
+    Vec<String>,
+    Vec<String>,
+)> {
+    let installed = tool
+        .markers
+        .iter()
+        .any(|marker| resolve_path(marker).exists());
+    // 全局文件沿用目录中的写法（如 `$CODEX_HOME/AGENTS.md`），项目文件以实际路径保存
+    let mut matches: Vec<(PathBuf, String, DiscoveryConfidence)> = Vec::new();
+    for pattern in &tool.global_paths {
+        for stored in expand_path_entry(pattern) {
+            let path = resolve_path(&stored);
+            if path.is_file() {
+                matches.push((path, stored, DiscoveryConfidence::High));
+            }
+        }
+    }
+    if let Some(root) = project_root {
+        for pattern in &tool.project_paths {
+            for path in expand_file_pattern(&root.join(pattern)) {
+                let stored = contract_tilde(&path, home);
+                matches.push((path, stored, DiscoveryConfidence::High));
+            }
+        }
+        // 共用文件名只有在工具已安装时才视为高可信
+        let shared = if installed {
+            DiscoveryConfidence::High
+        } else {
+            DiscoveryConfidence::Medium
+        };
+        for pattern in &tool.shared_project_paths {
+            for path in expand_file_pattern(&root.join(pattern)) {
+                let stored = contract_tilde(&path, home);
+                matches.push((path, stored, shared));
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    matches.retain(|(path, _, _)| !used.contains(path) && seen.insert(path.clone()));
+    if let Some(confidence) = matches.iter().map(|(_, _, confidence)| *confidence).max() {
+        let paths: Vec<String> = matches.into_iter().map(|(_, stored, _)| stored).collect();
+        return Some((tool, confidence, paths.clone(), paths));
+    }
+
+    // 只找到安装痕迹时建议创建第一个不含通配符的全局指令文件
+    if !installed {
+        return None;
+    }
+    let stored = tool.fixed_global_paths().into_iter().next()?;
+    if used.contains(&resolve_path(&stored)) {
+        return None;
+    }
+    Some((tool, DiscoveryConfidence::Low, vec![stored], Vec::new()))
+}
@@ -0,0 +1,24 @@
+use std::path::PathBuf;
+
+/// 接收服务层产生的副作用通知，由 Tauri、CLI 等适配层实现
+///
+/// 所有方法都有空实现，适配层只需覆盖自己关心的事件。
+pub trait VaultEventSink {
+    /// 即将覆盖客户端配置文件，`files` 为目标路径与将要写入的内容
+    fn config_files_writing(&self, _client_id: &str, _files: &[(PathBuf, &str)]) {}
+
+    /// 即将删除客户端配置文件，例如恢复快照时删除快照之后新增的文件
+    fn config_files_removing(&self, _client_id: &str, _paths: &[PathBuf]) {}
+
+    /// 写入流程结束，`paths` 只包含最终写入或删除成功的文件（失败回滚时为空）
+    fn config_files_written(&self, _client_id: &str, _paths: &[PathBuf]) {}
+
+    /// 客户端的快照列表发生变化
+    fn snapshots_changed(&self, _client_id: &str) {}
+}
+
+/// 忽略所有事件，用于 CLI 等没有界面需要同步的场景
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopEventSink;
+
+impl VaultEventSink for NoopEventSink {}
@@ -0,0 +1,232 @@
+//! 按行的三方合并
+//!
+//! 分别计算基准到双方的差异，把修改区间映射回基准的行号后按位置归并：
+//! 只有一方修改的区间直接采用该方内容，双方修改重叠且结果不同时记为冲突。
+
+use crate::models::MergeConflict;
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use std::ops::Range;
+
+pub(crate) struct MergeOutput {
+    pub content: String,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// 一方相对基准的一处修改：基准中的 `base` 区间被替换为该方的 `side` 区间
+#[derive(Debug, Clone)]
+struct Change {
+    base: Range<usize>,
+    side: Range<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Ours,
+    Theirs,
+}
+
+pub(crate) fn merge_three_way(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    ours_label: &str,
+    theirs_label: &str,
+) -> MergeOutput {
+    let base_lines = split_lines(base);
+    let ours_lines = split_lines(ours);
+    let theirs_lines = split_lines(theirs);
+
+    let mut changes: Vec<(Side, Change)> = changes(&base_lines, &ours_lines)
+        .into_iter()
+        .map(|change| (Side::Ours, change))
+        .chain(
+            changes(&base_lines, &theirs_lines)
+                .into_iter()
+                .map(|change| (Side::Theirs, change)),
+        )
+        .collect();
+    changes.sort_by_key(|(_, change)| (change.base.start, change.base.end));
+
+    let mut content = String::new();
+    let mut conflicts = Vec::new();
+    let mut cursor = 0;
+    let mut index = 0;
+    while index < changes.len() {
+        // 把与当前区间重叠的修改（可能来自双方）合成一组
+        let mut group = vec![&changes[index]];
+        let mut span = changes[index].1.base.clone();
+        index += 1;
+        while index < changes.len() && overlaps(&span, &changes[index].1.base) {
+            span.end = span.end.max(changes[index].1.base.end);
+            group.push(&changes[index]);
+            index += 1;
+        }
+
+        content.extend(base_lines[cursor..span.start].iter().copied());
+        cursor = span.end;
+
+        let ours_text = side_text(&group, Side::Ours, &span, &ours_lines);
+        let theirs_text = side_text(&group, Side::Theirs, &span, &theirs_lines);
+        match (ours_text, theirs_text) {
+            (Some(text), None) | (None, Some(text)) => content.push_str(&text),
+            (Some(ours_text), Some(theirs_text)) if ours_text == theirs_text => {
+                content.push_str(&ours_text)
+            }
+            (Some(ours_text), Some(theirs_text)) => {
+                push_conflict(
+                    &mut content,
+                    &ours_text,
+                    &theirs_text,
+                    ours_label,
+                    theirs_label,
+                );
+                conflicts.push(MergeConflict {
+                    base_start_line: span.start + 1,
+                    base_line_count: span.len(),
+                    base: base_lines[span.clone()].concat(),
+                    ours: ours_text,
+                    theirs: theirs_text,
+                });
+            }
+            (None, None) => {}
+        }
+    }
+    content.extend(base_lines[cursor..].iter().copied());
+
+    MergeOutput { content, conflicts }
+}
+
+/// 按行切分并保留换行符，拼接后与原文完全一致
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn changes(base: &[&str], side: &[&str]) -> Vec<Change> {
+    let mut changes: Vec<Change> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, side) {
+        if let DiffOp::Equal { .. } = op {
+            continue;
+        }
+        let (base_range, side_range) = (op.old_range(), op.new_range());
+        match changes.last_mut() {
+            Some(last)
+                if last.base.end == base_range.start && last.side.end == side_range.start =>
+            {
+                last.base.end = base_range.end;
+                last.side.end = side_range.end;
+            }
+            _ => changes.push(Change {
+                base: base_range,
+                side: side_range,
+            }),
+        }
+    }
+    changes
+}
+
+/// 区间有交集，或两处插入落在同一位置时视为重叠
+fn overlaps(span: &Range<usize>, next: &Range<usize>) -> bool {
+    next.start < span.end || next.start == span.start
+}
+
+/// 某一方在该组基准区间上的内容；该方在组内没有修改时返回 `None`
+fn side_text(
+    group: &[&(Side, Change)],
+    side: Side,
+    span: &Range<usize>,
+    side_lines: &[&str],
+) -> Option<String> {
+    let mut own = group
+        .iter()
+        .filter(|(owner, _)| *owner == side)
+        .map(|(_, change)| change);
+    let first = own.next()?;
+    let last = own.next_back().unwrap_or(first);
+    // 组内该方修改之外的部分与基准相同，按偏移量扩展到整个组
+    let start = first.side.start - (first.base.start - span.start);
+    let end = last.side.end + (span.end - last.base.end);
+    Some(side_lines[start..end].concat())
+}
+
+fn push_conflict(
+    content: &mut String,
+    ours: &str,
+    theirs: &str,
+    ours_label: &str,
+    theirs_label: &str,
+) {
+    content.push_str(&format!("<<<<<<< {}\n", ours_label));
+    push_section(content, ours);
+    content.push_str("=======\n");
+    push_section(content, theirs);
+    content.push_str(&format!(">>>>>>> {}\n", theirs_label));
+}
+
+fn push_section(content: &mut String, text: &str) {
+    content.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        content.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(base: &str, ours: &str, theirs: &str) -> MergeOutput {
+        merge_three_way(base, ours, theirs, "current", "snapshot")
+    }
+
+    #[test]
+    fn merges_edits_on_adjacent_lines() {
+        let output = merge("a\nb\nc\nd\n", "a\nB\nc\nd\n", "a\nb\nC\nd\n");
+        assert!(output.conflicts.is_empty());
+        assert_eq!(output.content, "a\nB\nC\nd\n");
+    }
+
+    #[test]
+    fn insertions_at_same_position_conflict() {
+        let output = merge("a\nb\n", "a\nours\nb\n", "a\ntheirs\nb\n");
+        assert_eq!(output.conflicts.len(), 1);
+        let conflict = &output.conflicts[0];
+        assert_eq!(conflict.base_line_count, 0);
+        assert_eq!(conflict.ours, "ours\n");
+        assert_eq!(conflict.theirs, "theirs\n");
+        assert_eq!(
+            output.content,
+            "a\n<<<<<<< current\nours\n=======\ntheirs\n>>>>>>> snapshot\nb\n"
+        );
+    }
+
+    #[test]
+    fn delete_against_edit_conflicts() {
+        let output = merge("a\nb\nc\nd\n", "a\nd\n", "a\nb\nC\nd\n");
+        assert_eq!(output.conflicts.len(), 1);
+        let conflict = &output.conflicts[0];
+        assert_eq!(conflict.base_start_line, 2);
+        assert_eq!(conflict.base, "b\nc\n");
+        assert_eq!(conflict.ours, "");
+        assert_eq!(conflict.theirs, "b\nC\n");
+    }
+
+    #[test]
+    fn identical_changes_merge_cleanly() {
+        let output = merge("a\nb\nc\n", "a\nX\nc\n", "a\nX\nc\n");
+        assert!(output.conflicts.is_empty());
+        assert_eq!(output.content, "a\nX\nc\n");
+    }
+
+    #[test]
+    fn keeps_missing_trailing_newline() {
+        let output = merge("a\nb\nc", "A\nb\nc", "a\nb\nC");
+        assert!(output.conflicts.is_empty());
+        assert_eq!(output.content, "A\nb\nC");
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let output = merge("a\r\nb\r\nc\r\n", "A\r\nb\r\nc\r\n", "a\r\nb\r\nC\r\n");
+        assert!(output.conflicts.is_empty());
+        assert_eq!(output.content, "A\r\nb\r\nC\r\n");
+    }
+}
@@ -0,0 +1,84 @@
+//! 与 Tauri 无关的业务服务层
+//!
+//! Tauri 命令、托盘、应用菜单与 `spv` 命令行都只是 [`VaultService`] 的适配器：
+//! 它们负责取参与呈现结果，副作用通过 [`VaultEventSink`] 回传。
+
+mod client;
+mod composition;
+mod config_files;
+mod diff;
+mod discovery;
+mod events;
+mod merge;
+mod project;
+mod prompt;
+mod search;
+mod snapshot;
+mod snapshot_archive;
+mod snapshot_search;
+mod structured_config;
+mod variables;
+
+pub use crate::error::{ErrorCode, VaultError, VaultResult};
+pub use config_files::{client_config_files, read_client_config_files};
+pub use events::{NoopEventSink, VaultEventSink};
+pub use project::normalize_project_path_str;
+pub use snapshot::snapshot_file_contents;
+
+use crate::storage::{ClientStore, PromptStore, SnapshotRepository};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// 持有全部仓库的共享句柄，可在线程间廉价克隆
+#[derive(Clone)]
+pub struct VaultService {
+    prompts: Arc<Mutex<PromptStore>>,
+    clients: Arc<Mutex<ClientStore>>,
+    snapshots: Arc<Mutex<SnapshotRepository>>,
+    /// 提示词检索索引，首次检索时建立；加锁顺序在 `prompts` 之后
+    prompt_index: Arc<Mutex<Option<search::PromptIndex>>>,
+}
+
+impl VaultService {
+    pub fn new(prompts: PromptStore, clients: ClientStore, snapshots: SnapshotRepository) -> Self {
+        Self {
+            prompts: Arc::new(Mutex::new(prompts)),
+            clients: Arc::new(Mutex::new(clients)),
+            snapshots: Arc::new(Mutex::new(snapshots)),
+            prompt_index: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 基于应用数据目录初始化全部仓库
+    pub fn open(data_dir: PathBuf) -> VaultResult<Self> {
+        Ok(Self::new(
+            PromptStore::new(data_dir.clone())?,
+            ClientStore::new(data_dir.clone())?,
+            SnapshotRepository::new(data_dir)?,
+        ))
+    }
+
+    fn lock_prompts(&self) -> VaultResult<MutexGuard<'_, PromptStore>> {
+        self.prompts
+            .lock()
+            .map_err(|_| VaultError::lock_poisoned("prompts"))
+    }
+
+    fn lock_clients(&self) -> VaultResult<MutexGuard<'_, ClientStore>> {
+        self.clients
+            .lock()
+            .map_err(|_| VaultError::lock_poisoned("clients"))
+    }
+
+    fn lock_snapshots(&self) -> VaultResult<MutexGuard<'_, SnapshotRepository>> {
+        self.snapshots
+            .lock()
+            .map_err(|_| VaultError::lock_poisoned("snapshots"))
+    }
+
+    fn lock_prompt_index(&self) -> VaultResult<MutexGuard<'_, Option<search::PromptIndex>>> {
+        self.prompt_index
+            .lock()
+            .map_err(|_| VaultError::lock_poisoned("prompt_index"))
+    }
+}
@@ -0,0 +1,246 @@
+use super::variables::VariableContext;
+use super::{ErrorCode, VaultError, VaultResult, VaultService};
+use crate::client_catalog::{load_catalog, ClientCatalog};
+use crate::models::{
+    ApplyResult, HistoryEntry, Project, ProjectConfig, ProjectConfigFile, Template,
+};
+use crate::storage::app_data::{append_history_entry, read_history_entries};
+use crate::storage::{load_store, AppConfig, AppStore, BackupManager};
+use crate::utils::path_utils::{expand_file_pattern, to_slash_path};
+use crate::utils::{atomic_write, normalize_path};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+impl VaultService {
+    pub fn get_templates(&self) -> VaultResult<Vec<Template>> {
+        let store = load_store()?;
+        Ok(store.config().templates.clone())
+    }
+
+    pub fn create_template(
+        &self,
+        name: String,
+        files: HashMap<String, String>,
+    ) -> VaultResult<Template> {
+        let mut store = load_store()?;
+        let template = Template::new(name, files);
+        store.config_mut().templates.push(template.clone());
+        store.save()?;
+        Ok(template)
+    }
+
+    pub fn update_template(
+        &self,
+        id: &str,
+        name: String,
+        files: HashMap<String, String>,
+    ) -> VaultResult<Template> {
+        let mut store = load_store()?;
+        let target = store
+            .config_mut()
+            .templates
+            .iter_mut()
+            .find(|tpl| tpl.id == id)
+            .ok_or_else(|| template_not_found(id))?;
+        target.name = name;
+        target.files = files;
+        target.touch();
+        let updated = target.clone();
+        store.save()?;
+        Ok(updated)
+    }
+
+    pub fn delete_template(&self, id: &str) -> VaultResult<()> {
+        let mut store = load_store()?;
+        let before = store.config().templates.len();
+        store
+            .config_mut()
+            .templates
+            .retain(|template| template.id != id);
+        if before == store.config().templates.len() {
+            return Err(template_not_found(id));
+        }
+        store.save()?;
+        Ok(())
+    }
+
+    /// 以项目中现有的配置文件创建模板
+    pub fn import_template_from_project(
+        &self,
+        project_path: &str,
+        name: String,
+    ) -> VaultResult<Template> {
+        let project_path = normalize_project_path_str(project_path)?;
+        let files: HashMap<String, String> =
+            collect_project_files(&load_catalog()?, &project_path)?
+                .into_iter()
+                .map(|file| (file.path, file.content))
+                .collect();
+        if files.is_empty() {
+            return Err(VaultError::new(ErrorCode::ProjectNoConfigFiles).with_path(&project_path));
+        }
+        self.create_template(name, files)
+    }
+
+    /// 替换模板变量并备份项目配置后写入模板文件，写入失败时自动回滚到备份
+    pub fn apply_template(
+        &self,
+        project_path: &str,
+        template_id: &str,
+    ) -> VaultResult<ApplyResult> {
+        let project_path = normalize_project_path_str(project_path)?;
+        let mut store = load_store()?;
+        let template = store
+            .config()
+            .templates
+            .iter()
+            .find(|tpl| tpl.id == template_id)
+            .cloned()
+            .ok_or_else(|| template_not_found(template_id))?;
+        // 变量在写盘前全部解析，缺失时不产生备份也不修改任何文件
+        let files = render_template_files(store.config(), &project_path, &template)?;
+
+        let manager = BackupManager::new()?;
+        let backup = manager.create_with_label(&project_path, &template.name)?;
+        match apply_template_files(&project_path, &files) {
+            Ok(modified_files) => {
+                update_project_record(&mut store, &project_path, &template.id);
+                store.save()?;
+
+                let history_entry = HistoryEntry {
+                    action: "apply".to_string(),
+                    template_name: template.name.clone(),
+                    timestamp: Utc::now().to_rfc3339(),
+                    backup_id: Some(backup.id.clone()),
+                };
+                append_history_entry(&project_path, history_entry)?;
+
+                Ok(ApplyResult {
+                    success: true,
+                    backup_id: Some(backup.id),
+                    modified_files,
+                })
+            }
+            Err(err) => {
+                let mut error = VaultError::new(ErrorCode::TemplateApplyFailed)
+                    .with_param("template_id", &template.id)
+                    .with_param("backup_id", &backup.id)
+                    .with_cause(err);
+                if let Err(restore_err) = manager.restore_backup(&project_path, &backup.id) {
+                    error = error.with_param("rollback_error", restore_err);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    pub fn get_project_config(&self, project_path: &str) -> VaultResult<ProjectConfig> {
+        let project_path = normalize_project_path_str(project_path)?;
+        let files = collect_project_files(&load_catalog()?, &project_path)?;
+        Ok(ProjectConfig { files })
+    }
+
+    pub fn get_project_history(&self, project_path: &str) -> VaultResult<Vec<HistoryEntry>> {
+        let project_path = normalize_project_path_str(project_path)?;
+        read_history_entries(&project_path)
+    }
+}
+
+/// 将前端传入的项目路径规范化为绝对路径
+pub fn normalize_project_path_str(path: &str) -> VaultResult<PathBuf> {
+    if path.trim().is_empty() {
+        return Err(ErrorCode::ProjectPathEmpty.into());
+    }
+    Ok(normalize_path(path))
+}
+
+fn render_template_files(
+    config: &AppConfig,
+    project_path: &Path,
+    template: &Template,
+) -> VaultResult<Vec<(String, String)>> {
+    let context = VariableContext::new(config, Some(project_path), None);
+    let mut files = Vec::with_capacity(template.files.len());
+    for (relative, content) in &template.files {
+        if relative.trim().is_empty() {
+            continue;
+        }
+        let rendered = context.render(content).map_err(|err| {
+            err.with_param("template_id", &template.id)
+                .with_param("file", relative)
+        })?;
+        files.push((relative.clone(), rendered));
+    }
+    Ok(files)
+}
+
+fn apply_template_files(
+    project_path: &Path,
+    files: &[(String, String)],
+) -> VaultResult<Vec<String>> {
+    let mut modified = Vec::new();
+    for (relative, content) in files {
+        let target = project_path.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| VaultError::create_dir_failed(parent, e))?;
+        }
+        atomic_write(&target, content)?;
+        modified.push(target.to_string_lossy().to_string());
+    }
+    Ok(modified)
+}
+
+fn template_not_found(template_id: &str) -> VaultError {
+    VaultError::new(ErrorCode::TemplateNotFound).with_param("template_id", template_id)
+}
+
+fn update_project_record(store: &mut AppStore, project_path: &Path, template_id: &str) {
+    let config = store.config_mut();
+    if let Some(project) = config
+        .projects
+        .iter_mut()
+        .find(|proj| proj.path == project_path)
+    {
+        project.update_template(template_id.to_string());
+    } else {
+        let mut project = Project::new(project_path.to_path_buf());
+        project.update_template(template_id.to_string());
+        config.projects.push(project);
+    }
+}
+
+/// 按客户端目录收集项目中存在的指令文件；多个工具共用的文件只归属目录中靠前的工具
+fn collect_project_files(
+    catalog: &ClientCatalog,
+    project_path: &Path,
+) -> VaultResult<Vec<ProjectConfigFile>> {
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    for entry in catalog.entries() {
+        let patterns = entry
+            .project_paths
+            .iter()
+            .chain(entry.shared_project_paths.iter());
+        for pattern in patterns {
+            for config_path in expand_file_pattern(&project_path.join(pattern)) {
+                let Ok(relative) = config_path.strip_prefix(project_path) else {
+                    continue;
+                };
+                let relative = to_slash_path(relative);
+                if !seen.insert(relative.clone()) {
+                    continue;
+                }
+                let content = fs::read_to_string(&config_path)
+                    .map_err(|e| VaultError::read_failed(&config_path, e))?;
+                files.push(ProjectConfigFile {
+                    client_id: entry.id.clone(),
+                    path: relative,
+                    content,
+                });
+            }
+        }
+    }
+    Ok(files)
+}
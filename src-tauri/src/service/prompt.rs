@@ -0,0 +1,376 @@
+use super::{ErrorCode, VaultError, VaultEventSink, VaultResult, VaultService};
+use crate::models::{ImportResult, Prompt, PromptRevision, PromptRevisionDiff};
+use chrono::Utc;
+use serde_json::Value;
+use similar::TextDiff;
+use std::env;
+
+const ENTITY: &str = "prompt";
+/// 每个提示词最多保留的历史版本数
+const MAX_PROMPT_REVISIONS: usize = 50;
+
+impl VaultService {
+    pub fn get_prompts(&self) -> VaultResult<Vec<Prompt>> {
+        self.lock_prompts()?.get_all()
+    }
+
+    pub fn get_prompt(&self, id: &str) -> VaultResult<Option<Prompt>> {
+        self.lock_prompts()?.get_by_id(id)
+    }
+
+    pub fn get_prompts_by_tags(&self, tags: &[String]) -> VaultResult<Vec<Prompt>> {
+        self.lock_prompts()?.get_by_tags(tags)
+    }
+
+    pub fn create_prompt(
+        &self,
+        name: String,
+        content: String,
+        tags: Vec<String>,
+    ) -> VaultResult<Prompt> {
+        if name.trim().is_empty() {
+            return Err(ErrorCode::PromptNameEmpty.into());
+        }
+        let prompt = Prompt::new(name, content, tags);
+        let mut repo = self.lock_prompts()?;
+        repo.save(prompt.clone())?;
+        self.index_prompts(std::slice::from_ref(&prompt))?;
+        repo.add_revision(
+            PromptRevision::from_prompt(&prompt, current_author()),
+            MAX_PROMPT_REVISIONS,
+        )?;
+        Ok(prompt)
+    }
+
+    pub fn update_prompt(
+        &self,
+        id: &str,
+        name: Option<String>,
+        content: Option<String>,
+        tags: Option<Vec<String>>,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<Prompt> {
+        let mut repo = self.lock_prompts()?;
+        let mut prompt = repo.get_by_id(id)?.ok_or_else(|| prompt_not_found(id))?;
+        let previous = prompt.clone();
+
+        let mut changed = false;
+        if let Some(new_name) = name {
+            if new_name.trim().is_empty() {
+                return Err(ErrorCode::PromptNameEmpty.into());
+            }
+            if new_name != prompt.name {
+                prompt.name = new_name;
+                changed = true;
+            }
+        }
+        if let Some(new_content) = content {
+            if new_content != prompt.content {
+                prompt.content = new_content;
+                changed = true;
+            }
+        }
+        if let Some(new_tags) = tags {
+            if new_tags != prompt.tags {
+                prompt.tags = new_tags;
+                changed = true;
+            }
+        }
+
+        if changed {
+            prompt.updated_at = Utc::now();
+        }
+
+        repo.save(prompt.clone())?;
+        self.index_prompts(std::slice::from_ref(&prompt))?;
+        if changed {
+            // 早于版本记录功能的提示词没有历史，先补记修改前的状态
+            repo.add_revision(
+                PromptRevision::from_prompt(&previous, None),
+                MAX_PROMPT_REVISIONS,
+            )?;
+            repo.add_revision(
+                PromptRevision::from_prompt(&prompt, current_author()),
+                MAX_PROMPT_REVISIONS,
+            )?;
+            drop(repo);
+            self.refresh_compositions(std::slice::from_ref(&prompt.id), sink);
+        }
+        Ok(prompt)
+    }
+
+    /// 按时间倒序返回提示词的历史版本
+    pub fn list_prompt_revisions(&self, prompt_id: &str) -> VaultResult<Vec<PromptRevision>> {
+        let repo = self.lock_prompts()?;
+        if repo.get_by_id(prompt_id)?.is_none() {
+            return Err(prompt_not_found(prompt_id));
+        }
+        let mut revisions = repo.get_revisions(prompt_id)?;
+        revisions.reverse();
+        Ok(revisions)
+    }
+
+    pub fn diff_prompt_revisions(
+        &self,
+        prompt_id: &str,
+        from_revision: &str,
+        to_revision: &str,
+    ) -> VaultResult<PromptRevisionDiff> {
+        let revisions = self.lock_prompts()?.get_revisions(prompt_id)?;
+        let from = find_revision(&revisions, prompt_id, from_revision)?;
+        let to = find_revision(&revisions, prompt_id, to_revision)?;
+        let content_diff = if from.content == to.content {
+            String::new()
+        } else {
+            TextDiff::from_lines(&from.content, &to.content)
+                .unified_diff()
+                .header(&from.id, &to.id)
+                .to_string()
+        };
+        Ok(PromptRevisionDiff {
+            prompt_id: prompt_id.to_string(),
+            from_revision: from.id.clone(),
+            to_revision: to.id.clone(),
+            old_name: from.name.clone(),
+            new_name: to.name.clone(),
+            added_tags: to
+                .tags
+                .iter()
+                .filter(|tag| !from.tags.contains(tag))
+                .cloned()
+                .collect(),
+            removed_tags: from
+                .tags
+                .iter()
+                .filter(|tag| !to.tags.contains(tag))
+                .cloned()
+                .collect(),
+            content_diff,
+        })
+    }
+
+    /// 将提示词恢复为指定版本的名称、内容与标签，恢复本身也会记为一个新版本
+    pub fn revert_prompt(
+        &self,
+        prompt_id: &str,
+        revision_id: &str,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<Prompt> {
+        let revision = {
+            let revisions = self.lock_prompts()?.get_revisions(prompt_id)?;
+            find_revision(&revisions, prompt_id, revision_id)?.clone()
+        };
+        self.update_prompt(
+            prompt_id,
+            Some(revision.name),
+            Some(revision.content),
+            Some(revision.tags),
+            sink,
+        )
+    }
+
+    pub fn delete_prompt(&self, id: &str, sink: &dyn VaultEventSink) -> VaultResult<bool> {
+        let mut repo = self.lock_prompts()?;
+        let removed = repo.delete(id)?;
+        self.unindex_prompt(id)?;
+        drop(repo);
+        if removed {
+            // 引用它的组合无法再渲染，失败原因会记录在组合上
+            self.refresh_compositions(&[id.to_string()], sink);
+        }
+        Ok(removed)
+    }
+
+    /// 以格式化 JSON 导出全部提示词
+    pub fn export_prompts(&self) -> VaultResult<String> {
+        let prompts = self.get_prompts()?;
+        serde_json::to_string_pretty(&prompts)
+            .map_err(|e| VaultError::serialize_failed("prompts", e))
+    }
+
+    /// 校验并按 ID 合并导入的提示词
+    pub fn import_prompts(
+        &self,
+        json_data: &str,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<ImportResult> {
+        let prompts = parse_and_validate_prompts(json_data)?;
+        if prompts.is_empty() {
+            return Ok(ImportResult {
+                total: 0,
+                added: 0,
+                updated: 0,
+            });
+        }
+        let mut repo = self.lock_prompts()?;
+        let mut changed = Vec::new();
+        for prompt in &prompts {
+            if let Some(existing) = repo.get_by_id(&prompt.id)? {
+                let revision = PromptRevision::from_prompt(prompt, current_author());
+                let existing_hash =
+                    PromptRevision::compute_hash(&existing.name, &existing.content, &existing.tags);
+                if revision.hash != existing_hash {
+                    changed.push((PromptRevision::from_prompt(&existing, None), revision));
+                }
+            }
+        }
+        let result = repo.import_prompts(prompts.clone())?;
+        self.index_prompts(&prompts)?;
+        for (previous, revision) in changed {
+            repo.add_revision(previous, MAX_PROMPT_REVISIONS)?;
+            repo.add_revision(revision, MAX_PROMPT_REVISIONS)?;
+        }
+        drop(repo);
+        let ids: Vec<String> = prompts.into_iter().map(|prompt| prompt.id).collect();
+        self.refresh_compositions(&ids, sink);
+        Ok(result)
+    }
+}
+
+fn prompt_not_found(prompt_id: &str) -> VaultError {
+    VaultError::new(ErrorCode::PromptNotFound).with_param("prompt_id", prompt_id)
+}
+
+fn find_revision<'a>(
+    revisions: &'a [PromptRevision],
+    prompt_id: &str,
+    revision_id: &str,
+) -> VaultResult<&'a PromptRevision> {
+    revisions
+        .iter()
+        .find(|revision| revision.id == revision_id.trim())
+        .ok_or_else(|| {
+            VaultError::new(ErrorCode::PromptRevisionNotFound)
+                .with_param("prompt_id", prompt_id)
+                .with_param("revision_id", revision_id)
+        })
+}
+
+/// 当前系统用户名，作为版本的修改者
+fn current_author() -> Option<String> {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+}
+
+fn parse_and_validate_prompts(json_data: &str) -> VaultResult<Vec<Prompt>> {
+    let trimmed = json_data.trim();
+    if trimmed.is_empty() {
+        return Err(ErrorCode::ImportEmpty.into());
+    }
+
+    let value: Value = serde_json::from_str(trimmed)
+        .map_err(|e| VaultError::new(ErrorCode::ImportInvalidJson).with_cause(e))?;
+    let entries = value
+        .as_array()
+        .ok_or_else(|| VaultError::new(ErrorCode::ImportNotArray).with_param("entity", ENTITY))?;
+    validate_prompt_entries(entries)?;
+
+    let prompts: Vec<Prompt> = serde_json::from_value(value)
+        .map_err(|e| VaultError::new(ErrorCode::ImportInvalidJson).with_cause(e))?;
+    validate_prompt_models(&prompts)?;
+    Ok(prompts)
+}
+
+fn validate_prompt_entries(entries: &[Value]) -> VaultResult<()> {
+    for (index, entry) in entries.iter().enumerate() {
+        let obj = entry.as_object().ok_or_else(|| {
+            VaultError::new(ErrorCode::ImportEntryNotObject)
+                .with_param("entity", ENTITY)
+                .with_param("index", index + 1)
+        })?;
+        ensure_string_field(obj, "id", index)?;
+        ensure_string_field(obj, "name", index)?;
+        ensure_string_field(obj, "content", index)?;
+        ensure_array_field(obj, "tags", index)?;
+        ensure_string_field(obj, "created_at", index)?;
+        ensure_string_field(obj, "updated_at", index)?;
+    }
+    Ok(())
+}
+
+fn ensure_string_field(
+    obj: &serde_json::Map<String, Value>,
+    field: &str,
+    index: usize,
+) -> VaultResult<()> {
+    match obj.get(field) {
+        Some(Value::String(value)) if !value.trim().is_empty() => Ok(()),
+        Some(Value::String(_)) => Err(field_error(ErrorCode::ImportFieldEmpty, index, field)),
+        Some(_) => Err(field_error(ErrorCode::ImportFieldInvalid, index, field)),
+        None => Err(field_error(ErrorCode::ImportFieldMissing, index, field)),
+    }
+}
+
+fn ensure_array_field(
+    obj: &serde_json::Map<String, Value>,
+    field: &str,
+    index: usize,
+) -> VaultResult<()> {
+    match obj.get(field) {
+        Some(Value::Array(_)) => Ok(()),
+        Some(_) => Err(field_error(ErrorCode::ImportFieldInvalid, index, field)),
+        None => Err(field_error(ErrorCode::ImportFieldMissing, index, field)),
+    }
+}
+
+fn validate_prompt_models(prompts: &[Prompt]) -> VaultResult<()> {
+    for (index, prompt) in prompts.iter().enumerate() {
+        if prompt.id.trim().is_empty() {
+            return Err(field_error(ErrorCode::ImportFieldEmpty, index, "id"));
+        }
+        if !is_safe_id(&prompt.id) {
+            return Err(field_error(ErrorCode::ImportFieldInvalid, index, "id"));
+        }
+        if prompt.name.trim().is_empty() {
+            return Err(field_error(ErrorCode::ImportFieldEmpty, index, "name"));
+        }
+        if prompt.content.trim().is_empty() {
+            return Err(field_error(ErrorCode::ImportFieldEmpty, index, "content"));
+        }
+        if prompt.tags.iter().any(|tag| tag.trim().is_empty()) {
+            return Err(field_error(ErrorCode::ImportFieldEmpty, index, "tags"));
+        }
+    }
+    Ok(())
+}
+
+/// ID 会用作历史版本的文件名，只允许字母、数字、`-` 与 `_`
+fn is_safe_id(id: &str) -> bool {
+    id.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn field_error(code: ErrorCode, index: usize, field: &str) -> VaultError {
+    VaultError::import_field(code, ENTITY, index, field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> String {
+        format!(
+            r#"[{{"id":{},"name":"n","content":"c","tags":[],"created_at":"2024-01-01T00:00:00Z","updated_at":"2024-01-01T00:00:00Z"}}]"#,
+            serde_json::to_string(id).unwrap()
+        )
+    }
+
+    #[test]
+    fn import_accepts_uuid_ids() {
+        let prompts =
+            parse_and_validate_prompts(&entry("3f2c9a1e-5b7d-4e8f-9a0b-1c2d3e4f5a6b")).unwrap();
+        assert_eq!(prompts.len(), 1);
+    }
+
+    #[test]
+    fn import_rejects_path_traversal_ids() {
+        for id in ["../../foo", "a/b", "a\\b", "..", ".hidden", "a b"] {
+            let err = parse_and_validate_prompts(&entry(id)).unwrap_err();
+            assert_eq!(err.code, ErrorCode::ImportFieldInvalid, "id {id:?}");
+            assert_eq!(err.params.get("field").map(String::as_str), Some("id"));
+            assert_eq!(err.params.get("index").map(String::as_str), Some("1"));
+        }
+    }
+}
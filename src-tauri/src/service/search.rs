@@ -0,0 +1,736 @@
+//! 提示词全文检索
+//!
+//! 名称、内容与标签被切分为词元后写入内存倒排索引，保存与删除提示词时同步更新。
+//! 英文等按字母数字切词并转小写，中日韩文字逐字切分，多字查询词按短语（相邻位置）匹配。
+//!
+//! 查询语法：
+//! - 空格分隔的词默认全部需要命中（AND），`OR` 分隔多组条件，任一组命中即可
+//! - `"..."` 为短语查询
+//! - `tag:名称` 只保留带该标签的提示词，`-tag:名称` 排除；`-词` 排除包含该词的提示词
+
+use super::{VaultResult, VaultService};
+use crate::models::{Prompt, PromptSearchHit, SearchField, SearchMatch, SearchSnippet};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// 未指定数量时返回的最大结果数
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+/// 摘录中命中位置之前、之后保留的字符数
+const SNIPPET_BEFORE: usize = 40;
+const SNIPPET_AFTER: usize = 120;
+
+impl VaultService {
+    /// 按相关度检索提示词
+    pub fn search_prompts(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> VaultResult<Vec<PromptSearchHit>> {
+        let query = parse_query(query);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let repo = self.lock_prompts()?;
+        let mut index = self.lock_prompt_index()?;
+        if index.is_none() {
+            *index = Some(PromptIndex::build(repo.get_all()?));
+        }
+        let index = index.as_ref().expect("索引已初始化");
+        Ok(index.search(&query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT)))
+    }
+
+    /// 同步已保存的提示词到索引；索引尚未建立时无需处理
+    pub(super) fn index_prompts(&self, prompts: &[Prompt]) -> VaultResult<()> {
+        if let Some(index) = self.lock_prompt_index()?.as_mut() {
+            for prompt in prompts {
+                index.upsert(prompt.clone());
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn unindex_prompt(&self, id: &str) -> VaultResult<()> {
+        if let Some(index) = self.lock_prompt_index()?.as_mut() {
+            index.remove(id);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Field {
+    Name,
+    Tag(usize),
+    Content,
+}
+
+impl Field {
+    fn kind(self) -> SearchField {
+        match self {
+            Field::Name => SearchField::Name,
+            Field::Tag(_) => SearchField::Tag,
+            Field::Content => SearchField::Content,
+        }
+    }
+
+    fn weight(self) -> f64 {
+        match self {
+            Field::Name => 3.0,
+            Field::Tag(_) => 2.0,
+            Field::Content => 1.0,
+        }
+    }
+
+    fn slot(self) -> usize {
+        match self {
+            Field::Name => 0,
+            Field::Tag(_) => 1,
+            Field::Content => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+/// 词元在某个字段中的一次出现
+#[derive(Debug, Clone, Copy)]
+struct Posting {
+    field: Field,
+    position: usize,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug)]
+struct IndexedPrompt {
+    prompt: Prompt,
+    terms: HashSet<String>,
+    tags: HashSet<String>,
+    /// 名称、标签、内容的词元数
+    lengths: [usize; 3],
+}
+
+/// 词元 -> 提示词 ID -> 出现位置
+#[derive(Debug, Default)]
+pub(super) struct PromptIndex {
+    postings: HashMap<String, HashMap<String, Vec<Posting>>>,
+    docs: HashMap<String, IndexedPrompt>,
+    total_lengths: [usize; 3],
+}
+
+impl PromptIndex {
+    fn build(prompts: Vec<Prompt>) -> Self {
+        let mut index = Self::default();
+        for prompt in prompts {
+            index.upsert(prompt);
+        }
+        index
+    }
+
+    fn upsert(&mut self, prompt: Prompt) {
+        self.remove(&prompt.id);
+
+        let mut fields = vec![(Field::Name, prompt.name.as_str())];
+        fields.extend(
+            prompt
+                .tags
+                .iter()
+                .enumerate()
+                .map(|(i, tag)| (Field::Tag(i), tag.as_str())),
+        );
+        fields.push((Field::Content, prompt.content.as_str()));
+
+        let mut terms = HashSet::new();
+        let mut lengths = [0usize; 3];
+        let mut tag_offset = 0usize;
+        for (field, text) in fields {
+            let tokens = tokenize(text);
+            lengths[field.slot()] += tokens.len();
+            // 多个标签共用一个长度槽，位置依次累加以免跨标签拼成短语
+            let base = if let Field::Tag(_) = field {
+                let base = tag_offset;
+                tag_offset += tokens.len() + 1;
+                base
+            } else {
+                0
+            };
+            for (position, token) in tokens.into_iter().enumerate() {
+                self.postings
+                    .entry(token.text.clone())
+                    .or_default()
+                    .entry(prompt.id.clone())
+                    .or_default()
+                    .push(Posting {
+                        field,
+                        position: base + position,
+                        start: token.start,
+                        end: token.end,
+                    });
+                terms.insert(token.text);
+            }
+        }
+        for (slot, length) in lengths.iter().enumerate() {
+            self.total_lengths[slot] += length;
+        }
+
+        let tags = prompt.tags.iter().map(|tag| tag.to_lowercase()).collect();
+        self.docs.insert(
+            prompt.id.clone(),
+            IndexedPrompt {
+                prompt,
+                terms,
+                tags,
+                lengths,
+            },
+        );
+    }
+
+    fn remove(&mut self, id: &str) {
+        let Some(doc) = self.docs.remove(id) else {
+            return;
+        };
+        for term in &doc.terms {
+            if let Some(entries) = self.postings.get_mut(term) {
+                entries.remove(id);
+                if entries.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        for (slot, length) in doc.lengths.iter().enumerate() {
+            self.total_lengths[slot] -= length;
+        }
+    }
+
+    fn search(&self, query: &[Vec<Condition>], limit: usize) -> Vec<PromptSearchHit> {
+        let mut hits = Vec::new();
+        for id in self.candidates(query) {
+            let doc = &self.docs[id];
+            let mut score = 0.0;
+            let mut matched = false;
+            let mut occurrences = Vec::new();
+            for clause in query {
+                if let Some((clause_score, clause_occurrences)) = self.evaluate(id, doc, clause) {
+                    matched = true;
+                    score += clause_score;
+                    occurrences.extend(clause_occurrences);
+                }
+            }
+            if matched {
+                hits.push(build_hit(doc, score, occurrences));
+            }
+        }
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| b.prompt.updated_at.cmp(&a.prompt.updated_at))
+                .then_with(|| a.prompt.name.cmp(&b.prompt.name))
+        });
+        hits.truncate(limit);
+        hits
+    }
+
+    /// 每组条件以最少出现的肯定词元缩小范围；没有肯定词时需要遍历全部提示词
+    fn candidates(&self, query: &[Vec<Condition>]) -> BTreeSet<&String> {
+        let mut candidates = BTreeSet::new();
+        for clause in query {
+            let rarest = clause
+                .iter()
+                .filter(|condition| !condition.negated)
+                .filter_map(|condition| match &condition.item {
+                    Item::Terms(terms) => terms
+                        .iter()
+                        .map(|term| self.postings.get(term).map_or(0, HashMap::len))
+                        .zip(terms)
+                        .min_by_key(|(df, _)| *df)
+                        .map(|(_, term)| term),
+                    Item::Tag(_) => None,
+                })
+                .min_by_key(|term| self.postings.get(*term).map_or(0, HashMap::len));
+            match rarest {
+                Some(term) => {
+                    if let Some(entries) = self.postings.get(term) {
+                        candidates.extend(entries.keys());
+                    }
+                }
+                None => return self.docs.keys().collect(),
+            }
+        }
+        candidates
+    }
+
+    /// 判断一组条件是否全部满足，满足时返回得分与命中位置
+    fn evaluate(
+        &self,
+        id: &str,
+        doc: &IndexedPrompt,
+        clause: &[Condition],
+    ) -> Option<(f64, Vec<Posting>)> {
+        let mut score = 0.0;
+        let mut occurrences = Vec::new();
+        for condition in clause {
+            let found = match &condition.item {
+                Item::Tag(tag) => {
+                    let has_tag = doc.tags.contains(tag);
+                    if has_tag != condition.negated {
+                        continue;
+                    }
+                    return None;
+                }
+                Item::Terms(terms) => self.find_phrase(id, terms),
+            };
+            if condition.negated {
+                if !found.is_empty() {
+                    return None;
+                }
+                continue;
+            }
+            if found.is_empty() {
+                return None;
+            }
+            score += self.bm25(doc, terms_df(self, &condition.item), &found);
+            occurrences.extend(found);
+        }
+        Some((score, occurrences))
+    }
+
+    /// 查找词元序列在同一字段中连续出现的位置，返回覆盖整个短语的区间
+    fn find_phrase(&self, id: &str, terms: &[String]) -> Vec<Posting> {
+        let Some(first) = terms
+            .first()
+            .and_then(|term| self.postings.get(term))
+            .and_then(|entries| entries.get(id))
+        else {
+            return Vec::new();
+        };
+        let mut rest = Vec::with_capacity(terms.len().saturating_sub(1));
+        for term in &terms[1..] {
+            match self.postings.get(term).and_then(|entries| entries.get(id)) {
+                Some(postings) => rest.push(postings),
+                None => return Vec::new(),
+            }
+        }
+        first
+            .iter()
+            .filter_map(|start| {
+                let mut end = start.end;
+                for (offset, postings) in rest.iter().enumerate() {
+                    let next = postings.iter().find(|posting| {
+                        same_field(posting.field, start.field)
+                            && posting.position == start.position + offset + 1
+                    })?;
+                    end = next.end;
+                }
+                Some(Posting { end, ..*start })
+            })
+            .collect()
+    }
+
+    fn bm25(&self, doc: &IndexedPrompt, df: usize, occurrences: &[Posting]) -> f64 {
+        let total_docs = self.docs.len() as f64;
+        let df = df as f64;
+        let idf = (1.0 + (total_docs - df + 0.5) / (df + 0.5)).ln();
+        let mut tf = [0usize; 3];
+        for posting in occurrences {
+            tf[posting.field.slot()] += 1;
+        }
+        let fields = [Field::Name, Field::Tag(0), Field::Content];
+        fields
+            .iter()
+            .filter(|field| tf[field.slot()] > 0)
+            .map(|field| {
+                let slot = field.slot();
+                let tf = tf[slot] as f64;
+                let average = (self.total_lengths[slot] as f64 / total_docs).max(1.0);
+                let norm = 1.0 - BM25_B + BM25_B * doc.lengths[slot] as f64 / average;
+                field.weight() * idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * norm)
+            })
+            .sum()
+    }
+}
+
+/// 短语的文档频率取其中最少出现的词元
+fn terms_df(index: &PromptIndex, item: &Item) -> usize {
+    match item {
+        Item::Terms(terms) => terms
+            .iter()
+            .map(|term| index.postings.get(term).map_or(0, HashMap::len))
+            .min()
+            .unwrap_or(0),
+        Item::Tag(_) => 0,
+    }
+}
+
+fn same_field(a: Field, b: Field) -> bool {
+    a.slot() == b.slot()
+}
+
+fn build_hit(doc: &IndexedPrompt, score: f64, occurrences: Vec<Posting>) -> PromptSearchHit {
+    let mut matches: Vec<SearchMatch> = occurrences
+        .iter()
+        .map(|posting| {
+            let (tag_index, start, end) = match posting.field {
+                Field::Tag(i) => (Some(i), posting.start, posting.end),
+                _ => (None, posting.start, posting.end),
+            };
+            SearchMatch {
+                field: posting.field.kind(),
+                tag_index,
+                start,
+                end,
+            }
+        })
+        .collect();
+    matches.sort_by_key(|m| (m.field, m.tag_index, m.start, m.end));
+    matches.dedup();
+
+    let content_matches: Vec<&SearchMatch> = matches
+        .iter()
+        .filter(|m| m.field == SearchField::Content)
+        .collect();
+    PromptSearchHit {
+        snippet: build_snippet(&doc.prompt.content, &content_matches),
+        prompt: doc.prompt.clone(),
+        score,
+        matches,
+    }
+}
+
+/// 以第一个内容命中为中心截取摘录；内容没有命中时取开头
+fn build_snippet(content: &str, matches: &[&SearchMatch]) -> Option<SearchSnippet> {
+    if content.is_empty() {
+        return None;
+    }
+    let chars: Vec<char> = content.chars().collect();
+    let start = matches
+        .first()
+        .map_or(0, |m| m.start.saturating_sub(SNIPPET_BEFORE));
+    let end = matches
+        .first()
+        .map_or(SNIPPET_BEFORE + SNIPPET_AFTER, |m| m.end + SNIPPET_AFTER);
+    let end = end.min(chars.len());
+    let highlights = matches
+        .iter()
+        .filter(|m| m.start >= start && m.end <= end)
+        .map(|m| [m.start - start, m.end - start])
+        .collect();
+    Some(SearchSnippet {
+        text: chars[start..end].iter().collect(),
+        start,
+        truncated: end < chars.len(),
+        highlights,
+    })
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2A6DF}'
+    )
+}
+
+/// 切分词元并记录字符区间；中日韩文字每个字单独成为一个词元
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut current_start = 0;
+    for (position, c) in text.chars().enumerate() {
+        if c.is_alphanumeric() && !is_cjk(c) || c == '_' {
+            if current.is_empty() {
+                current_start = position;
+            }
+            current.extend(c.to_lowercase());
+            continue;
+        }
+        if !current.is_empty() {
+            tokens.push(Token {
+                text: std::mem::take(&mut current),
+                start: current_start,
+                end: position,
+            });
+        }
+        if is_cjk(c) {
+            tokens.push(Token {
+                text: c.to_string(),
+                start: position,
+                end: position + 1,
+            });
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(Token {
+            text: current,
+            start: current_start,
+            end: text.chars().count(),
+        });
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    /// 一个或多个词元，多个时按短语匹配
+    Terms(Vec<String>),
+    Tag(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    item: Item,
+    negated: bool,
+}
+
+/// 解析为以 `OR` 分隔的若干组条件，组内条件全部满足才算命中
+fn parse_query(query: &str) -> Vec<Vec<Condition>> {
+    let mut clauses = Vec::new();
+    let mut clause = Vec::new();
+    let mut chars = query.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&first) = chars.peek() else {
+            break;
+        };
+
+        let negated = first == '-';
+        if negated {
+            chars.next();
+        }
+        let quoted = chars.peek() == Some(&'"');
+        let mut word = read_word(&mut chars);
+
+        if !negated && !quoted && word == "OR" {
+            if !clause.is_empty() {
+                clauses.push(std::mem::take(&mut clause));
+            }
+            continue;
+        }
+        if !negated && !quoted && word == "AND" {
+            continue;
+        }
+
+        let item = match word.get(..4) {
+            Some(prefix) if !quoted && prefix.eq_ignore_ascii_case("tag:") => {
+                let tag = word.split_off(4).trim().to_lowercase();
+                if tag.is_empty() {
+                    continue;
+                }
+                Item::Tag(tag)
+            }
+            _ => {
+                let terms: Vec<String> = tokenize(&word).into_iter().map(|t| t.text).collect();
+                if terms.is_empty() {
+                    continue;
+                }
+                Item::Terms(terms)
+            }
+        };
+        clause.push(Condition { item, negated });
+    }
+    if !clause.is_empty() {
+        clauses.push(clause);
+    }
+    clauses
+}
+
+/// 读取到引号外的空白为止，引号本身不计入；缺少闭合引号时读到结尾
+fn read_word(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut word = String::new();
+    let mut in_quotes = false;
+    while let Some(c) = chars.next_if(|&c| in_quotes || !c.is_whitespace()) {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        } else {
+            word.push(c);
+        }
+    }
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(id: &str, name: &str, content: &str, tags: &[&str]) -> Prompt {
+        let mut prompt = Prompt::new(name, content, tags.iter().map(|t| t.to_string()).collect());
+        prompt.id = id.to_string();
+        prompt
+    }
+
+    fn search(index: &PromptIndex, query: &str) -> Vec<String> {
+        index
+            .search(&parse_query(query), DEFAULT_SEARCH_LIMIT)
+            .into_iter()
+            .map(|hit| hit.prompt.id)
+            .collect()
+    }
+
+    fn terms(words: &[&str]) -> Item {
+        Item::Terms(words.iter().map(|w| w.to_string()).collect())
+    }
+
+    fn condition(item: Item, negated: bool) -> Condition {
+        Condition { item, negated }
+    }
+
+    #[test]
+    fn parses_or_groups_phrases_and_filters() {
+        assert_eq!(
+            parse_query("rust \"Error handling\" AND -draft OR Tag:CLI -tag:old"),
+            vec![
+                vec![
+                    condition(terms(&["rust"]), false),
+                    condition(terms(&["error", "handling"]), false),
+                    condition(terms(&["draft"]), true),
+                ],
+                vec![
+                    condition(Item::Tag("cli".to_string()), false),
+                    condition(Item::Tag("old".to_string()), true),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_skips_empty_groups_and_quoted_keywords() {
+        assert!(parse_query("  OR  ").is_empty());
+        assert!(parse_query("tag: -- \"\"").is_empty());
+        assert_eq!(
+            parse_query("\"OR\" \"tag:x\""),
+            vec![vec![
+                condition(terms(&["or"]), false),
+                condition(terms(&["tag", "x"]), false),
+            ]]
+        );
+        // 缺少闭合引号时读到结尾
+        assert_eq!(
+            parse_query("\"unclosed phrase"),
+            vec![vec![condition(terms(&["unclosed", "phrase"]), false)]]
+        );
+    }
+
+    #[test]
+    fn tokenizes_cjk_per_character() {
+        let tokens = tokenize("用Rust写代码, snake_case");
+        let texts: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, ["用", "rust", "写", "代", "码", "snake_case"]);
+        let spans: Vec<(usize, usize)> = tokens.iter().map(|t| (t.start, t.end)).collect();
+        assert_eq!(spans, [(0, 1), (1, 5), (5, 6), (6, 7), (7, 8), (10, 20)]);
+    }
+
+    #[test]
+    fn cjk_query_matches_adjacent_characters_only() {
+        let index = PromptIndex::build(vec![
+            prompt("adjacent", "a", "请帮我编写代码", &[]),
+            prompt("apart", "b", "代替手写的编码", &[]),
+        ]);
+        assert_eq!(search(&index, "代码"), ["adjacent"]);
+        assert_eq!(search(&index, "代 码").len(), 2);
+    }
+
+    #[test]
+    fn phrase_requires_adjacent_positions_in_one_field() {
+        let index = PromptIndex::build(vec![
+            prompt("phrase", "a", "Good error handling matters", &[]),
+            prompt("reversed", "b", "handling of error", &[]),
+            prompt("tags", "c", "", &["error", "handling"]),
+        ]);
+        assert_eq!(search(&index, "\"error handling\""), ["phrase"]);
+        assert_eq!(search(&index, "error handling").len(), 3);
+
+        let hit = &index.search(&parse_query("\"error handling\""), 10)[0];
+        assert_eq!(
+            hit.matches,
+            [SearchMatch {
+                field: SearchField::Content,
+                tag_index: None,
+                start: 5,
+                end: 19,
+            }]
+        );
+    }
+
+    #[test]
+    fn ranks_by_field_weight_frequency_and_length() {
+        let index = PromptIndex::build(vec![
+            prompt("content", "notes", "deploy steps", &[]),
+            prompt("name", "deploy", "steps", &[]),
+            prompt("tag", "notes", "steps", &["deploy"]),
+        ]);
+        assert_eq!(search(&index, "deploy"), ["name", "tag", "content"]);
+
+        let index = PromptIndex::build(vec![
+            prompt("once", "a", "cache one two three four", &[]),
+            prompt("twice", "b", "cache cache one two three", &[]),
+            prompt("short", "c", "cache", &[]),
+            prompt("other", "d", "unrelated", &[]),
+        ]);
+        assert_eq!(search(&index, "cache"), ["short", "twice", "once"]);
+    }
+
+    #[test]
+    fn or_groups_match_either_side_and_sum_scores() {
+        let index = PromptIndex::build(vec![
+            prompt("alpha", "a", "alpha", &[]),
+            prompt("beta", "b", "beta", &[]),
+            prompt("both", "c", "alpha beta", &[]),
+            prompt("none", "d", "gamma", &[]),
+        ]);
+        let hits = search(&index, "alpha OR beta");
+        assert_eq!(hits[0], "both");
+        assert_eq!(hits.len(), 3);
+        assert_eq!(search(&index, "alpha beta"), ["both"]);
+    }
+
+    #[test]
+    fn applies_tag_and_negation_filters() {
+        let index = PromptIndex::build(vec![
+            prompt("cli", "a", "review code", &["CLI"]),
+            prompt("old", "b", "review code", &["cli", "old"]),
+            prompt("draft", "c", "review draft code", &[]),
+        ]);
+        assert_eq!(search(&index, "review tag:cli").len(), 2);
+        assert_eq!(search(&index, "review tag:cli -tag:old"), ["cli"]);
+        assert_eq!(search(&index, "code -draft").len(), 2);
+        // 只有否定条件时在全部提示词中筛选
+        let mut ids = search(&index, "-tag:cli");
+        ids.sort();
+        assert_eq!(ids, ["draft"]);
+    }
+
+    #[test]
+    fn upsert_and_remove_keep_index_consistent() {
+        let mut index = PromptIndex::build(vec![
+            prompt("a", "first", "old wording", &["x"]),
+            prompt("b", "second", "shared wording", &[]),
+        ]);
+        index.upsert(prompt("a", "first", "new wording", &["y"]));
+        assert!(search(&index, "old").is_empty());
+        assert!(search(&index, "tag:x").is_empty());
+        assert_eq!(search(&index, "new tag:y"), ["a"]);
+        assert_eq!(search(&index, "wording").len(), 2);
+        assert!(!index.postings.contains_key("old"));
+
+        index.remove("a");
+        index.remove("missing");
+        assert_eq!(search(&index, "wording"), ["b"]);
+        assert!(!index.postings.contains_key("new"));
+        assert_eq!(index.total_lengths, [1, 0, 2]);
+
+        index.remove("b");
+        assert!(index.postings.is_empty());
+        assert_eq!(index.total_lengths, [0, 0, 0]);
+    }
+}
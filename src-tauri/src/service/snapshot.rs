@@ -0,0 +1,418 @@
+use super::config_files::{read_client_config_files, read_optional_file, write_files_atomically};
+use super::diff::diff_file_sets;
+use super::merge::merge_three_way;
+use super::{ErrorCode, VaultError, VaultEventSink, VaultResult, VaultService};
+use crate::models::{
+    ClientConfig, ConflictStyle, MergeFileStatus, MergeRestoreResult, MergedFile, RetentionPolicy,
+    Snapshot, SnapshotConfig, SnapshotDiff,
+};
+use crate::utils::path_utils::{expand_path_entry, resolve_path};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+impl VaultService {
+    /// 读取客户端当前的全部配置文件并保存为快照
+    pub fn create_snapshot(
+        &self,
+        client_id: &str,
+        name: String,
+        fallback_content: String,
+        is_auto: bool,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<Snapshot> {
+        let client = self.require_client(client_id)?;
+        if client.config_file_paths.is_empty() {
+            return Err(
+                VaultError::new(ErrorCode::ClientNoConfigPaths).with_param("client_id", &client.id)
+            );
+        }
+
+        let file_contents = read_client_config_files(&client)?;
+        let legacy_content = client
+            .default_config_path()
+            .and_then(|path| file_contents.get(path).cloned())
+            .unwrap_or(fallback_content);
+
+        let snapshot = self.lock_snapshots()?.create_snapshot(
+            &client.id,
+            name,
+            legacy_content,
+            Some(file_contents),
+            client.path_patterns(),
+            is_auto,
+        )?;
+        sink.snapshots_changed(&client.id);
+        Ok(snapshot)
+    }
+
+    pub fn get_snapshot_config(&self, client_id: &str) -> VaultResult<SnapshotConfig> {
+        self.lock_snapshots()?.get_config(client_id)
+    }
+
+    /// 按创建时间倒序返回客户端的快照
+    pub fn get_snapshots(&self, client_id: &str) -> VaultResult<Vec<Snapshot>> {
+        self.lock_snapshots()?.get_snapshots(client_id)
+    }
+
+    /// 读取单个快照并加载其全部文件内容；列表接口只返回元数据
+    pub fn get_snapshot(&self, client_id: &str, snapshot_id: &str) -> VaultResult<Snapshot> {
+        self.lock_snapshots()?.load_snapshot(client_id, snapshot_id)
+    }
+
+    /// 比较两个快照中的配置文件，`from_id` 为旧版本
+    pub fn diff_snapshots(
+        &self,
+        client_id: &str,
+        from_id: &str,
+        to_id: &str,
+    ) -> VaultResult<SnapshotDiff> {
+        let client = self.require_client(client_id)?;
+        let from = self.get_snapshot(client_id, from_id)?;
+        let to = self.get_snapshot(client_id, to_id)?;
+        Ok(build_snapshot_diff(
+            &client,
+            &from,
+            snapshot_file_contents(&client, &to),
+            Some(to.id.clone()),
+        ))
+    }
+
+    /// 比较快照与磁盘上的当前配置文件，即恢复该快照时将被覆盖的内容
+    pub fn diff_snapshot_with_current(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+    ) -> VaultResult<SnapshotDiff> {
+        let client = self.require_client(client_id)?;
+        let snapshot = self.get_snapshot(client_id, snapshot_id)?;
+        let mut current: BTreeMap<String, String> =
+            read_client_config_files(&client)?.into_iter().collect();
+        // 快照中可能包含已从客户端移除的路径，同样读取其当前内容
+        for path in snapshot_file_contents(&client, &snapshot).into_keys() {
+            if let Entry::Vacant(entry) = current.entry(path) {
+                let content = read_optional_file(entry.key())?;
+                entry.insert(content);
+            }
+        }
+        Ok(build_snapshot_diff(&client, &snapshot, current, None))
+    }
+
+    /// 将快照写回客户端配置文件，写入前后通知 `sink`
+    ///
+    /// 快照之后才出现在通配模式下的文件会被删除，使这些目录恢复到快照时的文件集合。
+    pub fn restore_snapshot(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<Snapshot> {
+        let snapshot = self.get_snapshot(client_id, snapshot_id)?;
+        let client = self.require_client(client_id)?;
+        let entries = restore_entries(&client, &snapshot)?;
+        let removals = files_added_since(&snapshot, &entries);
+        let targets: Vec<(PathBuf, &str)> = entries
+            .iter()
+            .map(|(path, content)| (resolve_path(path), content.as_str()))
+            .collect();
+
+        sink.config_files_writing(&client.id, &targets);
+        notify_removing(sink, &client.id, &removals);
+        let result = write_files_atomically(entries, removals);
+        let written = result.as_deref().unwrap_or_default();
+        sink.config_files_written(&client.id, written);
+
+        result.map(|_| snapshot)
+    }
+
+    /// 以三方合并的方式恢复快照，保留自最新快照以来对配置文件的修改
+    ///
+    /// 最新快照作为共同基准，当前文件为 ours，所选快照为 theirs；互不重叠的修改自动合并。
+    /// 有冲突时按 `style` 写入冲突标记，或不写入任何文件只返回冲突列表。
+    pub fn restore_snapshot_merged(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        style: ConflictStyle,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<MergeRestoreResult> {
+        let snapshot = self.get_snapshot(client_id, snapshot_id)?;
+        let client = self.require_client(client_id)?;
+        let base = match self.get_snapshots(client_id)?.first() {
+            Some(latest) => Some(self.get_snapshot(client_id, &latest.id)?),
+            None => None,
+        };
+        let base_files = base
+            .as_ref()
+            .map(|base| snapshot_file_contents(&client, base))
+            .unwrap_or_default();
+        let theirs_label = format!("snapshot {}", snapshot.name);
+
+        let mut files = Vec::new();
+        let mut entries = Vec::new();
+        let mut removals = Vec::new();
+        let restored = restore_entries(&client, &snapshot)?;
+        // 快照之后新增的文件：自基准快照后未被修改时删除，否则保留当前文件
+        for path in files_added_since(&snapshot, &restored) {
+            let ours = read_optional_file(&path)?;
+            let status = if base_files.get(&path) == Some(&ours) {
+                removals.push(path.clone());
+                MergeFileStatus::Removed
+            } else {
+                MergeFileStatus::KeptCurrent
+            };
+            files.push(MergedFile {
+                path,
+                status,
+                conflicts: Vec::new(),
+            });
+        }
+        for (path, theirs) in restored {
+            let ours = read_optional_file(&path)?;
+            let base = base_files
+                .get(&path)
+                .map(String::as_str)
+                .unwrap_or_default();
+            let (status, content, conflicts) = if ours == theirs {
+                (MergeFileStatus::Unchanged, None, Vec::new())
+            } else if ours == base {
+                (MergeFileStatus::Restored, Some(theirs), Vec::new())
+            } else if theirs == base {
+                (MergeFileStatus::KeptCurrent, None, Vec::new())
+            } else {
+                let merged = merge_three_way(base, &ours, &theirs, "current", &theirs_label);
+                let status = if merged.conflicts.is_empty() {
+                    MergeFileStatus::Merged
+                } else {
+                    MergeFileStatus::Conflicted
+                };
+                (status, Some(merged.content), merged.conflicts)
+            };
+            if let Some(content) = content {
+                entries.push((path.clone(), content));
+            }
+            files.push(MergedFile {
+                path,
+                status,
+                conflicts,
+            });
+        }
+
+        let mut result = MergeRestoreResult {
+            snapshot_id: snapshot.id,
+            base_snapshot_id: base.map(|base| base.id),
+            files,
+            written: false,
+        };
+        if (entries.is_empty() && removals.is_empty())
+            || (style == ConflictStyle::List && result.has_conflicts())
+        {
+            return Ok(result);
+        }
+
+        let targets: Vec<(PathBuf, &str)> = entries
+            .iter()
+            .map(|(path, content)| (resolve_path(path), content.as_str()))
+            .collect();
+        sink.config_files_writing(&client.id, &targets);
+        notify_removing(sink, &client.id, &removals);
+        let written = write_files_atomically(entries, removals);
+        sink.config_files_written(&client.id, written.as_deref().unwrap_or_default());
+        written?;
+        result.written = true;
+        Ok(result)
+    }
+
+    pub fn delete_snapshot(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<()> {
+        self.lock_snapshots()?
+            .delete_snapshot(client_id, snapshot_id)?;
+        sink.snapshots_changed(client_id);
+        Ok(())
+    }
+
+    pub fn rename_snapshot(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        new_name: String,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<()> {
+        self.lock_snapshots()?
+            .rename_snapshot(client_id, snapshot_id, new_name)?;
+        sink.snapshots_changed(client_id);
+        Ok(())
+    }
+
+    pub fn set_snapshot_pinned(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        pinned: bool,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<Snapshot> {
+        let snapshot =
+            self.lock_snapshots()?
+                .set_snapshot_pinned(client_id, snapshot_id, pinned)?;
+        sink.snapshots_changed(client_id);
+        Ok(snapshot)
+    }
+
+    pub fn set_snapshot_notes(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        notes: String,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<Snapshot> {
+        let snapshot = self
+            .lock_snapshots()?
+            .set_snapshot_notes(client_id, snapshot_id, notes)?;
+        sink.snapshots_changed(client_id);
+        Ok(snapshot)
+    }
+
+    pub fn set_snapshot_labels(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        labels: Vec<String>,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<Snapshot> {
+        let snapshot =
+            self.lock_snapshots()?
+                .set_snapshot_labels(client_id, snapshot_id, labels)?;
+        sink.snapshots_changed(client_id);
+        Ok(snapshot)
+    }
+
+    pub fn set_max_snapshots(
+        &self,
+        client_id: &str,
+        max: usize,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<()> {
+        self.lock_snapshots()?.set_max_snapshots(client_id, max)?;
+        sink.snapshots_changed(client_id);
+        Ok(())
+    }
+
+    pub fn set_max_auto_snapshots(
+        &self,
+        client_id: &str,
+        max: usize,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<()> {
+        self.lock_snapshots()?
+            .set_max_auto_snapshots(client_id, max)?;
+        sink.snapshots_changed(client_id);
+        Ok(())
+    }
+
+    pub fn set_max_manual_snapshots(
+        &self,
+        client_id: &str,
+        max: usize,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<()> {
+        self.lock_snapshots()?
+            .set_max_manual_snapshots(client_id, max)?;
+        sink.snapshots_changed(client_id);
+        Ok(())
+    }
+
+    pub fn set_snapshot_retention(
+        &self,
+        client_id: &str,
+        policy: Option<RetentionPolicy>,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<()> {
+        self.lock_snapshots()?
+            .set_retention_policy(client_id, policy)?;
+        sink.snapshots_changed(client_id);
+        Ok(())
+    }
+}
+
+/// 快照中各配置文件的内容；旧版单文件快照映射到客户端的默认配置路径
+pub fn snapshot_file_contents(
+    client: &ClientConfig,
+    snapshot: &Snapshot,
+) -> BTreeMap<String, String> {
+    if snapshot.is_multi_file() {
+        return snapshot.get_file_contents().into_iter().collect();
+    }
+    let mut files = BTreeMap::new();
+    if let Some(path) = client.default_config_path() {
+        files.insert(path.to_string(), snapshot.content.clone());
+    }
+    files
+}
+
+fn build_snapshot_diff(
+    client: &ClientConfig,
+    from: &Snapshot,
+    to_files: BTreeMap<String, String>,
+    to_snapshot: Option<String>,
+) -> SnapshotDiff {
+    let to_label = to_snapshot.as_deref().unwrap_or("current");
+    let files = diff_file_sets(
+        &snapshot_file_contents(client, from),
+        &to_files,
+        &from.id,
+        to_label,
+    );
+    SnapshotDiff {
+        client_id: client.id.clone(),
+        from_snapshot: from.id.clone(),
+        added_lines: files.iter().map(|file| file.added_lines).sum(),
+        removed_lines: files.iter().map(|file| file.removed_lines).sum(),
+        to_snapshot,
+        files,
+    }
+}
+
+fn restore_entries(
+    client: &ClientConfig,
+    snapshot: &Snapshot,
+) -> VaultResult<Vec<(String, String)>> {
+    if snapshot.is_multi_file() {
+        let contents = snapshot.get_file_contents();
+        if contents.is_empty() && snapshot.path_patterns.is_empty() {
+            return Err(
+                VaultError::new(ErrorCode::SnapshotEmpty).with_param("snapshot_id", &snapshot.id)
+            );
+        }
+        let mut entries: Vec<(String, String)> = contents.into_iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
+    } else {
+        let target_path = client.resolve_config_path(None)?;
+        Ok(vec![(target_path, snapshot.content.clone())])
+    }
+}
+
+/// 快照记录的通配模式当前匹配、但快照中没有的文件
+fn files_added_since(snapshot: &Snapshot, restored: &[(String, String)]) -> Vec<String> {
+    let mut files: Vec<String> = snapshot
+        .path_patterns
+        .iter()
+        .flat_map(|pattern| expand_path_entry(pattern))
+        .filter(|path| !restored.iter().any(|(restored, _)| restored == path))
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn notify_removing(sink: &dyn VaultEventSink, client_id: &str, removals: &[String]) {
+    if removals.is_empty() {
+        return;
+    }
+    let paths: Vec<PathBuf> = removals.iter().map(resolve_path).collect();
+    sink.config_files_removing(client_id, &paths);
+}
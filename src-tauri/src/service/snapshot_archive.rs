@@ -0,0 +1,115 @@
+use super::{ErrorCode, VaultError, VaultEventSink, VaultResult, VaultService};
+use crate::error::ErrorCause;
+use crate::models::{
+    SnapshotArchive, SnapshotArchiveEntry, SnapshotArchiveManifest, SnapshotConfig,
+    SnapshotImportResult, SNAPSHOT_ARCHIVE_FORMAT, SNAPSHOT_ARCHIVE_VERSION,
+};
+use chrono::Utc;
+use std::collections::HashMap;
+
+impl VaultService {
+    /// 把客户端的快照历史导出为 JSON 归档
+    ///
+    /// `snapshot_ids` 为 `None` 时导出全部快照并附带快照配置，否则只导出所选快照。
+    pub fn export_snapshot_archive(
+        &self,
+        client_id: &str,
+        snapshot_ids: Option<Vec<String>>,
+    ) -> VaultResult<String> {
+        let config = self
+            .lock_snapshots()?
+            .export_snapshots(client_id, snapshot_ids.as_deref())?;
+        let entries = config
+            .snapshots
+            .iter()
+            .map(|snapshot| SnapshotArchiveEntry {
+                id: snapshot.id.clone(),
+                name: snapshot.name.clone(),
+                created_at: snapshot.created_at,
+                is_auto: snapshot.is_auto,
+                content_hash: snapshot.content_hash.clone(),
+                files: snapshot.get_file_contents().into_keys().collect(),
+            })
+            .collect();
+        let settings = snapshot_ids.is_none().then(|| SnapshotConfig {
+            snapshots: Vec::new(),
+            ..config.clone()
+        });
+        let archive = SnapshotArchive {
+            manifest: SnapshotArchiveManifest {
+                format: SNAPSHOT_ARCHIVE_FORMAT.to_string(),
+                version: SNAPSHOT_ARCHIVE_VERSION,
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                exported_at: Utc::now(),
+                client_id: config.client_id.clone(),
+                entries,
+            },
+            settings,
+            snapshots: config.snapshots,
+        };
+        serde_json::to_string_pretty(&archive)
+            .map_err(|e| VaultError::serialize_failed("snapshot_archive", e))
+    }
+
+    /// 导入快照归档，`target_client_id` 为空时导入到归档记录的原客户端
+    pub fn import_snapshot_archive(
+        &self,
+        json_data: &str,
+        target_client_id: Option<&str>,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<SnapshotImportResult> {
+        let archive: SnapshotArchive = serde_json::from_str(json_data).map_err(|err| {
+            VaultError::new(ErrorCode::SnapshotArchiveInvalid).with_cause(ErrorCause::other(err))
+        })?;
+        let manifest = &archive.manifest;
+        if manifest.format != SNAPSHOT_ARCHIVE_FORMAT {
+            return Err(VaultError::new(ErrorCode::SnapshotArchiveInvalid)
+                .with_param("format", &manifest.format));
+        }
+        if manifest.version > SNAPSHOT_ARCHIVE_VERSION {
+            return Err(VaultError::new(ErrorCode::SnapshotArchiveUnsupported)
+                .with_param("version", manifest.version));
+        }
+        verify_manifest(&archive)?;
+
+        let target = target_client_id
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .unwrap_or(&manifest.client_id);
+        let client = self.require_client(target)?;
+        let result = self.lock_snapshots()?.import_snapshots(
+            &client.id,
+            archive.settings.as_ref(),
+            archive.snapshots,
+        )?;
+        if result.imported > 0 {
+            sink.snapshots_changed(&client.id);
+        }
+        Ok(result)
+    }
+}
+
+/// 清单须与快照一一对应，且记录的内容哈希一致
+fn verify_manifest(archive: &SnapshotArchive) -> VaultResult<()> {
+    let entries: HashMap<&str, &SnapshotArchiveEntry> = archive
+        .manifest
+        .entries
+        .iter()
+        .map(|entry| (entry.id.as_str(), entry))
+        .collect();
+    if entries.len() != archive.manifest.entries.len() || entries.len() != archive.snapshots.len() {
+        return Err(VaultError::new(ErrorCode::SnapshotArchiveCorrupted)
+            .with_param("entries", archive.manifest.entries.len())
+            .with_param("snapshots", archive.snapshots.len()));
+    }
+    for snapshot in &archive.snapshots {
+        let matches = entries
+            .get(snapshot.id.as_str())
+            .is_some_and(|entry| entry.content_hash == snapshot.content_hash);
+        if !matches {
+            return Err(VaultError::new(ErrorCode::SnapshotArchiveCorrupted)
+                .with_param("snapshot_id", &snapshot.id));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use super::snapshot::snapshot_file_contents;
+use super::{ErrorCode, VaultError, VaultResult, VaultService};
+use crate::error::ErrorCause;
+use crate::models::{Snapshot, SnapshotMatchField, SnapshotSearchHit, SnapshotSearchQuery};
+use regex::Regex;
+
+/// 文本条件：不区分大小写的子串或正则表达式
+enum TextMatcher {
+    Substring(String),
+    Pattern(Regex),
+}
+
+impl TextMatcher {
+    fn new(text: &str, regex: bool) -> VaultResult<Self> {
+        if !regex {
+            return Ok(Self::Substring(text.to_lowercase()));
+        }
+        Regex::new(text).map(Self::Pattern).map_err(|err| {
+            VaultError::new(ErrorCode::SnapshotInvalidPattern)
+                .with_param("pattern", text)
+                .with_cause(ErrorCause::other(err))
+        })
+    }
+
+    fn is_match(&self, haystack: &str) -> bool {
+        match self {
+            Self::Substring(needle) => haystack.to_lowercase().contains(needle),
+            Self::Pattern(regex) => regex.is_match(haystack),
+        }
+    }
+}
+
+impl VaultService {
+    /// 按标签、文本与时间范围跨客户端检索快照，结果按创建时间倒序
+    ///
+    /// 标签与时间条件只看元数据；指定文本时才为候选快照加载正文。
+    pub fn search_snapshots(
+        &self,
+        query: &SnapshotSearchQuery,
+    ) -> VaultResult<Vec<SnapshotSearchHit>> {
+        let matcher = match query.text.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => Some(TextMatcher::new(text, query.regex)?),
+            _ => None,
+        };
+        let clients = match query.client_id.as_deref() {
+            Some(client_id) => vec![self.require_client(client_id)?],
+            None => self.get_clients()?,
+        };
+
+        let mut hits = Vec::new();
+        for client in clients {
+            let repo = self.lock_snapshots()?;
+            for snapshot in repo.get_snapshots(&client.id)? {
+                if !matches_metadata(query, &snapshot) {
+                    continue;
+                }
+                let (matched_fields, matched_files) = match matcher {
+                    Some(ref matcher) => {
+                        let loaded = repo.load_snapshot(&client.id, &snapshot.id)?;
+                        let files: Vec<String> = snapshot_file_contents(&client, &loaded)
+                            .into_iter()
+                            .filter(|(_, content)| matcher.is_match(content))
+                            .map(|(path, _)| path)
+                            .collect();
+                        let mut fields = Vec::new();
+                        if matcher.is_match(&snapshot.name) {
+                            fields.push(SnapshotMatchField::Name);
+                        }
+                        if matcher.is_match(&snapshot.notes) {
+                            fields.push(SnapshotMatchField::Notes);
+                        }
+                        if !files.is_empty() {
+                            fields.push(SnapshotMatchField::Content);
+                        }
+                        if fields.is_empty() {
+                            continue;
+                        }
+                        (fields, files)
+                    }
+                    None => (Vec::new(), Vec::new()),
+                };
+                hits.push(SnapshotSearchHit {
+                    client_id: client.id.clone(),
+                    snapshot,
+                    matched_fields,
+                    matched_files,
+                });
+            }
+        }
+
+        hits.sort_by_key(|hit| std::cmp::Reverse(hit.snapshot.created_at));
+        if let Some(limit) = query.limit {
+            hits.truncate(limit);
+        }
+        Ok(hits)
+    }
+}
+
+fn matches_metadata(query: &SnapshotSearchQuery, snapshot: &Snapshot) -> bool {
+    query
+        .labels
+        .iter()
+        .filter(|label| !label.trim().is_empty())
+        .all(|label| snapshot.has_label(label))
+        && query.from.is_none_or(|from| snapshot.created_at >= from)
+        && query.to.is_none_or(|to| snapshot.created_at <= to)
+}
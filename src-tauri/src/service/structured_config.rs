@@ -0,0 +1,105 @@
+//! 按键路径读取、校验与修改 JSON、TOML、YAML 客户端配置文件
+
+use super::config_files::read_optional_file;
+use super::{ErrorCode, VaultError, VaultEventSink, VaultResult, VaultService};
+use crate::client_catalog::ConfigFormat;
+use crate::models::{ConfigPatchResult, ConfigValidation, ConfigValue};
+use crate::utils::file_ops::atomic_write;
+use crate::utils::path_utils::resolve_path;
+use crate::utils::structured;
+use serde_json::Value;
+
+impl VaultService {
+    /// 读取配置文件中键路径上的值；文件不存在时按空文档处理
+    pub fn read_config_value(
+        &self,
+        client_id: &str,
+        config_path: Option<String>,
+        key_path: Vec<String>,
+    ) -> VaultResult<ConfigValue> {
+        let (resolved, format) = self.resolve_structured_path(client_id, config_path)?;
+        let content = read_optional_file(&resolved)?;
+        let value = if content.trim().is_empty() {
+            None
+        } else {
+            structured::get_value(format, &content, &key_path)
+                .map_err(|err| err.with_path(&resolved))?
+        };
+        Ok(ConfigValue {
+            path: resolved,
+            format,
+            key_path,
+            value,
+        })
+    }
+
+    /// 校验 `content`（缺省时为磁盘上的文件内容）的语法，不写入任何文件
+    pub fn validate_config_file(
+        &self,
+        client_id: &str,
+        config_path: Option<String>,
+        content: Option<String>,
+    ) -> VaultResult<ConfigValidation> {
+        let resolved = self.resolve_client_path(client_id, config_path)?;
+        let format = ConfigFormat::from_path(&resolved);
+        let content = match content {
+            Some(content) => content,
+            None => read_optional_file(&resolved)?,
+        };
+        let error = structured::validate(format, &content).err();
+        Ok(ConfigValidation {
+            path: resolved,
+            format,
+            valid: error.is_none(),
+            error,
+        })
+    }
+
+    /// 把键路径上的值设为 `value` 后写回文件，其余内容的注释与排版尽量保持不变
+    pub fn patch_config_file(
+        &self,
+        client_id: &str,
+        config_path: Option<String>,
+        key_path: Vec<String>,
+        value: Value,
+        sink: &dyn VaultEventSink,
+    ) -> VaultResult<ConfigPatchResult> {
+        let (resolved, format) = self.resolve_structured_path(client_id, config_path)?;
+        let content = read_optional_file(&resolved)?;
+        let patched = structured::set_value(format, &content, &key_path, &value)
+            .map_err(|err| err.with_path(&resolved))?;
+
+        let path = resolve_path(&resolved);
+        sink.config_files_writing(client_id, &[(path.clone(), patched.content.as_str())]);
+        let written = atomic_write(&path, &patched.content);
+        // 内容不是来自编辑器，写入后需要让界面重新加载
+        let written_paths = if written.is_ok() {
+            vec![path]
+        } else {
+            Vec::new()
+        };
+        sink.config_files_written(client_id, &written_paths);
+        written?;
+
+        Ok(ConfigPatchResult {
+            path: resolved,
+            format,
+            formatting_preserved: patched.formatting_preserved,
+        })
+    }
+
+    fn resolve_structured_path(
+        &self,
+        client_id: &str,
+        config_path: Option<String>,
+    ) -> VaultResult<(String, ConfigFormat)> {
+        let resolved = self.resolve_client_path(client_id, config_path)?;
+        let format = ConfigFormat::from_path(&resolved);
+        if !format.is_structured() {
+            return Err(VaultError::new(ErrorCode::ConfigNotStructured)
+                .with_param("format", format.as_str())
+                .with_path(&resolved));
+        }
+        Ok((resolved, format))
+    }
+}
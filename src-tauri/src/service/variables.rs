@@ -0,0 +1,321 @@
+//! 模板变量
+//!
+//! 提示词、组合与模板文件可以用 `{{name}}` 引用变量，应用到配置文件前统一替换；
+//! 编辑器直接保存的内容原样写入。取值优先级：项目覆盖 > 库中默认值；内置变量由写入上下文提供。
+//! `\{{` 输出字面量 `{{`；花括号内不是合法变量名的内容（如 `{{#each}}`）原样保留。
+
+use super::project::normalize_project_path_str;
+use super::{ErrorCode, VaultError, VaultResult, VaultService};
+use crate::models::{Project, VariableDefinition};
+use crate::storage::load_store;
+use crate::storage::AppConfig;
+use chrono::Local;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::path::Path;
+
+/// 由写入上下文提供、不允许用户重新定义的变量
+const BUILTIN_VARIABLES: [&str; 5] = ["project_name", "project_path", "date", "os", "client_id"];
+
+impl VaultService {
+    pub fn get_variables(&self) -> VaultResult<Vec<VariableDefinition>> {
+        let store = load_store()?;
+        Ok(store.config().variables.clone())
+    }
+
+    /// 整体替换库中的变量定义
+    pub fn save_variables(
+        &self,
+        variables: Vec<VariableDefinition>,
+    ) -> VaultResult<Vec<VariableDefinition>> {
+        let mut seen = BTreeSet::new();
+        let mut normalized = Vec::with_capacity(variables.len());
+        for mut variable in variables {
+            variable.name = variable.name.trim().to_string();
+            validate_name(&variable.name)?;
+            if BUILTIN_VARIABLES.contains(&variable.name.as_str()) {
+                return Err(variable_error(ErrorCode::VariableReserved, &variable.name));
+            }
+            if !seen.insert(variable.name.clone()) {
+                return Err(variable_error(ErrorCode::VariableDuplicate, &variable.name));
+            }
+            normalized.push(variable);
+        }
+
+        let mut store = load_store()?;
+        store.config_mut().variables = normalized.clone();
+        store.save()?;
+        Ok(normalized)
+    }
+
+    /// 按客户端上下文替换提示词中的变量，应用或追加到配置文件前调用
+    pub fn render_prompt(&self, prompt_id: &str, client_id: &str) -> VaultResult<String> {
+        let prompt = self.get_prompt(prompt_id)?.ok_or_else(|| {
+            VaultError::new(ErrorCode::PromptNotFound).with_param("prompt_id", prompt_id)
+        })?;
+        self.require_client(client_id)?;
+        let store = load_store()?;
+        VariableContext::new(store.config(), None, Some(client_id)).render(&prompt.content)
+    }
+
+    pub fn get_project_variables(
+        &self,
+        project_path: &str,
+    ) -> VaultResult<BTreeMap<String, String>> {
+        let project_path = normalize_project_path_str(project_path)?;
+        let store = load_store()?;
+        Ok(find_project(store.config(), &project_path)
+            .map(|project| project.variables.clone())
+            .unwrap_or_default())
+    }
+
+    /// 整体替换项目的变量覆盖，只能覆盖库中已定义的变量
+    pub fn set_project_variables(
+        &self,
+        project_path: &str,
+        variables: BTreeMap<String, String>,
+    ) -> VaultResult<Project> {
+        let project_path = normalize_project_path_str(project_path)?;
+        let mut store = load_store()?;
+        let config = store.config_mut();
+        if let Some(name) = variables
+            .keys()
+            .find(|name| !config.variables.iter().any(|v| &v.name == *name))
+        {
+            return Err(variable_error(ErrorCode::VariableNotDefined, name));
+        }
+
+        let project = match config
+            .projects
+            .iter_mut()
+            .position(|project| project.path == project_path)
+        {
+            Some(index) => &mut config.projects[index],
+            None => {
+                config.projects.push(Project::new(project_path));
+                config.projects.last_mut().expect("刚插入的项目")
+            }
+        };
+        project.variables = variables;
+        let updated = project.clone();
+        store.save()?;
+        Ok(updated)
+    }
+}
+
+/// 一次写入可用的变量取值，`None` 表示变量存在但当前没有取值
+#[derive(Debug)]
+pub(super) struct VariableContext {
+    values: BTreeMap<String, Option<String>>,
+}
+
+impl VariableContext {
+    /// 按库中定义与项目覆盖构建上下文；未提供项目或客户端时对应的内置变量没有取值
+    pub(super) fn new(
+        config: &AppConfig,
+        project_path: Option<&Path>,
+        client_id: Option<&str>,
+    ) -> Self {
+        let mut values = BTreeMap::new();
+        values.insert(
+            "date".to_string(),
+            Some(Local::now().format("%Y-%m-%d").to_string()),
+        );
+        values.insert("os".to_string(), Some(env::consts::OS.to_string()));
+        values.insert("client_id".to_string(), client_id.map(str::to_string));
+        values.insert(
+            "project_path".to_string(),
+            project_path.map(|path| path.to_string_lossy().to_string()),
+        );
+        values.insert(
+            "project_name".to_string(),
+            project_path
+                .and_then(Path::file_name)
+                .map(|name| name.to_string_lossy().to_string()),
+        );
+
+        let overrides = project_path.and_then(|path| find_project(config, path));
+        for variable in &config.variables {
+            let value = overrides
+                .and_then(|project| project.variables.get(&variable.name))
+                .or(variable.default.as_ref())
+                .cloned();
+            values.insert(variable.name.clone(), value);
+        }
+        Self { values }
+    }
+
+    /// 替换内容中的全部变量，存在未定义或没有取值的变量时返回错误而不是原样写出
+    pub(super) fn render(&self, content: &str) -> VaultResult<String> {
+        let mut output = String::with_capacity(content.len());
+        let mut unknown = BTreeSet::new();
+        let mut missing = BTreeSet::new();
+        let mut rest = content;
+        while let Some(open) = rest.find("{{") {
+            if rest[..open].ends_with('\\') {
+                output.push_str(&rest[..open - 1]);
+                output.push_str("{{");
+                rest = &rest[open + 2..];
+                continue;
+            }
+            output.push_str(&rest[..open]);
+            let after = &rest[open + 2..];
+            let placeholder = after
+                .find("}}")
+                .map(|close| (after[..close].trim(), close))
+                .filter(|(name, _)| is_valid_name(name));
+            let Some((name, close)) = placeholder else {
+                output.push_str("{{");
+                rest = after;
+                continue;
+            };
+            match self.values.get(name) {
+                Some(Some(value)) => output.push_str(value),
+                Some(None) => {
+                    missing.insert(name);
+                }
+                None => {
+                    unknown.insert(name);
+                }
+            }
+            rest = &after[close + 2..];
+        }
+        output.push_str(rest);
+
+        if !unknown.is_empty() {
+            return Err(unresolved(ErrorCode::VariableUnknown, &unknown));
+        }
+        if !missing.is_empty() {
+            return Err(unresolved(ErrorCode::VariableMissingValue, &missing));
+        }
+        Ok(output)
+    }
+}
+
+fn find_project<'a>(config: &'a AppConfig, project_path: &Path) -> Option<&'a Project> {
+    config
+        .projects
+        .iter()
+        .find(|project| project.path == project_path)
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+fn validate_name(name: &str) -> VaultResult<()> {
+    if is_valid_name(name) {
+        Ok(())
+    } else {
+        Err(variable_error(ErrorCode::VariableNameInvalid, name))
+    }
+}
+
+fn variable_error(code: ErrorCode, name: &str) -> VaultError {
+    VaultError::new(code).with_param("name", name)
+}
+
+fn unresolved(code: ErrorCode, names: &BTreeSet<&str>) -> VaultError {
+    let names: Vec<&str> = names.iter().copied().collect();
+    VaultError::new(code).with_param("names", names.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn variable(name: &str, default: Option<&str>) -> VariableDefinition {
+        VariableDefinition {
+            name: name.to_string(),
+            default: default.map(str::to_string),
+            description: None,
+        }
+    }
+
+    fn config() -> AppConfig {
+        let mut project = Project::new(PathBuf::from("/work/demo"));
+        project
+            .variables
+            .insert("repo".to_string(), "demo-repo".to_string());
+        AppConfig {
+            projects: vec![project],
+            variables: vec![
+                variable("repo", Some("default-repo")),
+                variable("team", Some("core")),
+                variable("token", None),
+            ],
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn render_replaces_builtins() {
+        let config = config();
+        let context = VariableContext::new(&config, Some(Path::new("/work/demo")), Some("claude"));
+        let output = context
+            .render("{{project_name}} {{ project_path }} {{client_id}} {{os}}")
+            .unwrap();
+        assert_eq!(
+            output,
+            format!("demo /work/demo claude {}", env::consts::OS)
+        );
+        let date = context.render("{{date}}").unwrap();
+        assert_eq!(date, Local::now().format("%Y-%m-%d").to_string());
+    }
+
+    #[test]
+    fn render_prefers_project_overrides_over_defaults() {
+        let config = config();
+        let in_project = VariableContext::new(&config, Some(Path::new("/work/demo")), None);
+        assert_eq!(
+            in_project.render("{{repo}}/{{team}}").unwrap(),
+            "demo-repo/core"
+        );
+
+        let elsewhere = VariableContext::new(&config, Some(Path::new("/work/other")), None);
+        assert_eq!(
+            elsewhere.render("{{repo}}/{{team}}").unwrap(),
+            "default-repo/core"
+        );
+    }
+
+    #[test]
+    fn render_reports_unknown_variables() {
+        let config = config();
+        let context = VariableContext::new(&config, None, None);
+        let err = context
+            .render("{{nope}} {{team}} {{also_nope}}")
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::VariableUnknown);
+        assert_eq!(
+            err.params.get("names").map(String::as_str),
+            Some("also_nope, nope")
+        );
+    }
+
+    #[test]
+    fn render_reports_variables_without_value() {
+        let config = config();
+        let context = VariableContext::new(&config, None, None);
+        let err = context.render("{{token}} {{project_name}}").unwrap_err();
+        assert_eq!(err.code, ErrorCode::VariableMissingValue);
+        assert_eq!(
+            err.params.get("names").map(String::as_str),
+            Some("project_name, token")
+        );
+    }
+
+    #[test]
+    fn render_keeps_escapes_and_non_variable_braces() {
+        let config = config();
+        let context = VariableContext::new(&config, None, Some("claude"));
+        let output = context
+            .render(r"\{{client_id}} {{#each items}} {{a b}} {{ client_id }}")
+            .unwrap();
+        assert_eq!(output, "{{client_id}} {{#each items}} {{a b}} claude");
+    }
+}
@@ -0,0 +1,46 @@
+//! 应用数据目录及其中按项目存放的数据
+
+use super::{history, AppStore};
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::models::HistoryEntry;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+const APP_DIR_NAME: &str = "SystemPromptVault";
+const BACKUPS_DIR_NAME: &str = "backups";
+
+pub fn load_store() -> VaultResult<AppStore> {
+    let mut store = AppStore::new()?;
+    store.load()?;
+    Ok(store)
+}
+
+pub fn ensure_app_dir() -> VaultResult<PathBuf> {
+    let mut dir = dirs::data_dir().ok_or_else(|| VaultError::new(ErrorCode::DataDirUnavailable))?;
+    dir.push(APP_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|e| VaultError::create_dir_failed(&dir, e))?;
+    Ok(dir)
+}
+
+pub fn ensure_backups_dir() -> VaultResult<PathBuf> {
+    let mut dir = ensure_app_dir()?;
+    dir.push(BACKUPS_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|e| VaultError::create_dir_failed(&dir, e))?;
+    Ok(dir)
+}
+
+pub fn project_hash(path: &Path) -> String {
+    let mut hasher = DefaultHasher::new();
+    path.to_string_lossy().to_lowercase().hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+pub fn read_history_entries(project_path: &Path) -> VaultResult<Vec<HistoryEntry>> {
+    history::read_entries(&ensure_app_dir()?, &project_hash(project_path))
+}
+
+pub fn append_history_entry(project_path: &Path, entry: HistoryEntry) -> VaultResult<()> {
+    history::append_entry(&ensure_app_dir()?, &project_hash(project_path), &entry)
+}
@@ -0,0 +1,239 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::models::Backup;
+use crate::utils::atomic_write;
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::app_data::{ensure_backups_dir, project_hash};
+
+const CONFIG_DIRS: [&str; 3] = [".claude", ".codex", ".gemini"];
+const METADATA_FILE: &str = "metadata.json";
+
+#[derive(Debug, Clone)]
+pub struct BackupManager {
+    root: PathBuf,
+}
+
+impl BackupManager {
+    pub fn new() -> VaultResult<Self> {
+        Ok(Self {
+            root: ensure_backups_dir()?,
+        })
+    }
+
+    pub fn create_with_label(
+        &self,
+        project_path: &Path,
+        template_name: &str,
+    ) -> VaultResult<Backup> {
+        let now = Utc::now();
+        let bucket = self.project_bucket(project_path);
+        fs::create_dir_all(&bucket).map_err(|e| VaultError::create_dir_failed(&bucket, e))?;
+
+        let backup_id = format!("backup_{}_{}", now.format("%Y%m%d_%H%M%S"), Uuid::new_v4());
+        let backup_dir = bucket.join(&backup_id);
+        fs::create_dir_all(&backup_dir)
+            .map_err(|e| VaultError::create_dir_failed(&backup_dir, e))?;
+
+        let mut files = Vec::new();
+        for dir_name in CONFIG_DIRS {
+            let source = project_path.join(dir_name);
+            if source.exists() {
+                let target = backup_dir.join(dir_name);
+                copy_dir_with_tracking(&source, &target, project_path, &mut files)?;
+            }
+        }
+
+        let backup = Backup {
+            id: backup_id,
+            project_path: project_path.to_string_lossy().to_string(),
+            template_name: template_name.to_string(),
+            created_at: now.to_rfc3339(),
+            files,
+        };
+        self.write_metadata(&backup_dir, &backup)?;
+        Ok(backup)
+    }
+
+    pub fn list(&self, project_path: &Path) -> VaultResult<Vec<Backup>> {
+        let bucket = self.project_bucket(project_path);
+        if !bucket.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = Vec::new();
+        for entry in fs::read_dir(&bucket).map_err(|e| VaultError::read_failed(&bucket, e))? {
+            let entry = entry.map_err(|e| VaultError::read_failed(&bucket, e))?;
+            if entry
+                .file_type()
+                .map_err(|e| VaultError::read_failed(entry.path(), e))?
+                .is_dir()
+            {
+                if let Some(backup) = self.read_metadata(&entry.path())? {
+                    backups.push(backup);
+                }
+            }
+        }
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(backups)
+    }
+
+    pub fn restore_backup(&self, project_path: &Path, backup_id: &str) -> VaultResult<Backup> {
+        let bucket = self.project_bucket(project_path);
+        let backup_dir = bucket.join(backup_id);
+        if !backup_dir.exists() {
+            return Err(
+                VaultError::new(ErrorCode::BackupNotFound).with_param("backup_id", backup_id)
+            );
+        }
+
+        let backup = self.read_metadata(&backup_dir)?.ok_or_else(|| {
+            VaultError::new(ErrorCode::BackupMetadataMissing).with_param("backup_id", backup_id)
+        })?;
+
+        for dir_name in CONFIG_DIRS {
+            let target = project_path.join(dir_name);
+            if target.exists() {
+                fs::remove_dir_all(&target).map_err(|e| VaultError::remove_failed(&target, e))?;
+            }
+            let source = backup_dir.join(dir_name);
+            if source.exists() {
+                copy_dir_simple(&source, &target)?;
+            }
+        }
+        Ok(backup)
+    }
+
+    pub fn clean_old(&self, retention_count: u32) -> VaultResult<u32> {
+        if !self.root.exists() {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.root).map_err(|e| VaultError::read_failed(&self.root, e))? {
+            let entry = entry.map_err(|e| VaultError::read_failed(&self.root, e))?;
+            if !entry
+                .file_type()
+                .map_err(|e| VaultError::read_failed(entry.path(), e))?
+                .is_dir()
+            {
+                continue;
+            }
+
+            let mut backups = Vec::new();
+            for backup_entry in
+                fs::read_dir(entry.path()).map_err(|e| VaultError::read_failed(entry.path(), e))?
+            {
+                let backup_entry =
+                    backup_entry.map_err(|e| VaultError::read_failed(entry.path(), e))?;
+                if backup_entry
+                    .file_type()
+                    .map_err(|e| VaultError::read_failed(backup_entry.path(), e))?
+                    .is_dir()
+                {
+                    if let Some(backup) = self.read_metadata(&backup_entry.path())? {
+                        backups.push((backup_entry.path(), backup));
+                    }
+                }
+            }
+
+            backups.sort_by(|a, b| b.1.created_at.cmp(&a.1.created_at));
+            let retain = retention_count as usize;
+            if retain >= backups.len() {
+                continue;
+            }
+
+            for (path, _) in backups.into_iter().skip(retain) {
+                fs::remove_dir_all(&path).map_err(|e| VaultError::remove_failed(&path, e))?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    fn project_bucket(&self, project_path: &Path) -> PathBuf {
+        self.root.join(project_hash(project_path))
+    }
+
+    fn write_metadata(&self, backup_dir: &Path, backup: &Backup) -> VaultResult<()> {
+        let path = backup_dir.join(METADATA_FILE);
+        let content = serde_json::to_string_pretty(backup)
+            .map_err(|e| VaultError::serialize_failed("backup", e))?;
+        atomic_write(&path, &content)
+    }
+
+    fn read_metadata(&self, backup_dir: &Path) -> VaultResult<Option<Backup>> {
+        let path = backup_dir.join(METADATA_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| VaultError::read_failed(&path, e))?;
+        let backup =
+            serde_json::from_str::<Backup>(&raw).map_err(|e| VaultError::parse_failed(&path, e))?;
+        Ok(Some(backup))
+    }
+}
+
+fn copy_dir_with_tracking(
+    source: &Path,
+    target: &Path,
+    project_path: &Path,
+    files: &mut Vec<String>,
+) -> VaultResult<()> {
+    fs::create_dir_all(target).map_err(|e| VaultError::create_dir_failed(target, e))?;
+    for entry in fs::read_dir(source).map_err(|e| VaultError::read_failed(source, e))? {
+        let entry = entry.map_err(|e| VaultError::read_failed(source, e))?;
+        let entry_path = entry.path();
+        let target_path = target.join(entry.file_name());
+        let metadata = entry
+            .metadata()
+            .map_err(|e| VaultError::read_failed(&entry_path, e))?;
+        if metadata.is_dir() {
+            copy_dir_with_tracking(&entry_path, &target_path, project_path, files)?;
+        } else if metadata.is_file() {
+            if let Some(parent) = target_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| VaultError::create_dir_failed(parent, e))?;
+            }
+            fs::copy(&entry_path, &target_path).map_err(|e| {
+                VaultError::new(ErrorCode::IoCopyFailed)
+                    .with_path(&entry_path)
+                    .with_cause(e)
+            })?;
+            if let Ok(relative) = entry_path.strip_prefix(project_path) {
+                files.push(relative.to_string_lossy().to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn copy_dir_simple(source: &Path, target: &Path) -> VaultResult<()> {
+    if !source.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(target).map_err(|e| VaultError::create_dir_failed(target, e))?;
+    for entry in fs::read_dir(source).map_err(|e| VaultError::read_failed(source, e))? {
+        let entry = entry.map_err(|e| VaultError::read_failed(source, e))?;
+        let entry_path = entry.path();
+        let target_path = target.join(entry.file_name());
+        let metadata = entry
+            .metadata()
+            .map_err(|e| VaultError::read_failed(&entry_path, e))?;
+        if metadata.is_dir() {
+            copy_dir_simple(&entry_path, &target_path)?;
+        } else if metadata.is_file() {
+            if let Some(parent) = target_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| VaultError::create_dir_failed(parent, e))?;
+            }
+            fs::copy(&entry_path, &target_path).map_err(|e| {
+                VaultError::new(ErrorCode::IoCopyFailed)
+                    .with_path(&entry_path)
+                    .with_cause(e)
+            })?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use crate::error::{VaultError, VaultResult};
+use crate::utils::file_ops::atomic_write;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+const BLOBS_DIR_NAME: &str = "blobs";
+const REFCOUNTS_FILE_NAME: &str = "refcounts.json";
+
+/// 以 SHA-256 为键的内容寻址存储，相同内容只落盘一次
+///
+/// 引用计数保存在 `blobs/refcounts.json`，计数归零时删除对应 blob。
+pub struct BlobStore {
+    dir: PathBuf,
+}
+
+impl BlobStore {
+    pub fn new(app_data_dir: PathBuf) -> VaultResult<Self> {
+        let dir = app_data_dir.join(BLOBS_DIR_NAME);
+        fs::create_dir_all(&dir).map_err(|e| VaultError::create_dir_failed(&dir, e))?;
+        Ok(Self { dir })
+    }
+
+    pub fn hash(content: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(content.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// 写入内容并返回其哈希；已存在时不重复写入
+    pub fn put(&self, content: &str) -> VaultResult<String> {
+        let hash = Self::hash(content);
+        let path = self.blob_path(&hash);
+        if !path.exists() {
+            atomic_write(&path, content)?;
+        }
+        Ok(hash)
+    }
+
+    pub fn get(&self, hash: &str) -> VaultResult<String> {
+        let path = self.blob_path(hash);
+        fs::read_to_string(&path).map_err(|e| VaultError::read_failed(&path, e))
+    }
+
+    pub fn refcounts_exist(&self) -> bool {
+        self.refcounts_path().exists()
+    }
+
+    /// 为每个哈希增加一次引用
+    pub fn retain(&self, hashes: &BTreeSet<String>) -> VaultResult<()> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let mut counts = self.load_refcounts()?;
+        for hash in hashes {
+            *counts.entry(hash.clone()).or_insert(0) += 1;
+        }
+        self.save_refcounts(&counts)
+    }
+
+    /// 为每个哈希减少一次引用，并删除不再被引用的 blob
+    pub fn release(&self, hashes: &BTreeSet<String>) -> VaultResult<()> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let mut counts = self.load_refcounts()?;
+        let mut unreferenced = Vec::new();
+        for hash in hashes {
+            let remaining = counts
+                .get(hash)
+                .copied()
+                .unwrap_or_default()
+                .saturating_sub(1);
+            if remaining == 0 {
+                counts.remove(hash);
+                unreferenced.push(hash);
+            } else {
+                counts.insert(hash.clone(), remaining);
+            }
+        }
+        self.save_refcounts(&counts)?;
+
+        for hash in unreferenced {
+            let path = self.blob_path(hash);
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(VaultError::remove_failed(&path, err)),
+            }
+        }
+        Ok(())
+    }
+
+    /// 用完整的引用计数覆盖现有索引，用于索引缺失时的重建
+    pub fn reset_refcounts(&self, counts: BTreeMap<String, usize>) -> VaultResult<()> {
+        self.save_refcounts(&counts)
+    }
+
+    fn load_refcounts(&self) -> VaultResult<BTreeMap<String, usize>> {
+        let path = self.refcounts_path();
+        match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(&path, e)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(err) => Err(VaultError::read_failed(&path, err)),
+        }
+    }
+
+    fn save_refcounts(&self, counts: &BTreeMap<String, usize>) -> VaultResult<()> {
+        let content = serde_json::to_string_pretty(counts)
+            .map_err(|e| VaultError::serialize_failed("blob_refcounts", e))?;
+        atomic_write(self.refcounts_path(), &content)
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.dir.join(hash)
+    }
+
+    fn refcounts_path(&self) -> PathBuf {
+        self.dir.join(REFCOUNTS_FILE_NAME)
+    }
+}
@@ -1,4 +1,6 @@
-use crate::models::{default_clients, ClientConfig};
+use crate::client_catalog::ClientCatalog;
+use crate::error::{VaultError, VaultResult};
+use crate::models::ClientConfig;
 use crate::utils::file_ops::atomic_write;
 use indexmap::IndexMap;
 use serde_json::Value;
@@ -13,14 +15,15 @@ pub struct ClientRepository {
 }
 
 impl ClientRepository {
-    pub fn new(data_dir: PathBuf) -> Result<Self, String> {
-        fs::create_dir_all(&data_dir).map_err(|e| format!("创建数据目录失败: {}", e))?;
+    pub fn new(data_dir: PathBuf) -> VaultResult<Self> {
+        fs::create_dir_all(&data_dir).map_err(|e| VaultError::create_dir_failed(&data_dir, e))?;
         let path = data_dir.join(CLIENTS_FILE_NAME);
         let (clients, should_persist) = if path.exists() {
             Self::load_clients(&path)?
         } else {
             (
-                default_clients()
+                ClientCatalog::load(&data_dir)?
+                    .default_clients()
                     .into_iter()
                     .map(|client| (client.id.clone(), client))
                     .collect(),
@@ -35,20 +38,20 @@ impl ClientRepository {
         Ok(repo)
     }
 
-    pub fn get_all(&self) -> Result<Vec<ClientConfig>, String> {
+    pub fn get_all(&self) -> VaultResult<Vec<ClientConfig>> {
         Ok(self.clients.values().cloned().collect())
     }
 
-    pub fn get_by_id(&self, id: &str) -> Result<Option<ClientConfig>, String> {
+    pub fn get_by_id(&self, id: &str) -> VaultResult<Option<ClientConfig>> {
         Ok(self.clients.get(id).cloned())
     }
 
-    pub fn save(&mut self, client: ClientConfig) -> Result<(), String> {
+    pub fn save(&mut self, client: ClientConfig) -> VaultResult<()> {
         self.clients.insert(client.id.clone(), client);
         self.persist()
     }
 
-    pub fn delete(&mut self, id: &str) -> Result<bool, String> {
+    pub fn delete(&mut self, id: &str) -> VaultResult<bool> {
         let removed = self.clients.shift_remove(id).is_some();
         if removed {
             self.persist()?;
@@ -56,10 +59,10 @@ impl ClientRepository {
         Ok(removed)
     }
 
-    fn load_clients(path: &Path) -> Result<(IndexMap<String, ClientConfig>, bool), String> {
-        let raw = fs::read_to_string(path).map_err(|e| format!("读取客户端配置失败: {}", e))?;
+    fn load_clients(path: &Path) -> VaultResult<(IndexMap<String, ClientConfig>, bool)> {
+        let raw = fs::read_to_string(path).map_err(|e| VaultError::read_failed(path, e))?;
         let entries: Vec<Value> =
-            serde_json::from_str(&raw).map_err(|e| format!("解析客户端配置失败: {}", e))?;
+            serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(path, e))?;
 
         let mut migrated = false;
         let mut clients = IndexMap::new();
@@ -68,18 +71,21 @@ impl ClientRepository {
             if entry.get("config_file_paths").is_none() && entry.get("config_file_path").is_some() {
                 migrated = true;
             }
-            let client: ClientConfig =
-                serde_json::from_value(entry).map_err(|e| format!("解析客户端配置失败: {}", e))?;
+            let mut client: ClientConfig =
+                serde_json::from_value(entry).map_err(|e| VaultError::parse_failed(path, e))?;
+            if client.migrate_legacy_default_paths() {
+                migrated = true;
+            }
             clients.insert(client.id.clone(), client);
         }
 
         Ok((clients, migrated))
     }
 
-    fn persist(&self) -> Result<(), String> {
+    fn persist(&self) -> VaultResult<()> {
         let clients: Vec<ClientConfig> = self.clients.values().cloned().collect();
         let content = serde_json::to_string_pretty(&clients)
-            .map_err(|e| format!("序列化客户端配置失败: {}", e))?;
+            .map_err(|e| VaultError::serialize_failed("clients", e))?;
         atomic_write(&self.path, &content)
     }
 }
@@ -0,0 +1,264 @@
+use super::json_snapshot_storage::JsonSnapshotStorage;
+use super::snapshot_repository::SnapshotStorage;
+use crate::error::{VaultError, VaultResult};
+use crate::models::{Snapshot, SnapshotConfig};
+use git2::{FileMode, Oid, Repository, Signature, Time};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+const GIT_DIR_NAME: &str = "snapshots.git";
+const COMMITTER_NAME: &str = "SystemPromptVault";
+const COMMITTER_EMAIL: &str = "vault@localhost";
+/// 快照正文不对应任何配置文件时在树中的位置
+const LEGACY_ENTRY: &str = ".legacy";
+
+/// 每个快照记录为数据目录中裸仓库 `snapshots.git` 的一次提交
+///
+/// 每个客户端一条分支 `clients/<client_id>`（ID 中的特殊字符转义为 `%XX`），提交说明为快照名称，
+/// 并附带 `Snapshot-Id` 与 `Snapshot-Auto` trailer，因此可以直接用 `git log -p clients/<client_id>`
+/// 查看演变。
+/// 快照列表等元数据仍由 [`JsonSnapshotStorage`] 保存，`files` 记录 Git blob 的对象 ID；
+/// 启用前已存在的快照继续从 blob 存储读取。裁剪只把快照移出列表，Git 历史保持不变。
+pub struct GitSnapshotStorage {
+    metadata: JsonSnapshotStorage,
+    repo: Repository,
+}
+
+impl GitSnapshotStorage {
+    pub fn open(app_data_dir: PathBuf) -> VaultResult<Self> {
+        let git_dir = app_data_dir.join(GIT_DIR_NAME);
+        let repo = if git_dir.exists() {
+            Repository::open_bare(&git_dir)
+        } else {
+            Repository::init_bare(&git_dir)
+        }
+        .map_err(|err| VaultError::git_failed(err).with_path(&git_dir))?;
+        Ok(Self {
+            metadata: JsonSnapshotStorage::open(app_data_dir)?,
+            repo,
+        })
+    }
+
+    /// 客户端对应的分支引用；ID 中字母、数字、`-`、`_` 以外的字符按 UTF-8 字节转义为 `%XX`，
+    /// 不同的 ID 总是对应不同的分支
+    fn branch_ref(client_id: &str) -> String {
+        let mut name = String::with_capacity(client_id.len());
+        for byte in client_id.bytes() {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_') {
+                name.push(char::from(byte));
+            } else {
+                name.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        format!("refs/heads/clients/{}", name)
+    }
+
+    fn commit_snapshot(&self, snapshot: &Snapshot, tree: &TreeNode) -> VaultResult<Oid> {
+        let tree = self
+            .repo
+            .find_tree(tree.write(&self.repo)?)
+            .map_err(VaultError::git_failed)?;
+        let refname = Self::branch_ref(&snapshot.client_id);
+        let parent = match self.repo.refname_to_id(&refname) {
+            Ok(oid) => Some(self.repo.find_commit(oid).map_err(VaultError::git_failed)?),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => None,
+            Err(err) => return Err(VaultError::git_failed(err)),
+        };
+        let time = Time::new(snapshot.created_at.timestamp(), 0);
+        let signature = Signature::new(COMMITTER_NAME, COMMITTER_EMAIL, &time)
+            .map_err(VaultError::git_failed)?;
+        let message = format!(
+            "{}\n\nSnapshot-Id: {}\nSnapshot-Auto: {}\n",
+            snapshot.name, snapshot.id, snapshot.is_auto
+        );
+        self.repo
+            .commit(
+                Some(&refname),
+                &signature,
+                &signature,
+                &message,
+                &tree,
+                &parent.iter().collect::<Vec<_>>(),
+            )
+            .map_err(VaultError::git_failed)
+    }
+
+    fn read_blob(&self, hash: &str) -> VaultResult<String> {
+        let blob = Oid::from_str(hash)
+            .and_then(|oid| self.repo.find_blob(oid))
+            .map_err(|err| VaultError::git_failed(err).with_param("hash", hash))?;
+        Ok(String::from_utf8_lossy(blob.content()).into_owned())
+    }
+}
+
+/// 正文是否保存在 Git 中；blob 存储使用 64 位的 SHA-256，Git 对象 ID 为 40 位
+fn is_git_snapshot(snapshot: &Snapshot) -> bool {
+    snapshot.blob_refs().iter().all(|hash| hash.len() == 40)
+}
+
+impl SnapshotStorage for GitSnapshotStorage {
+    fn load_config(&self, client_id: &str) -> VaultResult<Option<SnapshotConfig>> {
+        self.metadata.load_config(client_id)
+    }
+
+    fn save_config(&self, config: &SnapshotConfig) -> VaultResult<()> {
+        self.metadata.save_config(config)
+    }
+
+    fn store_bodies(&self, snapshot: &mut Snapshot) -> VaultResult<()> {
+        let mut tree = TreeNode::default();
+        if let Some(contents) = snapshot.multi_file_contents.as_ref() {
+            for (path, content) in contents {
+                let oid = self
+                    .repo
+                    .blob(content.as_bytes())
+                    .map_err(VaultError::git_failed)?;
+                tree.insert(&tree_path(path), oid);
+                snapshot.files.insert(path.clone(), oid.to_string());
+            }
+        }
+        if !snapshot.content.is_empty() {
+            let oid = self
+                .repo
+                .blob(snapshot.content.as_bytes())
+                .map_err(VaultError::git_failed)?;
+            // 正文须可从提交到达，否则会被 `git gc` 清理
+            if !snapshot.files.values().any(|hash| *hash == oid.to_string()) {
+                tree.insert(&[LEGACY_ENTRY.to_string()], oid);
+            }
+            snapshot.content_blob = Some(oid.to_string());
+        }
+        self.commit_snapshot(snapshot, &tree)?;
+        snapshot.strip_bodies();
+        Ok(())
+    }
+
+    fn load_bodies(&self, snapshot: &mut Snapshot) -> VaultResult<()> {
+        if !is_git_snapshot(snapshot) {
+            return self.metadata.load_bodies(snapshot);
+        }
+        if !snapshot.files.is_empty() {
+            let mut contents = HashMap::with_capacity(snapshot.files.len());
+            for (path, hash) in &snapshot.files {
+                contents.insert(path.clone(), self.read_blob(hash)?);
+            }
+            snapshot.multi_file_contents = Some(contents);
+        }
+        if let Some(hash) = snapshot.content_blob.as_deref() {
+            snapshot.content = self.read_blob(hash)?;
+        }
+        Ok(())
+    }
+
+    /// 只释放旧快照在 blob 存储中的引用，Git 中的提交保留为历史
+    fn release(&self, snapshots: &[Snapshot]) -> VaultResult<()> {
+        let legacy: Vec<Snapshot> = snapshots
+            .iter()
+            .filter(|snapshot| !is_git_snapshot(snapshot))
+            .cloned()
+            .collect();
+        self.metadata.release(&legacy)
+    }
+}
+
+/// 配置文件路径在提交树中的位置：`~` 映射为 `home`，盘符映射为小写字母目录
+fn tree_path(path: &str) -> Vec<String> {
+    let normalized = path.replace('\\', "/");
+    let mut components = Vec::new();
+    let rest = if let Some(rest) = normalized.strip_prefix("~/") {
+        components.push("home".to_string());
+        rest
+    } else if let Some((drive, rest)) = normalized
+        .split_once(":/")
+        .filter(|(drive, _)| drive.len() == 1)
+    {
+        components.push(drive.to_ascii_lowercase());
+        rest
+    } else {
+        normalized.as_str()
+    };
+    components.extend(
+        rest.split('/')
+            .filter(|part| !part.is_empty() && *part != ".")
+            .map(|part| match part {
+                ".." => "__".to_string(),
+                ".git" => "_git".to_string(),
+                part => part.to_string(),
+            }),
+    );
+    components
+}
+
+/// 构建提交树用的目录结构
+#[derive(Default)]
+struct TreeNode {
+    blobs: BTreeMap<String, Oid>,
+    dirs: BTreeMap<String, TreeNode>,
+}
+
+impl TreeNode {
+    fn insert(&mut self, path: &[String], oid: Oid) {
+        match path {
+            [] => {}
+            [name] => {
+                self.blobs.insert(name.clone(), oid);
+            }
+            [dir, rest @ ..] => self.dirs.entry(dir.clone()).or_default().insert(rest, oid),
+        }
+    }
+
+    fn write(&self, repo: &Repository) -> VaultResult<Oid> {
+        let mut builder = repo.treebuilder(None).map_err(VaultError::git_failed)?;
+        for (name, oid) in &self.blobs {
+            builder
+                .insert(name, *oid, FileMode::Blob.into())
+                .map_err(VaultError::git_failed)?;
+        }
+        for (name, dir) in &self.dirs {
+            builder
+                .insert(name, dir.write(repo)?, FileMode::Tree.into())
+                .map_err(VaultError::git_failed)?;
+        }
+        builder.write().map_err(VaultError::git_failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branch_ref_keeps_plain_ids() {
+        assert_eq!(
+            GitSnapshotStorage::branch_ref("Claude"),
+            "refs/heads/clients/Claude"
+        );
+        assert_eq!(
+            GitSnapshotStorage::branch_ref("my-client_2"),
+            "refs/heads/clients/my-client_2"
+        );
+    }
+
+    #[test]
+    fn branch_ref_is_injective() {
+        let ids = ["a.b", "a-b", "a_b", "a%2Eb", "a/b", "a b", "客户端", "a..b"];
+        let refs: std::collections::HashSet<_> = ids
+            .iter()
+            .map(|id| GitSnapshotStorage::branch_ref(id))
+            .collect();
+        assert_eq!(refs.len(), ids.len());
+        assert_eq!(
+            GitSnapshotStorage::branch_ref("a.b"),
+            "refs/heads/clients/a%2Eb"
+        );
+        assert_eq!(
+            GitSnapshotStorage::branch_ref("a%2Eb"),
+            "refs/heads/clients/a%252Eb"
+        );
+        for id in ids {
+            assert!(git2::Reference::is_valid_name(
+                &GitSnapshotStorage::branch_ref(id)
+            ));
+        }
+    }
+}
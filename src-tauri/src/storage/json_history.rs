@@ -0,0 +1,39 @@
+//! 项目操作历史，保存在 `history/<项目路径哈希>.json`
+
+use crate::error::{VaultError, VaultResult};
+use crate::models::HistoryEntry;
+use crate::utils::file_ops::atomic_write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const HISTORY_DIR_NAME: &str = "history";
+
+pub fn read_entries(data_dir: &Path, project_hash: &str) -> VaultResult<Vec<HistoryEntry>> {
+    let path = history_file_path(data_dir, project_hash);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw = fs::read_to_string(&path).map_err(|e| VaultError::read_failed(&path, e))?;
+    let entries: Vec<HistoryEntry> =
+        serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(&path, e))?;
+    Ok(entries)
+}
+
+pub fn append_entry(data_dir: &Path, project_hash: &str, entry: &HistoryEntry) -> VaultResult<()> {
+    let mut entries = read_entries(data_dir, project_hash)?;
+    entries.push(entry.clone());
+    write_entries(data_dir, project_hash, &entries)
+}
+
+fn write_entries(data_dir: &Path, project_hash: &str, entries: &[HistoryEntry]) -> VaultResult<()> {
+    let path = history_file_path(data_dir, project_hash);
+    let content = serde_json::to_string_pretty(entries)
+        .map_err(|e| VaultError::serialize_failed("history", e))?;
+    atomic_write(&path, &content)
+}
+
+fn history_file_path(data_dir: &Path, project_hash: &str) -> PathBuf {
+    data_dir
+        .join(HISTORY_DIR_NAME)
+        .join(format!("{}.json", project_hash))
+}
@@ -0,0 +1,168 @@
+use super::blob_store::BlobStore;
+use super::snapshot_repository::SnapshotStorage;
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::models::{Snapshot, SnapshotConfig};
+use crate::utils::file_ops::atomic_write;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+const SNAPSHOT_DIR_NAME: &str = "snapshots";
+
+/// 快照元数据保存在 `snapshots/<client>.json`，文件正文按哈希存放在 [`BlobStore`]
+pub struct JsonSnapshotStorage {
+    base_dir: PathBuf,
+    blobs: BlobStore,
+}
+
+impl JsonSnapshotStorage {
+    pub fn open(app_data_dir: PathBuf) -> VaultResult<Self> {
+        let base_dir = app_data_dir.join(SNAPSHOT_DIR_NAME);
+        fs::create_dir_all(&base_dir).map_err(|e| VaultError::create_dir_failed(&base_dir, e))?;
+        let storage = Self {
+            base_dir,
+            blobs: BlobStore::new(app_data_dir)?,
+        };
+        if !storage.blobs.refcounts_exist() {
+            storage.rebuild_refcounts()?;
+        }
+        Ok(storage)
+    }
+
+    /// 已保存过快照的客户端 ID
+    pub fn client_ids(&self) -> VaultResult<Vec<String>> {
+        let mut ids = Vec::new();
+        for path in self.config_paths()? {
+            if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                ids.push(stem.to_string());
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn snapshot_file_path(&self, client_id: &str) -> PathBuf {
+        self.base_dir.join(format!("{}.json", client_id))
+    }
+
+    fn config_paths(&self) -> VaultResult<Vec<PathBuf>> {
+        let entries =
+            fs::read_dir(&self.base_dir).map_err(|e| VaultError::read_failed(&self.base_dir, e))?;
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| VaultError::read_failed(&self.base_dir, e))?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// 旧版快照把正文内联在 JSON 中，首次读取时迁移到 blob 存储
+    ///
+    /// 未能写回配置时归还已迁移快照的引用，下次读取会重新迁移，避免引用计数重复累加。
+    fn migrate_inline_bodies(&self, config: &mut SnapshotConfig) -> VaultResult<()> {
+        let mut migrated = Vec::new();
+        for snapshot in config
+            .snapshots
+            .iter_mut()
+            .filter(|snapshot| snapshot.has_inline_bodies())
+        {
+            if let Err(err) = self.store_bodies(snapshot) {
+                self.release_unsaved(&migrated);
+                return Err(err);
+            }
+            migrated.push(snapshot.clone());
+        }
+        self.save_config(config)
+            .inspect_err(|_| self.release_unsaved(&migrated))
+    }
+
+    /// 失败只记录日志，保留导致回滚的原始错误
+    fn release_unsaved(&self, snapshots: &[Snapshot]) {
+        if let Err(err) = self.release(snapshots) {
+            eprintln!("[Storage] 释放未保存快照的 blob 失败: {}", err);
+        }
+    }
+
+    /// 引用计数索引缺失时，扫描全部快照重新统计
+    fn rebuild_refcounts(&self) -> VaultResult<()> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for path in self.config_paths()? {
+            let raw = fs::read_to_string(&path).map_err(|e| VaultError::read_failed(&path, e))?;
+            let config: SnapshotConfig =
+                serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(&path, e))?;
+            let refs = config.snapshots.iter().flat_map(Snapshot::blob_refs);
+            for hash in refs {
+                *counts.entry(hash).or_insert(0) += 1;
+            }
+        }
+        self.blobs.reset_refcounts(counts)
+    }
+}
+
+impl SnapshotStorage for JsonSnapshotStorage {
+    fn load_config(&self, client_id: &str) -> VaultResult<Option<SnapshotConfig>> {
+        let path = self.snapshot_file_path(client_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| VaultError::read_failed(&path, e))?;
+        let mut config: SnapshotConfig =
+            serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(&path, e))?;
+        if config.snapshots.iter().any(Snapshot::has_inline_bodies) {
+            if config.client_id.trim().is_empty() {
+                config.client_id = client_id.to_string();
+            }
+            self.migrate_inline_bodies(&mut config)?;
+        }
+        Ok(Some(config))
+    }
+
+    fn save_config(&self, config: &SnapshotConfig) -> VaultResult<()> {
+        if config.client_id.trim().is_empty() {
+            return Err(ErrorCode::ClientIdEmpty.into());
+        }
+        let path = self.snapshot_file_path(&config.client_id);
+        let content = serde_json::to_string_pretty(config)
+            .map_err(|e| VaultError::serialize_failed("snapshot_config", e))?;
+        atomic_write(&path, &content)
+    }
+
+    fn store_bodies(&self, snapshot: &mut Snapshot) -> VaultResult<()> {
+        if let Some(contents) = snapshot.multi_file_contents.as_ref() {
+            for (path, content) in contents {
+                let hash = self.blobs.put(content)?;
+                snapshot.files.insert(path.clone(), hash);
+            }
+        }
+        if !snapshot.content.is_empty() {
+            snapshot.content_blob = Some(self.blobs.put(&snapshot.content)?);
+        }
+        snapshot.strip_bodies();
+        self.blobs.retain(&snapshot.blob_refs())
+    }
+
+    fn load_bodies(&self, snapshot: &mut Snapshot) -> VaultResult<()> {
+        if !snapshot.files.is_empty() {
+            let mut contents = HashMap::with_capacity(snapshot.files.len());
+            for (path, hash) in &snapshot.files {
+                contents.insert(path.clone(), self.blobs.get(hash)?);
+            }
+            snapshot.multi_file_contents = Some(contents);
+        }
+        if let Some(hash) = snapshot.content_blob.as_deref() {
+            snapshot.content = self.blobs.get(hash)?;
+        }
+        Ok(())
+    }
+
+    fn release(&self, snapshots: &[Snapshot]) -> VaultResult<()> {
+        for snapshot in snapshots {
+            self.blobs.release(&snapshot.blob_refs())?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
-use crate::models::{Project, Template};
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::models::{Composition, Project, Template, VariableDefinition};
 use crate::utils::file_ops::atomic_write;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -30,6 +31,12 @@ pub struct AppConfig {
     pub templates: Vec<Template>,
     pub projects: Vec<Project>,
     pub settings: AppSettings,
+    /// 模板与提示词可引用的自定义变量
+    #[serde(default)]
+    pub variables: Vec<VariableDefinition>,
+    /// 由库中提示词拼装的客户端配置文件
+    #[serde(default)]
+    pub compositions: Vec<Composition>,
     pub updated_at: DateTime<Utc>,
 }
 
@@ -39,6 +46,8 @@ impl Default for AppConfig {
             templates: Vec::new(),
             projects: Vec::new(),
             settings: AppSettings::default(),
+            variables: Vec::new(),
+            compositions: Vec::new(),
             updated_at: Utc::now(),
         }
     }
@@ -51,11 +60,8 @@ pub struct JsonStore {
 }
 
 impl JsonStore {
-    pub fn new() -> Result<Self, String> {
-        let mut base_dir = resolve_app_data_dir()?;
-        base_dir.push(APP_DIR_NAME);
-        fs::create_dir_all(&base_dir).map_err(|e| format!("创建数据目录失败: {}", e))?;
-
+    pub fn new() -> VaultResult<Self> {
+        let base_dir = app_dir()?;
         let config_path = base_dir.join(CONFIG_FILE_NAME);
         Ok(Self {
             path: config_path,
@@ -63,11 +69,12 @@ impl JsonStore {
         })
     }
 
-    pub fn load(&mut self) -> Result<&AppConfig, String> {
+    pub fn load(&mut self) -> VaultResult<&AppConfig> {
         if self.path.exists() {
-            let raw = fs::read_to_string(&self.path).map_err(|e| format!("读取配置失败: {}", e))?;
+            let raw = fs::read_to_string(&self.path)
+                .map_err(|e| VaultError::read_failed(&self.path, e))?;
             let config: AppConfig =
-                serde_json::from_str(&raw).map_err(|e| format!("解析配置失败: {}", e))?;
+                serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(&self.path, e))?;
             self.config = config;
             Ok(&self.config)
         } else {
@@ -77,11 +84,11 @@ impl JsonStore {
         }
     }
 
-    pub fn save(&mut self) -> Result<(), String> {
+    pub fn save(&mut self) -> VaultResult<()> {
         self.config.updated_at = Utc::now();
         self.ensure_parent_dir()?;
         let content = serde_json::to_string_pretty(&self.config)
-            .map_err(|e| format!("序列化配置失败: {}", e))?;
+            .map_err(|e| VaultError::serialize_failed("app_config", e))?;
         atomic_write(&self.path, &content)
     }
 
@@ -97,18 +104,26 @@ impl JsonStore {
         self.path.as_path()
     }
 
-    fn ensure_parent_dir(&self) -> Result<(), String> {
+    fn ensure_parent_dir(&self) -> VaultResult<()> {
         if let Some(parent) = self.path.parent() {
-            fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?
+            fs::create_dir_all(parent).map_err(|e| VaultError::create_dir_failed(parent, e))?
         }
         Ok(())
     }
 }
 
-fn resolve_app_data_dir() -> Result<PathBuf, String> {
+/// `app_config.json` 所在的应用目录，不存在时创建
+pub(crate) fn app_dir() -> VaultResult<PathBuf> {
+    let mut base_dir = resolve_app_data_dir()?;
+    base_dir.push(APP_DIR_NAME);
+    fs::create_dir_all(&base_dir).map_err(|e| VaultError::create_dir_failed(&base_dir, e))?;
+    Ok(base_dir)
+}
+
+fn resolve_app_data_dir() -> VaultResult<PathBuf> {
     let data_dir = legacy_tauri_app_data_dir()
         .or_else(dirs::data_dir)
-        .ok_or_else(|| "无法定位应用数据目录".to_string())?;
+        .ok_or_else(|| VaultError::new(ErrorCode::DataDirUnavailable))?;
     Ok(data_dir)
 }
 
@@ -1,7 +1,44 @@
+pub mod app_data;
+pub mod backup;
+pub mod blob_store;
 pub mod client_repository;
+#[cfg(feature = "git-snapshots")]
+pub mod git_snapshot_storage;
+#[cfg(not(feature = "sqlite"))]
+pub mod json_history;
+pub mod json_snapshot_storage;
 mod json_store;
 pub mod prompt_repository;
 pub mod snapshot_repository;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 
+pub use app_data::{ensure_app_dir, load_store};
+pub use backup::BackupManager;
+pub use blob_store::BlobStore;
 pub use json_store::{AppConfig, AppSettings, JsonStore};
-pub use snapshot_repository::SnapshotRepository;
+pub use snapshot_repository::{SnapshotRepository, SnapshotStorage};
+
+/// 当前启用的项目历史存储
+#[cfg(not(feature = "sqlite"))]
+pub use json_history as history;
+#[cfg(feature = "sqlite")]
+pub use sqlite::history;
+
+/// 当前启用的提示词仓库：默认为 JSON 文件，启用 `sqlite` feature 时为 SQLite
+#[cfg(not(feature = "sqlite"))]
+pub type PromptStore = prompt_repository::PromptRepository;
+#[cfg(feature = "sqlite")]
+pub type PromptStore = sqlite::SqlitePromptRepository;
+
+/// 当前启用的客户端仓库
+#[cfg(not(feature = "sqlite"))]
+pub type ClientStore = client_repository::ClientRepository;
+#[cfg(feature = "sqlite")]
+pub type ClientStore = sqlite::SqliteClientRepository;
+
+/// 当前启用的模板与项目记录存储
+#[cfg(not(feature = "sqlite"))]
+pub type AppStore = JsonStore;
+#[cfg(feature = "sqlite")]
+pub type AppStore = sqlite::SqliteAppStore;
@@ -1,39 +1,46 @@
-use crate::commands::prompt::ImportResult;
-use crate::models::Prompt;
+use crate::error::{VaultError, VaultResult};
+use crate::models::{ImportResult, Prompt, PromptRevision};
 use crate::utils::file_ops::atomic_write;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 const PROMPTS_FILE_NAME: &str = "prompts.json";
+const REVISIONS_DIR_NAME: &str = "prompt_revisions";
 
 pub struct PromptRepository {
     path: PathBuf,
+    revisions_dir: PathBuf,
     prompts: HashMap<String, Prompt>,
 }
 
 impl PromptRepository {
-    pub fn new(data_dir: PathBuf) -> Result<Self, String> {
-        fs::create_dir_all(&data_dir).map_err(|e| format!("创建数据目录失败: {}", e))?;
+    pub fn new(data_dir: PathBuf) -> VaultResult<Self> {
+        fs::create_dir_all(&data_dir).map_err(|e| VaultError::create_dir_failed(&data_dir, e))?;
         let path = data_dir.join(PROMPTS_FILE_NAME);
+        let revisions_dir = data_dir.join(REVISIONS_DIR_NAME);
         let prompts = if path.exists() {
             Self::load_prompts(&path)?
         } else {
             HashMap::new()
         };
 
-        Ok(Self { path, prompts })
+        Ok(Self {
+            path,
+            revisions_dir,
+            prompts,
+        })
     }
 
-    pub fn get_all(&self) -> Result<Vec<Prompt>, String> {
+    pub fn get_all(&self) -> VaultResult<Vec<Prompt>> {
         Ok(self.prompts.values().cloned().collect())
     }
 
-    pub fn get_by_id(&self, id: &str) -> Result<Option<Prompt>, String> {
+    pub fn get_by_id(&self, id: &str) -> VaultResult<Option<Prompt>> {
         Ok(self.prompts.get(id).cloned())
     }
 
-    pub fn get_by_tags(&self, tags: &[String]) -> Result<Vec<Prompt>, String> {
+    pub fn get_by_tags(&self, tags: &[String]) -> VaultResult<Vec<Prompt>> {
         if tags.is_empty() {
             return Ok(Vec::new());
         }
@@ -45,20 +52,50 @@ impl PromptRepository {
             .collect())
     }
 
-    pub fn save(&mut self, prompt: Prompt) -> Result<(), String> {
+    pub fn save(&mut self, prompt: Prompt) -> VaultResult<()> {
         self.prompts.insert(prompt.id.clone(), prompt);
         self.persist()
     }
 
-    pub fn delete(&mut self, id: &str) -> Result<bool, String> {
+    pub fn delete(&mut self, id: &str) -> VaultResult<bool> {
         let removed = self.prompts.remove(id).is_some();
         if removed {
             self.persist()?;
+            let path = self.revisions_path(id);
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| VaultError::remove_failed(&path, e))?;
+            }
         }
         Ok(removed)
     }
 
-    pub fn import_prompts(&mut self, prompts: Vec<Prompt>) -> Result<ImportResult, String> {
+    /// 按时间从旧到新返回提示词的历史版本
+    pub fn get_revisions(&self, prompt_id: &str) -> VaultResult<Vec<PromptRevision>> {
+        let path = self.revisions_path(prompt_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| VaultError::read_failed(&path, e))?;
+        serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(&path, e))
+    }
+
+    /// 追加版本并只保留最近 `limit` 个；与最新版本内容相同时跳过，返回是否写入
+    pub fn add_revision(&mut self, revision: PromptRevision, limit: usize) -> VaultResult<bool> {
+        let mut revisions = self.get_revisions(&revision.prompt_id)?;
+        if revisions.last().map(|last| last.hash == revision.hash) == Some(true) {
+            return Ok(false);
+        }
+        let path = self.revisions_path(&revision.prompt_id);
+        revisions.push(revision);
+        let excess = revisions.len().saturating_sub(limit);
+        revisions.drain(..excess);
+        let content = serde_json::to_string_pretty(&revisions)
+            .map_err(|e| VaultError::serialize_failed("prompt_revisions", e))?;
+        atomic_write(&path, &content)?;
+        Ok(true)
+    }
+
+    pub fn import_prompts(&mut self, prompts: Vec<Prompt>) -> VaultResult<ImportResult> {
         if prompts.is_empty() {
             return Ok(ImportResult {
                 total: 0,
@@ -81,7 +118,7 @@ impl PromptRepository {
 
         let merged_list: Vec<Prompt> = merged.values().cloned().collect();
         let content = serde_json::to_string_pretty(&merged_list)
-            .map_err(|e| format!("序列化提示词失败: {}", e))?;
+            .map_err(|e| VaultError::serialize_failed("prompts", e))?;
         atomic_write(&self.path, &content)?;
         self.prompts = merged;
         Ok(ImportResult {
@@ -91,17 +128,21 @@ impl PromptRepository {
         })
     }
 
-    fn load_prompts(path: &Path) -> Result<HashMap<String, Prompt>, String> {
-        let raw = fs::read_to_string(path).map_err(|e| format!("读取提示词失败: {}", e))?;
+    fn load_prompts(path: &Path) -> VaultResult<HashMap<String, Prompt>> {
+        let raw = fs::read_to_string(path).map_err(|e| VaultError::read_failed(path, e))?;
         let prompts: Vec<Prompt> =
-            serde_json::from_str(&raw).map_err(|e| format!("解析提示词失败: {}", e))?;
+            serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(path, e))?;
         Ok(prompts.into_iter().map(|p| (p.id.clone(), p)).collect())
     }
 
-    fn persist(&self) -> Result<(), String> {
+    fn revisions_path(&self, prompt_id: &str) -> PathBuf {
+        self.revisions_dir.join(format!("{}.json", prompt_id))
+    }
+
+    fn persist(&self) -> VaultResult<()> {
         let prompts: Vec<Prompt> = self.prompts.values().cloned().collect();
         let content = serde_json::to_string_pretty(&prompts)
-            .map_err(|e| format!("序列化提示词失败: {}", e))?;
+            .map_err(|e| VaultError::serialize_failed("prompts", e))?;
         atomic_write(&self.path, &content)
     }
 }
@@ -1,21 +1,47 @@
-use crate::models::{Snapshot, SnapshotConfig};
-use crate::utils::file_ops::atomic_write;
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::models::{RetentionPolicy, Snapshot, SnapshotConfig, SnapshotImportResult};
+use chrono::{DateTime, Duration, Utc};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::PathBuf;
 
-const SNAPSHOT_DIR_NAME: &str = "snapshots";
+/// 快照的持久化后端，快照规则（去重、数量上限等）统一由 [`SnapshotRepository`] 处理
+pub trait SnapshotStorage: Send {
+    /// 读取客户端的快照配置（只含元数据），不存在时返回 `None`
+    fn load_config(&self, client_id: &str) -> VaultResult<Option<SnapshotConfig>>;
+
+    fn save_config(&self, config: &SnapshotConfig) -> VaultResult<()>;
+
+    /// 将快照正文写入 blob，快照本身只保留哈希；若随后未能保存包含该快照的配置，
+    /// 须对它调用 `release` 归还占用的 blob
+    fn store_bodies(&self, snapshot: &mut Snapshot) -> VaultResult<()>;
+
+    /// 从 blob 中加载快照正文
+    fn load_bodies(&self, snapshot: &mut Snapshot) -> VaultResult<()>;
+
+    /// 释放已从配置中移除的快照所引用的 blob，须在 `save_config` 之后调用
+    fn release(&self, snapshots: &[Snapshot]) -> VaultResult<()>;
+}
 
 pub struct SnapshotRepository {
-    base_dir: PathBuf,
+    storage: Box<dyn SnapshotStorage>,
 }
 
 impl SnapshotRepository {
-    pub fn new(app_data_dir: PathBuf) -> Result<Self, String> {
-        let base_dir = app_data_dir.join(SNAPSHOT_DIR_NAME);
-        fs::create_dir_all(&base_dir).map_err(|e| format!("创建快照目录失败: {}", e))?;
-        Ok(Self { base_dir })
+    /// 按启用的 cargo feature 选择存储后端：默认为 JSON 文件，`sqlite` 时为 SQLite 数据库，
+    /// `git-snapshots` 时为 Git 仓库（优先于 `sqlite`）
+    pub fn new(app_data_dir: PathBuf) -> VaultResult<Self> {
+        #[cfg(feature = "git-snapshots")]
+        let storage = super::git_snapshot_storage::GitSnapshotStorage::open(app_data_dir)?;
+        #[cfg(all(feature = "sqlite", not(feature = "git-snapshots")))]
+        let storage = super::sqlite::SqliteSnapshotStorage::open(&app_data_dir)?;
+        #[cfg(not(any(feature = "sqlite", feature = "git-snapshots")))]
+        let storage = super::json_snapshot_storage::JsonSnapshotStorage::open(app_data_dir)?;
+        Ok(Self::with_storage(Box::new(storage)))
+    }
+
+    pub fn with_storage(storage: Box<dyn SnapshotStorage>) -> Self {
+        Self { storage }
     }
 
     pub fn create_snapshot(
@@ -24,12 +50,13 @@ impl SnapshotRepository {
         name: String,
         content: String,
         multi_file_contents: Option<HashMap<String, String>>,
+        path_patterns: Vec<String>,
         is_auto: bool,
-    ) -> Result<Snapshot, String> {
+    ) -> VaultResult<Snapshot> {
         let client_id = Self::normalize_client_id(client_id)?;
         let normalized_name = name.trim();
         if normalized_name.is_empty() {
-            return Err("快照名称不能为空".to_string());
+            return Err(ErrorCode::SnapshotNameEmpty.into());
         }
         let mut config = self.load_config(&client_id)?;
         let content_hash = if let Some(ref contents) = multi_file_contents {
@@ -44,11 +71,12 @@ impl SnapshotRepository {
                 .max_by(|a, b| a.created_at.cmp(&b.created_at))
             {
                 if latest.content_hash == content_hash {
-                    return Err("内容未变化,跳过快照创建".to_string());
+                    return Err(VaultError::new(ErrorCode::SnapshotUnchanged)
+                        .with_param("client_id", &client_id));
                 }
             }
         }
-        let snapshot = Snapshot::new(
+        let mut snapshot = Snapshot::new(
             client_id.clone(),
             normalized_name.to_string(),
             content,
@@ -56,13 +84,16 @@ impl SnapshotRepository {
             content_hash,
             multi_file_contents,
         );
+        snapshot.path_patterns = path_patterns;
+        self.storage.store_bodies(&mut snapshot)?;
         config.snapshots.push(snapshot.clone());
-        Self::enforce_limit(&mut config);
-        self.save_config(&config)?;
+        let pruned = Self::enforce_limit(&mut config, Utc::now());
+        self.save_with_added(&config, std::slice::from_ref(&snapshot))?;
+        self.storage.release(&pruned)?;
         Ok(snapshot)
     }
 
-    pub fn get_snapshots(&self, client_id: &str) -> Result<Vec<Snapshot>, String> {
+    pub fn get_snapshots(&self, client_id: &str) -> VaultResult<Vec<Snapshot>> {
         let client_id = Self::normalize_client_id(client_id)?;
         let config = self.load_config(&client_id)?;
         let mut snapshots = config.snapshots;
@@ -70,7 +101,7 @@ impl SnapshotRepository {
         Ok(snapshots)
     }
 
-    pub fn get_config(&self, client_id: &str) -> Result<SnapshotConfig, String> {
+    pub fn get_config(&self, client_id: &str) -> VaultResult<SnapshotConfig> {
         let client_id = Self::normalize_client_id(client_id)?;
         let mut config = self.load_config(&client_id)?;
         config
@@ -79,28 +110,139 @@ impl SnapshotRepository {
         Ok(config)
     }
 
-    pub fn restore_snapshot(&self, client_id: &str, snapshot_id: &str) -> Result<Snapshot, String> {
+    /// 读取单个快照并从 blob 中加载全部正文
+    pub fn load_snapshot(&self, client_id: &str, snapshot_id: &str) -> VaultResult<Snapshot> {
         let client_id = Self::normalize_client_id(client_id)?;
         let snapshot_id = Self::normalize_snapshot_id(snapshot_id)?;
         let config = self.load_config(&client_id)?;
-        let snapshot = config
+        let mut snapshot = config
             .snapshots
-            .iter()
+            .into_iter()
             .find(|s| s.id == snapshot_id)
-            .ok_or_else(|| "未找到指定快照".to_string())?;
-        Ok(snapshot.clone())
+            .ok_or_else(|| VaultError::snapshot_not_found(&client_id, &snapshot_id))?;
+        self.storage.load_bodies(&mut snapshot)?;
+        Ok(snapshot)
     }
 
-    pub fn delete_snapshot(&self, client_id: &str, snapshot_id: &str) -> Result<(), String> {
+    /// 读取客户端的快照配置并为快照加载正文，用于导出
+    ///
+    /// `snapshot_ids` 为 `None` 时导出全部快照。导出的快照只保留内联正文，不含本机 blob 哈希。
+    pub fn export_snapshots(
+        &self,
+        client_id: &str,
+        snapshot_ids: Option<&[String]>,
+    ) -> VaultResult<SnapshotConfig> {
         let client_id = Self::normalize_client_id(client_id)?;
-        let snapshot_id = Self::normalize_snapshot_id(snapshot_id)?;
         let mut config = self.load_config(&client_id)?;
-        let original_len = config.snapshots.len();
-        config.snapshots.retain(|s| s.id != snapshot_id);
-        if config.snapshots.len() == original_len {
-            return Err("未找到指定快照".to_string());
+        if let Some(ids) = snapshot_ids {
+            for id in ids {
+                if !config.snapshots.iter().any(|s| &s.id == id) {
+                    return Err(VaultError::snapshot_not_found(&client_id, id));
+                }
+            }
+            config.snapshots.retain(|s| ids.contains(&s.id));
+        }
+        config.snapshots.sort_by_key(|s| s.created_at);
+        for snapshot in &mut config.snapshots {
+            self.storage.load_bodies(snapshot)?;
+            snapshot.files.clear();
+            snapshot.content_blob = None;
         }
-        self.save_config(&config)
+        Ok(config)
+    }
+
+    /// 把带内联正文的快照合并进客户端的历史
+    ///
+    /// 已有相同 ID 或相同内容哈希的快照被跳过；目标客户端尚无快照时沿用 `settings` 中的
+    /// 数量上限与保留策略。导入后按常规规则裁剪。
+    pub fn import_snapshots(
+        &self,
+        client_id: &str,
+        settings: Option<&SnapshotConfig>,
+        snapshots: Vec<Snapshot>,
+    ) -> VaultResult<SnapshotImportResult> {
+        let client_id = Self::normalize_client_id(client_id)?;
+        let mut incoming = Vec::with_capacity(snapshots.len());
+        for mut snapshot in snapshots {
+            let content_hash = match snapshot.multi_file_contents {
+                Some(ref contents) => Self::calculate_multi_content_hash(contents),
+                None => Self::calculate_content_hash(&snapshot.content),
+            };
+            if !snapshot.content_hash.is_empty() && snapshot.content_hash != content_hash {
+                return Err(VaultError::new(ErrorCode::SnapshotArchiveCorrupted)
+                    .with_param("snapshot_id", &snapshot.id));
+            }
+            snapshot.content_hash = content_hash;
+            snapshot.client_id = client_id.clone();
+            snapshot.files.clear();
+            snapshot.content_blob = None;
+            incoming.push(snapshot);
+        }
+
+        let mut config = self.load_config(&client_id)?;
+        let mut result = SnapshotImportResult {
+            client_id: client_id.clone(),
+            total: incoming.len(),
+            imported: 0,
+            skipped_existing: 0,
+            skipped_duplicate: 0,
+            pruned: 0,
+        };
+        if config.snapshots.is_empty() {
+            if let Some(settings) = settings {
+                config.max_snapshots = settings.max_snapshots;
+                config.max_auto_snapshots = settings.max_auto_snapshots;
+                config.max_manual_snapshots = settings.max_manual_snapshots;
+                config.retention = settings.retention.clone();
+                Self::normalize_limits(&mut config);
+            }
+        }
+        let mut ids: HashSet<String> = config.snapshots.iter().map(|s| s.id.clone()).collect();
+        let mut hashes: HashSet<String> = config
+            .snapshots
+            .iter()
+            .map(|s| s.content_hash.clone())
+            .collect();
+        let mut added = Vec::new();
+        for mut snapshot in incoming {
+            if ids.contains(&snapshot.id) {
+                result.skipped_existing += 1;
+            } else if hashes.contains(&snapshot.content_hash) {
+                result.skipped_duplicate += 1;
+            } else {
+                if let Err(err) = self.storage.store_bodies(&mut snapshot) {
+                    self.release_unsaved(&added);
+                    return Err(err);
+                }
+                ids.insert(snapshot.id.clone());
+                hashes.insert(snapshot.content_hash.clone());
+                added.push(snapshot.clone());
+                config.snapshots.push(snapshot);
+                result.imported += 1;
+            }
+        }
+        if result.imported == 0 {
+            return Ok(result);
+        }
+        let pruned = Self::enforce_limit(&mut config, Utc::now());
+        result.pruned = pruned.len();
+        self.save_with_added(&config, &added)?;
+        self.storage.release(&pruned)?;
+        Ok(result)
+    }
+
+    pub fn delete_snapshot(&self, client_id: &str, snapshot_id: &str) -> VaultResult<()> {
+        let client_id = Self::normalize_client_id(client_id)?;
+        let snapshot_id = Self::normalize_snapshot_id(snapshot_id)?;
+        let mut config = self.load_config(&client_id)?;
+        let position = config
+            .snapshots
+            .iter()
+            .position(|s| s.id == snapshot_id)
+            .ok_or_else(|| VaultError::snapshot_not_found(&client_id, &snapshot_id))?;
+        let removed = config.snapshots.remove(position);
+        self.storage.save_config(&config)?;
+        self.storage.release(&[removed])
     }
 
     pub fn rename_snapshot(
@@ -108,29 +250,71 @@ impl SnapshotRepository {
         client_id: &str,
         snapshot_id: &str,
         new_name: String,
-    ) -> Result<(), String> {
+    ) -> VaultResult<()> {
         let client_id = Self::normalize_client_id(client_id)?;
         let snapshot_id = Self::normalize_snapshot_id(snapshot_id)?;
         let normalized_name = new_name.trim();
         if normalized_name.is_empty() {
-            return Err("快照名称不能为空".to_string());
+            return Err(ErrorCode::SnapshotNameEmpty.into());
         }
         let mut config = self.load_config(&client_id)?;
         let snapshot = config
             .snapshots
             .iter_mut()
             .find(|s| s.id == snapshot_id)
-            .ok_or_else(|| "未找到指定快照".to_string())?;
+            .ok_or_else(|| VaultError::snapshot_not_found(&client_id, &snapshot_id))?;
         if snapshot.name != normalized_name {
             snapshot.name = normalized_name.to_string();
-            self.save_config(&config)?;
+            self.storage.save_config(&config)?;
         }
         Ok(())
     }
 
-    pub fn set_max_snapshots(&self, client_id: &str, max: usize) -> Result<(), String> {
+    /// 固定或取消固定快照；取消固定后快照重新计入上限，在下次裁剪时按常规规则处理
+    pub fn set_snapshot_pinned(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        pinned: bool,
+    ) -> VaultResult<Snapshot> {
+        self.update_snapshot(client_id, snapshot_id, |snapshot| {
+            std::mem::replace(&mut snapshot.pinned, pinned) != pinned
+        })
+    }
+
+    pub fn set_snapshot_notes(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        notes: String,
+    ) -> VaultResult<Snapshot> {
+        let notes = notes.trim_end().to_string();
+        self.update_snapshot(client_id, snapshot_id, |snapshot| {
+            std::mem::replace(&mut snapshot.notes, notes.clone()) != notes
+        })
+    }
+
+    /// 整体替换快照标签；标签去除首尾空白，空标签被忽略
+    pub fn set_snapshot_labels(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        labels: Vec<String>,
+    ) -> VaultResult<Snapshot> {
+        let labels: BTreeSet<String> = labels
+            .iter()
+            .map(|label| label.trim())
+            .filter(|label| !label.is_empty())
+            .map(str::to_string)
+            .collect();
+        self.update_snapshot(client_id, snapshot_id, |snapshot| {
+            std::mem::replace(&mut snapshot.labels, labels.clone()) != labels
+        })
+    }
+
+    pub fn set_max_snapshots(&self, client_id: &str, max: usize) -> VaultResult<()> {
         if max == 0 {
-            return Err("最大快照数量必须大于 0".to_string());
+            return Err(ErrorCode::SnapshotInvalidLimit.into());
         }
         let client_id = Self::normalize_client_id(client_id)?;
         let mut config = self.load_config(&client_id)?;
@@ -138,121 +322,242 @@ impl SnapshotRepository {
         config.max_auto_snapshots = max;
         config.max_manual_snapshots = max;
         Self::sync_legacy_limit(&mut config);
-        let _ = Self::enforce_limit(&mut config);
-        self.save_config(&config)
+        let pruned = Self::enforce_limit(&mut config, Utc::now());
+        self.storage.save_config(&config)?;
+        self.storage.release(&pruned)
     }
 
-    pub fn set_max_auto_snapshots(&self, client_id: &str, max: usize) -> Result<(), String> {
+    pub fn set_max_auto_snapshots(&self, client_id: &str, max: usize) -> VaultResult<()> {
         if max == 0 {
-            return Err("最大快照数量必须大于 0".to_string());
+            return Err(ErrorCode::SnapshotInvalidLimit.into());
         }
         let client_id = Self::normalize_client_id(client_id)?;
         let mut config = self.load_config(&client_id)?;
         config.max_auto_snapshots = max;
         Self::sync_legacy_limit(&mut config);
-        let _ = Self::enforce_limit(&mut config);
-        self.save_config(&config)
+        let pruned = Self::enforce_limit(&mut config, Utc::now());
+        self.storage.save_config(&config)?;
+        self.storage.release(&pruned)
     }
 
-    pub fn set_max_manual_snapshots(&self, client_id: &str, max: usize) -> Result<(), String> {
+    pub fn set_max_manual_snapshots(&self, client_id: &str, max: usize) -> VaultResult<()> {
         if max == 0 {
-            return Err("最大快照数量必须大于 0".to_string());
+            return Err(ErrorCode::SnapshotInvalidLimit.into());
         }
         let client_id = Self::normalize_client_id(client_id)?;
         let mut config = self.load_config(&client_id)?;
         config.max_manual_snapshots = max;
         Self::sync_legacy_limit(&mut config);
-        let _ = Self::enforce_limit(&mut config);
-        self.save_config(&config)
+        let pruned = Self::enforce_limit(&mut config, Utc::now());
+        self.storage.save_config(&config)?;
+        self.storage.release(&pruned)
+    }
+
+    /// 设置自动快照的保留策略，`None` 时恢复按数量上限裁剪
+    pub fn set_retention_policy(
+        &self,
+        client_id: &str,
+        policy: Option<RetentionPolicy>,
+    ) -> VaultResult<()> {
+        if policy.as_ref().is_some_and(|policy| !policy.is_valid()) {
+            return Err(ErrorCode::SnapshotInvalidRetention.into());
+        }
+        let client_id = Self::normalize_client_id(client_id)?;
+        let mut config = self.load_config(&client_id)?;
+        config.retention = policy;
+        let pruned = Self::enforce_limit(&mut config, Utc::now());
+        self.storage.save_config(&config)?;
+        self.storage.release(&pruned)
     }
 
-    pub fn cleanup_old_snapshots(&self, client_id: &str) -> Result<(), String> {
+    pub fn cleanup_old_snapshots(&self, client_id: &str) -> VaultResult<()> {
         let client_id = Self::normalize_client_id(client_id)?;
         let mut config = self.load_config(&client_id)?;
-        if Self::enforce_limit(&mut config) {
-            self.save_config(&config)?;
+        let pruned = Self::enforce_limit(&mut config, Utc::now());
+        if !pruned.is_empty() {
+            self.storage.save_config(&config)?;
+            self.storage.release(&pruned)?;
         }
         Ok(())
     }
 
-    fn load_config(&self, client_id: &str) -> Result<SnapshotConfig, String> {
-        let path = self.snapshot_file_path(client_id);
-        if path.exists() {
-            let raw = fs::read_to_string(&path).map_err(|e| format!("读取快照配置失败: {}", e))?;
-            let mut config: SnapshotConfig =
-                serde_json::from_str(&raw).map_err(|e| format!("解析快照配置失败: {}", e))?;
-            if config.client_id.trim().is_empty() {
-                config.client_id = client_id.to_string();
-            }
-            Self::normalize_limits(&mut config);
-            Ok(config)
-        } else {
-            Ok(SnapshotConfig::new(client_id.to_string()))
+    /// 修改单个快照的元数据，`update` 返回是否有变化，无变化时不写盘
+    fn update_snapshot(
+        &self,
+        client_id: &str,
+        snapshot_id: &str,
+        update: impl FnOnce(&mut Snapshot) -> bool,
+    ) -> VaultResult<Snapshot> {
+        let client_id = Self::normalize_client_id(client_id)?;
+        let snapshot_id = Self::normalize_snapshot_id(snapshot_id)?;
+        let mut config = self.load_config(&client_id)?;
+        let snapshot = config
+            .snapshots
+            .iter_mut()
+            .find(|s| s.id == snapshot_id)
+            .ok_or_else(|| VaultError::snapshot_not_found(&client_id, &snapshot_id))?;
+        let changed = update(snapshot);
+        let updated = snapshot.clone();
+        if changed {
+            self.storage.save_config(&config)?;
         }
+        Ok(updated)
     }
 
-    fn save_config(&self, config: &SnapshotConfig) -> Result<(), String> {
-        if config.client_id.trim().is_empty() {
-            return Err("快照配置缺少客户端 ID".to_string());
+    /// 保存加入了新快照的配置，失败时归还新快照占用的 blob
+    fn save_with_added(&self, config: &SnapshotConfig, added: &[Snapshot]) -> VaultResult<()> {
+        self.storage
+            .save_config(config)
+            .inspect_err(|_| self.release_unsaved(added))
+    }
+
+    /// 归还未能保存的快照占用的 blob；失败只记录日志，保留导致回滚的原始错误
+    fn release_unsaved(&self, snapshots: &[Snapshot]) {
+        if let Err(err) = self.storage.release(snapshots) {
+            eprintln!("[Storage] 释放未保存快照的 blob 失败: {}", err);
         }
-        let path = self.snapshot_file_path(&config.client_id);
-        let content = serde_json::to_string_pretty(config)
-            .map_err(|e| format!("序列化快照配置失败: {}", e))?;
-        atomic_write(&path, &content)
     }
 
-    fn snapshot_file_path(&self, client_id: &str) -> PathBuf {
-        self.base_dir.join(format!("{}.json", client_id))
+    fn load_config(&self, client_id: &str) -> VaultResult<SnapshotConfig> {
+        let mut config = match self.storage.load_config(client_id)? {
+            Some(config) => config,
+            None => return Ok(SnapshotConfig::new(client_id.to_string())),
+        };
+        if config.client_id.trim().is_empty() {
+            config.client_id = client_id.to_string();
+        }
+        Self::normalize_limits(&mut config);
+        Ok(config)
     }
 
-    fn normalize_client_id(client_id: &str) -> Result<String, String> {
+    fn normalize_client_id(client_id: &str) -> VaultResult<String> {
         let trimmed = client_id.trim();
         if trimmed.is_empty() {
-            Err("客户端 ID 不能为空".to_string())
+            Err(ErrorCode::ClientIdEmpty.into())
         } else {
             Ok(trimmed.to_string())
         }
     }
 
-    fn normalize_snapshot_id(snapshot_id: &str) -> Result<String, String> {
+    fn normalize_snapshot_id(snapshot_id: &str) -> VaultResult<String> {
         let trimmed = snapshot_id.trim();
         if trimmed.is_empty() {
-            Err("快照 ID 不能为空".to_string())
+            Err(ErrorCode::SnapshotIdEmpty.into())
         } else {
             Ok(trimmed.to_string())
         }
     }
 
-    fn enforce_limit(config: &mut SnapshotConfig) -> bool {
+    /// 按保留策略与数量上限裁剪快照，返回被移除的快照以便释放其 blob 引用
+    ///
+    /// 设置了保留策略时自动快照按时间分档保留，不再受 `max_auto_snapshots` 约束；
+    /// 策略的 `include_manual` 为真时手动快照同样分档保留，否则按数量上限裁剪。
+    /// 固定的快照从不删除，也不计入上限。
+    /// 时间分档以 `now` 为基准。
+    fn enforce_limit(config: &mut SnapshotConfig, now: DateTime<Utc>) -> Vec<Snapshot> {
         if config.snapshots.is_empty() {
-            return false;
+            return Vec::new();
         }
         Self::normalize_limits(config);
         config
             .snapshots
             .sort_by(|a, b| a.created_at.cmp(&b.created_at));
-        let auto_count = config.snapshots.iter().filter(|s| s.is_auto).count();
-        let manual_count = config.snapshots.iter().filter(|s| !s.is_auto).count();
-        let mut auto_to_remove = auto_count.saturating_sub(config.max_auto_snapshots);
-        let mut manual_to_remove = manual_count.saturating_sub(config.max_manual_snapshots);
-        if auto_to_remove == 0 && manual_to_remove == 0 {
-            return false;
-        }
-        let mut changed = false;
-        config.snapshots.retain(|snapshot| {
-            if snapshot.is_auto && auto_to_remove > 0 {
+        let expired = match config.retention {
+            Some(ref policy) => Self::expired_by_retention(&config.snapshots, policy, now),
+            None => HashSet::new(),
+        };
+        let unpinned = || config.snapshots.iter().filter(|s| !s.pinned);
+        let auto_count = unpinned().filter(|s| s.is_auto).count();
+        let manual_count = unpinned().filter(|s| !s.is_auto).count();
+        let mut auto_to_remove = if config.retention.is_some() {
+            0
+        } else {
+            auto_count.saturating_sub(config.max_auto_snapshots)
+        };
+        let mut manual_to_remove = if config
+            .retention
+            .as_ref()
+            .is_some_and(|policy| policy.include_manual)
+        {
+            0
+        } else {
+            manual_count.saturating_sub(config.max_manual_snapshots)
+        };
+        if expired.is_empty() && auto_to_remove == 0 && manual_to_remove == 0 {
+            return Vec::new();
+        }
+        let mut removed = Vec::new();
+        for snapshot in std::mem::take(&mut config.snapshots) {
+            if snapshot.pinned {
+                config.snapshots.push(snapshot);
+            } else if expired.contains(&snapshot.id) {
+                removed.push(snapshot);
+            } else if snapshot.is_auto && auto_to_remove > 0 {
                 auto_to_remove -= 1;
-                changed = true;
-                false
+                removed.push(snapshot);
             } else if !snapshot.is_auto && manual_to_remove > 0 {
                 manual_to_remove -= 1;
-                changed = true;
-                false
+                removed.push(snapshot);
             } else {
-                true
+                config.snapshots.push(snapshot);
             }
-        });
-        changed
+        }
+        removed
+    }
+
+    /// 找出保留策略下应删除的快照；`snapshots` 须按创建时间升序排列
+    ///
+    /// 自动与手动快照各自分档，手动快照不会因同一时间档内较新的自动快照而被删除。
+    fn expired_by_retention(
+        snapshots: &[Snapshot],
+        policy: &RetentionPolicy,
+        now: DateTime<Utc>,
+    ) -> HashSet<String> {
+        let mut kept_buckets = HashSet::new();
+        let mut expired = HashSet::new();
+        let covered = snapshots
+            .iter()
+            .rev()
+            .filter(|s| !s.pinned && (s.is_auto || policy.include_manual));
+        for snapshot in covered {
+            let keep = match Self::retention_bucket(policy, snapshot.created_at, now) {
+                Some(RetentionBucket::All) => true,
+                Some(bucket) => kept_buckets.insert((snapshot.is_auto, bucket)),
+                None => false,
+            };
+            if !keep {
+                expired.insert(snapshot.id.clone());
+            }
+        }
+        expired
+    }
+
+    /// 快照按年龄落入的时间档，超出保留期限时返回 `None`
+    fn retention_bucket(
+        policy: &RetentionPolicy,
+        created_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<RetentionBucket> {
+        const HOUR: i64 = 60 * 60;
+        const DAY: i64 = 24 * HOUR;
+        const WEEK: i64 = 7 * DAY;
+
+        let age = now - created_at;
+        let timestamp = created_at.timestamp();
+        if age < Duration::hours(policy.keep_all_hours.into()) {
+            Some(RetentionBucket::All)
+        } else if age < Duration::hours(policy.hourly_hours.into()) {
+            Some(RetentionBucket::Hour(timestamp.div_euclid(HOUR)))
+        } else if age < Duration::days(policy.daily_days.into()) {
+            Some(RetentionBucket::Day(timestamp.div_euclid(DAY)))
+        } else if policy
+            .weekly_weeks
+            .is_none_or(|weeks| age < Duration::weeks(weeks.into()))
+        {
+            Some(RetentionBucket::Week(timestamp.div_euclid(WEEK)))
+        } else {
+            None
+        }
     }
 
     fn calculate_content_hash(content: &str) -> String {
@@ -295,3 +600,255 @@ impl SnapshotRepository {
             .max(config.max_manual_snapshots);
     }
 }
+
+/// 保留策略中的时间档，值为按 UTC 对齐的档序号
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RetentionBucket {
+    All,
+    Hour(i64),
+    Day(i64),
+    Week(i64),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap()
+    }
+
+    fn snapshot(id: &str, created_at: DateTime<Utc>, is_auto: bool, pinned: bool) -> Snapshot {
+        let mut snapshot = Snapshot::new("client", id, "", is_auto, id, None);
+        snapshot.id = id.to_string();
+        snapshot.created_at = created_at;
+        snapshot.pinned = pinned;
+        snapshot
+    }
+
+    fn auto(id: &str, created_at: DateTime<Utc>) -> Snapshot {
+        snapshot(id, created_at, true, false)
+    }
+
+    fn policy(weekly_weeks: Option<u32>) -> RetentionPolicy {
+        RetentionPolicy {
+            keep_all_hours: 1,
+            hourly_hours: 24,
+            daily_days: 30,
+            weekly_weeks,
+            include_manual: false,
+        }
+    }
+
+    fn expired(snapshots: &[Snapshot], policy: &RetentionPolicy) -> Vec<String> {
+        let mut ids: Vec<_> = SnapshotRepository::expired_by_retention(snapshots, policy, now())
+            .into_iter()
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    fn ids(snapshots: &[Snapshot]) -> Vec<&str> {
+        snapshots.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    /// 记录 blob 引用数的存储，保存配置总是失败
+    #[derive(Default)]
+    struct FailingStorage {
+        refs: std::sync::Arc<std::sync::Mutex<HashMap<String, i64>>>,
+    }
+
+    impl SnapshotStorage for FailingStorage {
+        fn load_config(&self, _client_id: &str) -> VaultResult<Option<SnapshotConfig>> {
+            Ok(None)
+        }
+
+        fn save_config(&self, config: &SnapshotConfig) -> VaultResult<()> {
+            Err(VaultError::write_failed(
+                &config.client_id,
+                std::io::Error::other("disk full"),
+            ))
+        }
+
+        fn store_bodies(&self, snapshot: &mut Snapshot) -> VaultResult<()> {
+            for (path, content) in snapshot.multi_file_contents.take().unwrap_or_default() {
+                snapshot.files.insert(path, content);
+            }
+            let mut refs = self.refs.lock().unwrap();
+            for hash in snapshot.blob_refs() {
+                *refs.entry(hash).or_default() += 1;
+            }
+            Ok(())
+        }
+
+        fn load_bodies(&self, _snapshot: &mut Snapshot) -> VaultResult<()> {
+            Ok(())
+        }
+
+        fn release(&self, snapshots: &[Snapshot]) -> VaultResult<()> {
+            let mut refs = self.refs.lock().unwrap();
+            for hash in snapshots.iter().flat_map(Snapshot::blob_refs) {
+                *refs.entry(hash).or_default() -= 1;
+            }
+            Ok(())
+        }
+    }
+
+    fn contents(body: &str) -> Option<HashMap<String, String>> {
+        Some(HashMap::from([("~/a.md".to_string(), body.to_string())]))
+    }
+
+    #[test]
+    fn failed_save_releases_new_snapshot_blobs() {
+        let storage = FailingStorage::default();
+        let refs = std::sync::Arc::clone(&storage.refs);
+        let repo = SnapshotRepository::with_storage(Box::new(storage));
+
+        let created = repo.create_snapshot(
+            "client",
+            "manual".to_string(),
+            String::new(),
+            contents("one"),
+            Vec::new(),
+            false,
+        );
+        assert!(created.is_err());
+
+        let imported = vec![
+            Snapshot::new("other", "first", "", false, "", contents("two")),
+            Snapshot::new("other", "second", "", false, "", contents("three")),
+        ];
+        assert!(repo.import_snapshots("client", None, imported).is_err());
+
+        let refs = refs.lock().unwrap();
+        assert_eq!(refs.len(), 3);
+        assert!(refs.values().all(|count| *count == 0), "{:?}", refs);
+    }
+
+    #[test]
+    fn keep_all_window_is_exclusive() {
+        let snapshots = vec![
+            auto("a", now() - Duration::hours(1)),
+            auto("b", now() - Duration::minutes(50)),
+            auto("c", now() - Duration::minutes(40)),
+        ];
+        assert_eq!(
+            SnapshotRepository::retention_bucket(&policy(None), snapshots[0].created_at, now()),
+            Some(RetentionBucket::Hour(
+                snapshots[0].created_at.timestamp() / 3600
+            ))
+        );
+        assert!(expired(&snapshots, &policy(None)).is_empty());
+    }
+
+    #[test]
+    fn hourly_bucket_keeps_newest_per_clock_hour() {
+        let hour = Utc.with_ymd_and_hms(2024, 1, 10, 9, 0, 0).unwrap();
+        let snapshots = vec![
+            auto("before", hour - Duration::seconds(1)),
+            auto("start", hour),
+            auto("middle", hour + Duration::minutes(30)),
+            auto("end", hour + Duration::minutes(59)),
+        ];
+        assert_eq!(expired(&snapshots, &policy(None)), ["middle", "start"]);
+    }
+
+    #[test]
+    fn daily_and_weekly_buckets() {
+        let day = Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap();
+        let snapshots = vec![
+            auto("old-week", now() - Duration::weeks(8)),
+            auto("day-early", day + Duration::hours(1)),
+            auto("day-late", day + Duration::hours(23)),
+            auto("next-day", day + Duration::days(1)),
+        ];
+        assert_eq!(expired(&snapshots, &policy(None)), ["day-early"]);
+        assert_eq!(
+            expired(&snapshots, &policy(Some(8))),
+            ["day-early", "old-week"]
+        );
+    }
+
+    #[test]
+    fn pinned_snapshots_are_never_expired_nor_occupy_buckets() {
+        let hour = Utc.with_ymd_and_hms(2024, 1, 10, 9, 0, 0).unwrap();
+        let snapshots = vec![
+            auto("older", hour + Duration::minutes(10)),
+            snapshot("pinned", hour + Duration::minutes(20), true, true),
+            auto("newer", hour + Duration::minutes(30)),
+            snapshot("ancient", now() - Duration::weeks(52), true, true),
+        ];
+        assert_eq!(expired(&snapshots, &policy(Some(4))), ["older"]);
+    }
+
+    #[test]
+    fn retention_replaces_auto_count_limit_but_not_manual() {
+        let mut config = SnapshotConfig::new("client");
+        config.max_auto_snapshots = 1;
+        config.max_manual_snapshots = 1;
+        config.retention = Some(policy(None));
+        config.snapshots = vec![
+            snapshot("m1", now() - Duration::minutes(30), false, false),
+            snapshot("m2", now() - Duration::minutes(20), false, false),
+            auto("a1", now() - Duration::minutes(10)),
+            auto("a2", now() - Duration::minutes(5)),
+            auto("a3", now() - Duration::hours(3) - Duration::minutes(1)),
+            auto("a4", now() - Duration::hours(3) - Duration::minutes(2)),
+        ];
+        let removed = SnapshotRepository::enforce_limit(&mut config, now());
+        assert_eq!(ids(&removed), ["a4", "m1"]);
+        assert_eq!(ids(&config.snapshots), ["a3", "m2", "a1", "a2"]);
+    }
+
+    #[test]
+    fn retention_thins_manual_snapshots_when_included() {
+        let mut config = SnapshotConfig::new("client");
+        config.max_manual_snapshots = 1;
+        config.retention = Some(RetentionPolicy {
+            include_manual: true,
+            ..policy(None)
+        });
+        config.snapshots = vec![
+            snapshot("m1", now() - Duration::minutes(30), false, false),
+            snapshot("m2", now() - Duration::minutes(20), false, false),
+            snapshot(
+                "m3",
+                now() - Duration::hours(3) - Duration::minutes(2),
+                false,
+                false,
+            ),
+            snapshot(
+                "m4",
+                now() - Duration::hours(3) - Duration::minutes(1),
+                false,
+                false,
+            ),
+            snapshot(
+                "mp",
+                now() - Duration::hours(3) - Duration::minutes(3),
+                false,
+                true,
+            ),
+            auto("a1", now() - Duration::hours(3) - Duration::minutes(5)),
+        ];
+        let removed = SnapshotRepository::enforce_limit(&mut config, now());
+        assert_eq!(ids(&removed), ["m3"]);
+        assert_eq!(ids(&config.snapshots), ["a1", "mp", "m4", "m1", "m2"]);
+    }
+
+    #[test]
+    fn count_limit_skips_pinned_snapshots() {
+        let mut config = SnapshotConfig::new("client");
+        config.max_auto_snapshots = 2;
+        config.snapshots = vec![
+            snapshot("p", now() - Duration::hours(4), true, true),
+            auto("a1", now() - Duration::hours(3)),
+            auto("a2", now() - Duration::hours(2)),
+            auto("a3", now() - Duration::hours(1)),
+        ];
+        let removed = SnapshotRepository::enforce_limit(&mut config, now());
+        assert_eq!(ids(&removed), ["a1"]);
+        assert_eq!(ids(&config.snapshots), ["p", "a2", "a3"]);
+    }
+}
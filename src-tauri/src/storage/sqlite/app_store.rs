@@ -0,0 +1,152 @@
+use super::{from_json, get_meta, query_json, set_meta, to_json};
+use crate::error::{VaultError, VaultResult};
+use crate::models::{Composition, Project, Template};
+use crate::storage::json_store::app_dir;
+use crate::storage::{AppConfig, AppSettings};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+
+const SETTINGS_KEY: &str = "app_settings";
+const VARIABLES_KEY: &str = "app_variables";
+const UPDATED_AT_KEY: &str = "app_updated_at";
+
+/// 与 [`JsonStore`](crate::storage::JsonStore) 接口一致的 SQLite 实现，保存模板、组合、项目记录与应用设置
+pub struct SqliteAppStore {
+    conn: Connection,
+    path: PathBuf,
+    config: AppConfig,
+}
+
+impl SqliteAppStore {
+    pub fn new() -> VaultResult<Self> {
+        let base_dir = app_dir()?;
+        Ok(Self {
+            conn: super::open(&base_dir)?,
+            path: base_dir.join(super::DATABASE_FILE_NAME),
+            config: AppConfig::default(),
+        })
+    }
+
+    pub fn load(&mut self) -> VaultResult<&AppConfig> {
+        let mut config = AppConfig {
+            templates: query_json(
+                &self.conn,
+                "SELECT data FROM templates ORDER BY position",
+                [],
+                "templates",
+            )?,
+            compositions: query_json(
+                &self.conn,
+                "SELECT data FROM compositions ORDER BY position",
+                [],
+                "compositions",
+            )?,
+            projects: query_json(
+                &self.conn,
+                "SELECT data FROM projects ORDER BY position",
+                [],
+                "projects",
+            )?,
+            ..AppConfig::default()
+        };
+        if let Some(raw) = get_meta(&self.conn, SETTINGS_KEY)? {
+            config.settings = from_json::<AppSettings>(&raw, "meta")?;
+        }
+        if let Some(raw) = get_meta(&self.conn, VARIABLES_KEY)? {
+            config.variables = from_json(&raw, "meta")?;
+        }
+        if let Some(raw) = get_meta(&self.conn, UPDATED_AT_KEY)? {
+            config.updated_at = from_json::<DateTime<Utc>>(&raw, "meta")?;
+        }
+        self.config = config;
+        Ok(&self.config)
+    }
+
+    /// 模板、组合与项目记录数量有限，保存时在同一事务内整体替换
+    pub fn save(&mut self) -> VaultResult<()> {
+        self.config.updated_at = Utc::now();
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(VaultError::database_failed)?;
+        save_config(&tx, &self.config)?;
+        tx.commit().map_err(VaultError::database_failed)
+    }
+
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut AppConfig {
+        &mut self.config
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+}
+
+pub(super) fn save_config(conn: &Connection, config: &AppConfig) -> VaultResult<()> {
+    conn.execute("DELETE FROM templates", [])
+        .map_err(VaultError::database_failed)?;
+    for (position, template) in config.templates.iter().enumerate() {
+        insert_template(conn, position, template)?;
+    }
+    conn.execute("DELETE FROM compositions", [])
+        .map_err(VaultError::database_failed)?;
+    for (position, composition) in config.compositions.iter().enumerate() {
+        insert_composition(conn, position, composition)?;
+    }
+    conn.execute("DELETE FROM projects", [])
+        .map_err(VaultError::database_failed)?;
+    for (position, project) in config.projects.iter().enumerate() {
+        insert_project(conn, position, project)?;
+    }
+    set_meta(conn, SETTINGS_KEY, &to_json(&config.settings, "meta")?)?;
+    set_meta(conn, VARIABLES_KEY, &to_json(&config.variables, "meta")?)?;
+    set_meta(conn, UPDATED_AT_KEY, &to_json(&config.updated_at, "meta")?)
+}
+
+fn insert_template(conn: &Connection, position: usize, template: &Template) -> VaultResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO templates (id, position, data) VALUES (?1, ?2, ?3)",
+        params![
+            template.id,
+            position as i64,
+            to_json(template, "templates")?
+        ],
+    )
+    .map_err(VaultError::database_failed)?;
+    Ok(())
+}
+
+fn insert_composition(
+    conn: &Connection,
+    position: usize,
+    composition: &Composition,
+) -> VaultResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO compositions (id, position, data) VALUES (?1, ?2, ?3)",
+        params![
+            composition.id,
+            position as i64,
+            to_json(composition, "compositions")?
+        ],
+    )
+    .map_err(VaultError::database_failed)?;
+    Ok(())
+}
+
+fn insert_project(conn: &Connection, position: usize, project: &Project) -> VaultResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO projects (path, position, data) VALUES (?1, ?2, ?3)",
+        params![
+            project.path.to_string_lossy(),
+            position as i64,
+            to_json(project, "projects")?
+        ],
+    )
+    .map_err(VaultError::database_failed)?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use super::{from_json, query_json, to_json};
+use crate::client_catalog::ClientCatalog;
+use crate::error::{VaultError, VaultResult};
+use crate::models::ClientConfig;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::PathBuf;
+
+const TABLE: &str = "clients";
+
+/// 与 [`ClientRepository`](crate::storage::client_repository::ClientRepository) 接口一致的 SQLite 实现
+pub struct SqliteClientRepository {
+    conn: Connection,
+}
+
+impl SqliteClientRepository {
+    pub fn new(data_dir: PathBuf) -> VaultResult<Self> {
+        let conn = super::open(&data_dir)?;
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM clients", [], |row| row.get(0))
+            .map_err(VaultError::database_failed)?;
+        if count == 0 {
+            for client in ClientCatalog::load(&data_dir)?.default_clients() {
+                upsert_client(&conn, &client)?;
+            }
+        }
+        let repo = Self { conn };
+        repo.migrate_legacy_default_paths()?;
+        Ok(repo)
+    }
+
+    /// 迁移内置客户端中仍为旧默认值的路径，见 [`ClientConfig::migrate_legacy_default_paths`]
+    fn migrate_legacy_default_paths(&self) -> VaultResult<()> {
+        for mut client in self.get_all()? {
+            if client.migrate_legacy_default_paths() {
+                upsert_client(&self.conn, &client)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_all(&self) -> VaultResult<Vec<ClientConfig>> {
+        query_json(
+            &self.conn,
+            "SELECT data FROM clients ORDER BY position",
+            [],
+            TABLE,
+        )
+    }
+
+    pub fn get_by_id(&self, id: &str) -> VaultResult<Option<ClientConfig>> {
+        let raw: Option<String> = self
+            .conn
+            .query_row("SELECT data FROM clients WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(VaultError::database_failed)?;
+        raw.map(|raw| from_json(&raw, TABLE)).transpose()
+    }
+
+    pub fn save(&mut self, client: ClientConfig) -> VaultResult<()> {
+        upsert_client(&self.conn, &client)
+    }
+
+    pub fn delete(&mut self, id: &str) -> VaultResult<bool> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM clients WHERE id = ?1", [id])
+            .map_err(VaultError::database_failed)?;
+        Ok(removed > 0)
+    }
+}
+
+/// 已存在的客户端保持原有顺序，新客户端追加到末尾
+pub(super) fn upsert_client(conn: &Connection, client: &ClientConfig) -> VaultResult<()> {
+    conn.execute(
+        "INSERT INTO clients (id, position, data)
+         VALUES (?1, (SELECT COALESCE(MAX(position), 0) + 1 FROM clients), ?2)
+         ON CONFLICT (id) DO UPDATE SET data = excluded.data",
+        params![client.id, to_json(client, TABLE)?],
+    )
+    .map_err(VaultError::database_failed)?;
+    Ok(())
+}
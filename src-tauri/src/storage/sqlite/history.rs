@@ -0,0 +1,35 @@
+//! 项目操作历史，按项目路径哈希分组
+
+use super::{query_json, to_json};
+use crate::error::{VaultError, VaultResult};
+use crate::models::HistoryEntry;
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+pub fn read_entries(data_dir: &Path, project_hash: &str) -> VaultResult<Vec<HistoryEntry>> {
+    let conn = super::open(data_dir)?;
+    query_json(
+        &conn,
+        "SELECT data FROM history WHERE project_hash = ?1 ORDER BY id",
+        [project_hash],
+        "history",
+    )
+}
+
+pub fn append_entry(data_dir: &Path, project_hash: &str, entry: &HistoryEntry) -> VaultResult<()> {
+    let conn = super::open(data_dir)?;
+    insert_entry(&conn, project_hash, entry)
+}
+
+pub(super) fn insert_entry(
+    conn: &Connection,
+    project_hash: &str,
+    entry: &HistoryEntry,
+) -> VaultResult<()> {
+    conn.execute(
+        "INSERT INTO history (project_hash, data) VALUES (?1, ?2)",
+        params![project_hash, to_json(entry, "history")?],
+    )
+    .map_err(VaultError::database_failed)?;
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use super::{app_store, clients, get_meta, history, prompts, set_meta, snapshots};
+use crate::error::{VaultError, VaultResult};
+use crate::models::{ClientConfig, HistoryEntry, Prompt, PromptRevision};
+use crate::storage::json_snapshot_storage::JsonSnapshotStorage;
+use crate::storage::{JsonStore, SnapshotStorage};
+use chrono::Utc;
+use rusqlite::{Connection, TransactionBehavior};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MIGRATED_KEY: &str = "json_migrated_at";
+
+/// 一次 JSON -> SQLite 迁移导入的记录数
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    pub prompts: usize,
+    pub prompt_revisions: usize,
+    pub clients: usize,
+    pub snapshots: usize,
+    pub templates: usize,
+    pub compositions: usize,
+    pub projects: usize,
+    pub history_entries: usize,
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "prompts={}, prompt_revisions={}, clients={}, snapshots={}, templates={}, compositions={}, projects={}, history={}",
+            self.prompts,
+            self.prompt_revisions,
+            self.clients,
+            self.snapshots,
+            self.templates,
+            self.compositions,
+            self.projects,
+            self.history_entries
+        )
+    }
+}
+
+/// 把数据目录中的 JSON 数据导入数据库，整个过程在一个事务内完成
+///
+/// 迁移完成后在 `meta` 表记录时间，之后再打开数据库不会重复导入；已迁移过时返回 `None`。
+pub(super) fn migrate_from_json(
+    conn: &mut Connection,
+    data_dir: &Path,
+) -> VaultResult<Option<MigrationReport>> {
+    if get_meta(conn, MIGRATED_KEY)?.is_some() {
+        return Ok(None);
+    }
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(VaultError::database_failed)?;
+    // 其他进程可能已抢先完成迁移，拿到写锁后再确认一次
+    if get_meta(&tx, MIGRATED_KEY)?.is_some() {
+        return Ok(None);
+    }
+
+    let mut report = MigrationReport::default();
+
+    if let Some(entries) = read_json_file::<Vec<Prompt>>(&data_dir.join("prompts.json"))? {
+        for prompt in &entries {
+            prompts::upsert_prompt(&tx, prompt)?;
+        }
+        report.prompts = entries.len();
+    }
+
+    for path in json_files(&data_dir.join("prompt_revisions"))? {
+        let revisions = read_json_file::<Vec<PromptRevision>>(&path)?.unwrap_or_default();
+        for revision in &revisions {
+            prompts::insert_revision(&tx, revision, revisions.len())?;
+        }
+        report.prompt_revisions += revisions.len();
+    }
+
+    if let Some(entries) = read_json_file::<Vec<ClientConfig>>(&data_dir.join("clients.json"))? {
+        for client in &entries {
+            clients::upsert_client(&tx, client)?;
+        }
+        report.clients = entries.len();
+    }
+
+    if data_dir.join("snapshots").is_dir() {
+        let storage = JsonSnapshotStorage::open(data_dir.to_path_buf())?;
+        for client_id in storage.client_ids()? {
+            let Some(mut config) = storage.load_config(&client_id)? else {
+                continue;
+            };
+            if config.client_id.trim().is_empty() {
+                config.client_id = client_id;
+            }
+            for snapshot in config.snapshots.iter_mut() {
+                storage.load_bodies(snapshot)?;
+                snapshots::store_bodies(&tx, snapshot)?;
+            }
+            snapshots::save_config(&tx, &config)?;
+            report.snapshots += config.snapshots.len();
+        }
+    }
+
+    let mut store = JsonStore::new()?;
+    if store.path().exists() {
+        let config = store.load()?;
+        app_store::save_config(&tx, config)?;
+        report.templates = config.templates.len();
+        report.compositions = config.compositions.len();
+        report.projects = config.projects.len();
+    }
+
+    for path in json_files(&data_dir.join("history"))? {
+        let Some(project_hash) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let items = read_json_file::<Vec<HistoryEntry>>(&path)?.unwrap_or_default();
+        for item in &items {
+            history::insert_entry(&tx, project_hash, item)?;
+        }
+        report.history_entries += items.len();
+    }
+
+    set_meta(&tx, MIGRATED_KEY, &Utc::now().to_rfc3339())?;
+    tx.commit().map_err(VaultError::database_failed)?;
+    Ok(Some(report))
+}
+
+/// 目录下的全部 `.json` 文件，目录不存在时为空
+fn json_files(dir: &Path) -> VaultResult<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(dir).map_err(|e| VaultError::read_failed(dir, e))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| VaultError::read_failed(dir, e))?.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+fn read_json_file<T: DeserializeOwned>(path: &Path) -> VaultResult<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(path).map_err(|e| VaultError::read_failed(path, e))?;
+    let value = serde_json::from_str(&raw).map_err(|e| VaultError::parse_failed(path, e))?;
+    Ok(Some(value))
+}
@@ -0,0 +1,171 @@
+//! 可选的 SQLite 存储后端（`sqlite` feature）
+//!
+//! 提示词（含历史版本）、客户端、快照、模板、组合与项目历史集中保存在 `vault.db` 中，每次修改只写入变化的行；
+//! 提示词额外维护 FTS5 全文索引。首次打开数据库时会从现有 JSON 文件一次性迁移数据，
+//! 原 JSON 文件保持不动，可随时切回默认后端。
+
+mod app_store;
+mod clients;
+pub mod history;
+mod migrate;
+mod prompts;
+mod snapshots;
+
+pub use app_store::SqliteAppStore;
+pub use clients::SqliteClientRepository;
+pub use prompts::SqlitePromptRepository;
+pub use snapshots::SqliteSnapshotStorage;
+
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use rusqlite::{Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const DATABASE_FILE_NAME: &str = "vault.db";
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS prompts (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS prompts_fts USING fts5(
+    id UNINDEXED,
+    name,
+    content,
+    tags,
+    tokenize = 'unicode61'
+);
+CREATE TABLE IF NOT EXISTS prompt_revisions (
+    id TEXT PRIMARY KEY,
+    prompt_id TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS prompt_revisions_by_prompt ON prompt_revisions (prompt_id);
+CREATE TABLE IF NOT EXISTS clients (
+    id TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS snapshot_configs (
+    client_id TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS snapshots (
+    id TEXT PRIMARY KEY,
+    client_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS snapshots_by_client ON snapshots (client_id, created_at);
+CREATE TABLE IF NOT EXISTS blobs (
+    hash TEXT PRIMARY KEY,
+    content TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS snapshot_blobs (
+    snapshot_id TEXT NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+    hash TEXT NOT NULL,
+    PRIMARY KEY (snapshot_id, hash)
+);
+CREATE INDEX IF NOT EXISTS snapshot_blobs_by_hash ON snapshot_blobs (hash);
+CREATE TABLE IF NOT EXISTS templates (
+    id TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS compositions (
+    id TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS projects (
+    path TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_hash TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS history_by_project ON history (project_hash, id);
+";
+
+/// 打开（必要时创建）数据目录下的数据库，并在首次打开时迁移旧 JSON 数据
+pub fn open(data_dir: &Path) -> VaultResult<Connection> {
+    fs::create_dir_all(data_dir).map_err(|e| VaultError::create_dir_failed(data_dir, e))?;
+    let mut conn =
+        Connection::open(data_dir.join(DATABASE_FILE_NAME)).map_err(VaultError::database_failed)?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(VaultError::database_failed)?;
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")
+        .map_err(VaultError::database_failed)?;
+    conn.execute_batch(SCHEMA)
+        .map_err(VaultError::database_failed)?;
+    if let Some(report) = migrate::migrate_from_json(&mut conn, data_dir)? {
+        eprintln!("[Storage] 已从 JSON 文件迁移数据: {}", report);
+    }
+    Ok(conn)
+}
+
+fn get_meta(conn: &Connection, key: &str) -> VaultResult<Option<String>> {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .optional()
+    .map_err(VaultError::database_failed)
+}
+
+fn set_meta(conn: &Connection, key: &str, value: &str) -> VaultResult<()> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        [key, value],
+    )
+    .map_err(VaultError::database_failed)?;
+    Ok(())
+}
+
+/// 行数据以 JSON 保存，模型新增字段时无需修改表结构
+fn to_json<T: Serialize>(value: &T, target: &'static str) -> VaultResult<String> {
+    serde_json::to_string(value).map_err(|e| VaultError::serialize_failed(target, e))
+}
+
+fn from_json<T: DeserializeOwned>(raw: &str, table: &'static str) -> VaultResult<T> {
+    serde_json::from_str(raw).map_err(|e| {
+        VaultError::new(ErrorCode::ParseFailed)
+            .with_param("table", table)
+            .with_cause(e)
+    })
+}
+
+/// 执行查询并把每行第一列的 JSON 解析为模型
+fn query_json<T, P>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+    table: &'static str,
+) -> VaultResult<Vec<T>>
+where
+    T: DeserializeOwned,
+    P: rusqlite::Params,
+{
+    let mut stmt = conn.prepare(sql).map_err(VaultError::database_failed)?;
+    let rows = stmt
+        .query_map(params, |row| row.get::<_, String>(0))
+        .map_err(VaultError::database_failed)?;
+    let mut items = Vec::new();
+    for raw in rows {
+        let raw = raw.map_err(VaultError::database_failed)?;
+        items.push(from_json(&raw, table)?);
+    }
+    Ok(items)
+}
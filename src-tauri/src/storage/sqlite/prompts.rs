@@ -0,0 +1,203 @@
+use super::{from_json, query_json, to_json};
+use crate::error::{VaultError, VaultResult};
+use crate::models::{ImportResult, Prompt, PromptRevision};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::PathBuf;
+
+const TABLE: &str = "prompts";
+const REVISIONS_TABLE: &str = "prompt_revisions";
+
+/// 与 [`PromptRepository`](crate::storage::prompt_repository::PromptRepository) 接口一致的 SQLite 实现
+pub struct SqlitePromptRepository {
+    conn: Connection,
+}
+
+impl SqlitePromptRepository {
+    pub fn new(data_dir: PathBuf) -> VaultResult<Self> {
+        Ok(Self {
+            conn: super::open(&data_dir)?,
+        })
+    }
+
+    pub fn get_all(&self) -> VaultResult<Vec<Prompt>> {
+        query_json(&self.conn, "SELECT data FROM prompts", [], TABLE)
+    }
+
+    pub fn get_by_id(&self, id: &str) -> VaultResult<Option<Prompt>> {
+        let raw: Option<String> = self
+            .conn
+            .query_row("SELECT data FROM prompts WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(VaultError::database_failed)?;
+        raw.map(|raw| from_json(&raw, TABLE)).transpose()
+    }
+
+    pub fn get_by_tags(&self, tags: &[String]) -> VaultResult<Vec<Prompt>> {
+        if tags.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .get_all()?
+            .into_iter()
+            .filter(|prompt| prompt.tags.iter().any(|tag| tags.iter().any(|t| t == tag)))
+            .collect())
+    }
+
+    /// 在名称、内容与标签上做 FTS5 全文检索，按 bm25 相关度排序
+    pub fn search(&self, query: &str, limit: usize) -> VaultResult<Vec<Prompt>> {
+        let Some(expression) = fts_expression(query) else {
+            return Ok(Vec::new());
+        };
+        query_json(
+            &self.conn,
+            "SELECT p.data FROM prompts_fts f JOIN prompts p ON p.id = f.id
+             WHERE prompts_fts MATCH ?1 ORDER BY bm25(prompts_fts) LIMIT ?2",
+            params![expression, limit as i64],
+            TABLE,
+        )
+    }
+
+    pub fn save(&mut self, prompt: Prompt) -> VaultResult<()> {
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(VaultError::database_failed)?;
+        upsert_prompt(&tx, &prompt)?;
+        tx.commit().map_err(VaultError::database_failed)
+    }
+
+    pub fn delete(&mut self, id: &str) -> VaultResult<bool> {
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(VaultError::database_failed)?;
+        let removed = tx
+            .execute("DELETE FROM prompts WHERE id = ?1", [id])
+            .map_err(VaultError::database_failed)?;
+        tx.execute("DELETE FROM prompts_fts WHERE id = ?1", [id])
+            .map_err(VaultError::database_failed)?;
+        tx.execute("DELETE FROM prompt_revisions WHERE prompt_id = ?1", [id])
+            .map_err(VaultError::database_failed)?;
+        tx.commit().map_err(VaultError::database_failed)?;
+        Ok(removed > 0)
+    }
+
+    /// 按时间从旧到新返回提示词的历史版本
+    pub fn get_revisions(&self, prompt_id: &str) -> VaultResult<Vec<PromptRevision>> {
+        query_json(
+            &self.conn,
+            "SELECT data FROM prompt_revisions WHERE prompt_id = ?1 ORDER BY rowid",
+            [prompt_id],
+            REVISIONS_TABLE,
+        )
+    }
+
+    /// 追加版本并只保留最近 `limit` 个；与最新版本内容相同时跳过，返回是否写入
+    pub fn add_revision(&mut self, revision: PromptRevision, limit: usize) -> VaultResult<bool> {
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(VaultError::database_failed)?;
+        let inserted = insert_revision(&tx, &revision, limit)?;
+        tx.commit().map_err(VaultError::database_failed)?;
+        Ok(inserted)
+    }
+
+    pub fn import_prompts(&mut self, prompts: Vec<Prompt>) -> VaultResult<ImportResult> {
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(VaultError::database_failed)?;
+        let mut added = 0usize;
+        let mut updated = 0usize;
+        for prompt in &prompts {
+            if upsert_prompt(&tx, prompt)? {
+                updated += 1;
+            } else {
+                added += 1;
+            }
+        }
+        tx.commit().map_err(VaultError::database_failed)?;
+        Ok(ImportResult {
+            total: added + updated,
+            added,
+            updated,
+        })
+    }
+}
+
+/// 写入提示词并同步全文索引，返回该 ID 之前是否已存在
+pub(super) fn upsert_prompt(conn: &Connection, prompt: &Prompt) -> VaultResult<bool> {
+    let existed = conn
+        .execute("DELETE FROM prompts_fts WHERE id = ?1", [&prompt.id])
+        .map_err(VaultError::database_failed)?
+        > 0;
+    conn.execute(
+        "INSERT INTO prompts (id, data) VALUES (?1, ?2)
+         ON CONFLICT (id) DO UPDATE SET data = excluded.data",
+        params![prompt.id, to_json(prompt, TABLE)?],
+    )
+    .map_err(VaultError::database_failed)?;
+    conn.execute(
+        "INSERT INTO prompts_fts (id, name, content, tags) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            prompt.id,
+            prompt.name,
+            prompt.content,
+            prompt.tags.join(" ")
+        ],
+    )
+    .map_err(VaultError::database_failed)?;
+    Ok(existed)
+}
+
+pub(super) fn insert_revision(
+    conn: &Connection,
+    revision: &PromptRevision,
+    limit: usize,
+) -> VaultResult<bool> {
+    let latest: Option<String> = conn
+        .query_row(
+            "SELECT hash FROM prompt_revisions WHERE prompt_id = ?1 ORDER BY rowid DESC LIMIT 1",
+            [&revision.prompt_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(VaultError::database_failed)?;
+    if latest.as_deref() == Some(revision.hash.as_str()) {
+        return Ok(false);
+    }
+    conn.execute(
+        "INSERT INTO prompt_revisions (id, prompt_id, hash, data) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            revision.id,
+            revision.prompt_id,
+            revision.hash,
+            to_json(revision, REVISIONS_TABLE)?
+        ],
+    )
+    .map_err(VaultError::database_failed)?;
+    conn.execute(
+        "DELETE FROM prompt_revisions WHERE prompt_id = ?1 AND rowid NOT IN (
+             SELECT rowid FROM prompt_revisions WHERE prompt_id = ?1 ORDER BY rowid DESC LIMIT ?2
+         )",
+        params![revision.prompt_id, limit as i64],
+    )
+    .map_err(VaultError::database_failed)?;
+    Ok(true)
+}
+
+/// 把用户输入拆成带引号的词组，避免特殊字符被当作 FTS5 语法
+fn fts_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
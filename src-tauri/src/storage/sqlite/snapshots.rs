@@ -0,0 +1,178 @@
+use super::{from_json, query_json, to_json};
+use crate::error::{VaultError, VaultResult};
+use crate::models::{Snapshot, SnapshotConfig};
+use crate::storage::{BlobStore, SnapshotStorage};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const CONFIG_TABLE: &str = "snapshot_configs";
+const TABLE: &str = "snapshots";
+
+/// 快照元数据按行保存，正文存放在 `blobs` 表中并通过 `snapshot_blobs` 记录引用
+pub struct SqliteSnapshotStorage {
+    conn: Connection,
+}
+
+impl SqliteSnapshotStorage {
+    pub fn open(data_dir: &Path) -> VaultResult<Self> {
+        Ok(Self {
+            conn: super::open(data_dir)?,
+        })
+    }
+
+    fn get_blob(&self, hash: &str) -> VaultResult<String> {
+        self.conn
+            .query_row("SELECT content FROM blobs WHERE hash = ?1", [hash], |row| {
+                row.get(0)
+            })
+            .map_err(|e| VaultError::database_failed(e).with_param("hash", hash))
+    }
+}
+
+impl SnapshotStorage for SqliteSnapshotStorage {
+    fn load_config(&self, client_id: &str) -> VaultResult<Option<SnapshotConfig>> {
+        let raw: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT data FROM snapshot_configs WHERE client_id = ?1",
+                [client_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(VaultError::database_failed)?;
+        let snapshots: Vec<Snapshot> = query_json(
+            &self.conn,
+            "SELECT data FROM snapshots WHERE client_id = ?1 ORDER BY created_at",
+            [client_id],
+            TABLE,
+        )?;
+        let mut config = match raw {
+            Some(raw) => from_json::<SnapshotConfig>(&raw, CONFIG_TABLE)?,
+            None if snapshots.is_empty() => return Ok(None),
+            None => SnapshotConfig::new(client_id.to_string()),
+        };
+        config.snapshots = snapshots;
+        Ok(Some(config))
+    }
+
+    fn save_config(&self, config: &SnapshotConfig) -> VaultResult<()> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(VaultError::database_failed)?;
+        save_config(&tx, config)?;
+        tx.commit().map_err(VaultError::database_failed)
+    }
+
+    fn store_bodies(&self, snapshot: &mut Snapshot) -> VaultResult<()> {
+        store_bodies(&self.conn, snapshot)
+    }
+
+    fn load_bodies(&self, snapshot: &mut Snapshot) -> VaultResult<()> {
+        if !snapshot.files.is_empty() {
+            let mut contents = HashMap::with_capacity(snapshot.files.len());
+            for (path, hash) in &snapshot.files {
+                contents.insert(path.clone(), self.get_blob(hash)?);
+            }
+            snapshot.multi_file_contents = Some(contents);
+        }
+        if let Some(hash) = snapshot.content_blob.as_deref() {
+            snapshot.content = self.get_blob(hash)?;
+        }
+        Ok(())
+    }
+
+    /// 快照行删除时 `snapshot_blobs` 级联删除，这里只需清理不再被引用的 blob
+    fn release(&self, snapshots: &[Snapshot]) -> VaultResult<()> {
+        for hash in snapshots.iter().flat_map(Snapshot::blob_refs) {
+            self.conn
+                .execute(
+                    "DELETE FROM blobs WHERE hash = ?1
+                     AND NOT EXISTS (SELECT 1 FROM snapshot_blobs WHERE hash = ?1)",
+                    [&hash],
+                )
+                .map_err(VaultError::database_failed)?;
+        }
+        Ok(())
+    }
+}
+
+/// 同步一个客户端的快照行：删除配置中已移除的快照，写入其余快照及其 blob 引用
+pub(super) fn save_config(conn: &Connection, config: &SnapshotConfig) -> VaultResult<()> {
+    let settings = SnapshotConfig {
+        snapshots: Vec::new(),
+        ..config.clone()
+    };
+    conn.execute(
+        "INSERT INTO snapshot_configs (client_id, data) VALUES (?1, ?2)
+         ON CONFLICT (client_id) DO UPDATE SET data = excluded.data",
+        params![config.client_id, to_json(&settings, CONFIG_TABLE)?],
+    )
+    .map_err(VaultError::database_failed)?;
+
+    let keep: HashSet<&str> = config.snapshots.iter().map(|s| s.id.as_str()).collect();
+    let existing: Vec<String> = {
+        let mut stmt = conn
+            .prepare("SELECT id FROM snapshots WHERE client_id = ?1")
+            .map_err(VaultError::database_failed)?;
+        let rows = stmt
+            .query_map([&config.client_id], |row| row.get(0))
+            .map_err(VaultError::database_failed)?;
+        rows.collect::<Result<_, _>>()
+            .map_err(VaultError::database_failed)?
+    };
+    for id in existing.iter().filter(|id| !keep.contains(id.as_str())) {
+        conn.execute("DELETE FROM snapshots WHERE id = ?1", [id])
+            .map_err(VaultError::database_failed)?;
+    }
+
+    for snapshot in &config.snapshots {
+        conn.execute(
+            "INSERT INTO snapshots (id, client_id, created_at, data) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (id) DO UPDATE SET
+                 client_id = excluded.client_id,
+                 created_at = excluded.created_at,
+                 data = excluded.data",
+            params![
+                snapshot.id,
+                config.client_id,
+                snapshot.created_at.to_rfc3339(),
+                to_json(snapshot, TABLE)?
+            ],
+        )
+        .map_err(VaultError::database_failed)?;
+        for hash in snapshot.blob_refs() {
+            conn.execute(
+                "INSERT OR IGNORE INTO snapshot_blobs (snapshot_id, hash) VALUES (?1, ?2)",
+                [&snapshot.id, &hash],
+            )
+            .map_err(VaultError::database_failed)?;
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn store_bodies(conn: &Connection, snapshot: &mut Snapshot) -> VaultResult<()> {
+    if let Some(contents) = snapshot.multi_file_contents.as_ref() {
+        for (path, content) in contents {
+            let hash = put_blob(conn, content)?;
+            snapshot.files.insert(path.clone(), hash);
+        }
+    }
+    if !snapshot.content.is_empty() {
+        snapshot.content_blob = Some(put_blob(conn, &snapshot.content)?);
+    }
+    snapshot.strip_bodies();
+    Ok(())
+}
+
+fn put_blob(conn: &Connection, content: &str) -> VaultResult<String> {
+    let hash = BlobStore::hash(content);
+    conn.execute(
+        "INSERT OR IGNORE INTO blobs (hash, content) VALUES (?1, ?2)",
+        params![hash, content],
+    )
+    .map_err(VaultError::database_failed)?;
+    Ok(hash)
+}
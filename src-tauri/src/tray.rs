@@ -1,16 +1,13 @@
-use std::sync::{Arc, Mutex};
-
-use crate::commands;
-use crate::file_watcher::ConfigFileWatcher;
+use crate::auto_snapshot;
+use crate::commands::event_sink::TauriEventSink;
 use crate::models::{ClientConfig, Snapshot};
-use crate::storage::{
-    client_repository::ClientRepository, snapshot_repository::SnapshotRepository,
-};
-use chrono::{DateTime, Local, Utc};
+use crate::service::{VaultError, VaultService};
+use chrono::{DateTime, Local};
 use serde::Serialize;
 use tauri::menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::tray::TrayIconBuilder;
 use tauri::{App, AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 
 const TRAY_ID: &str = "systempromptvault_tray";
 pub(crate) const SNAPSHOT_MENU_PREFIX: &str = "restore_snapshot_";
@@ -33,10 +30,6 @@ impl TrayError {
     pub fn new(message: impl Into<String>) -> Self {
         Self(message.into())
     }
-
-    fn from_poison(target: &str) -> Self {
-        Self(format!("获取 {target} 锁失败"))
-    }
 }
 
 impl std::fmt::Display for TrayError {
@@ -59,6 +52,12 @@ impl From<String> for TrayError {
     }
 }
 
+impl From<VaultError> for TrayError {
+    fn from(value: VaultError) -> Self {
+        Self::new(value.to_string())
+    }
+}
+
 /// Initialize the tray icon during the Tauri `setup` hook.
 pub fn init_tray(app: &App) -> TrayResult<()> {
     let handle = app.handle();
@@ -85,7 +84,7 @@ pub fn handle_tray_event<R: Runtime>(
     if id == SHOW_MAIN_WINDOW_MENU_ID {
         show_main_window(app_handle)
     } else if id == QUIT_MENU_ID {
-        app_handle.exit(0);
+        auto_snapshot::exit_app(app_handle);
         Ok(())
     } else if let Some(rest) = id.strip_prefix(SNAPSHOT_MENU_PREFIX) {
         if let Some(idx) = rest.rfind('_') {
@@ -128,37 +127,74 @@ fn show_main_window<R: Runtime>(app_handle: &AppHandle<R>) -> TrayResult<()> {
     window.set_focus().map_err(TrayError::from)
 }
 
+/// 先列出将被覆盖的文件并请求确认，当前文件与快照一致时直接恢复
 fn restore_snapshot_from_menu<R: Runtime>(
     app_handle: &AppHandle<R>,
     client_id: &str,
     snapshot_id: &str,
 ) -> TrayResult<()> {
-    let snapshot_state = app_handle.state::<Arc<Mutex<SnapshotRepository>>>();
-    let snapshot_repo = Arc::clone(snapshot_state.inner());
-    let client_state = app_handle.state::<Arc<Mutex<ClientRepository>>>();
-    let watcher_state = app_handle.state::<Arc<Mutex<ConfigFileWatcher>>>();
-
-    commands::snapshot::restore_snapshot_from_tray(
-        app_handle.clone(),
-        Arc::clone(snapshot_state.inner()),
-        Arc::clone(client_state.inner()),
-        Arc::clone(watcher_state.inner()),
-        client_id.to_string(),
-        snapshot_id.to_string(),
-    )
-    .map_err(TrayError::from)?;
+    let service = app_handle.state::<VaultService>();
+    let diff = service.diff_snapshot_with_current(client_id, snapshot_id)?;
+    if diff.is_empty() {
+        return restore_snapshot(app_handle, client_id, snapshot_id);
+    }
 
-    let (snapshot_name, created_at_iso) = {
-        let repo = snapshot_repo
-            .lock()
-            .map_err(|_| TrayError::from_poison("快照仓库"))?;
-        let snapshots = repo.get_snapshots(client_id).map_err(TrayError::from)?;
-        snapshots
-            .iter()
-            .find(|s| s.id == snapshot_id)
-            .map(|s| (s.name.clone(), s.created_at.to_rfc3339()))
-            .unwrap_or_else(|| ("未知快照".to_string(), Utc::now().to_rfc3339()))
-    };
+    let snapshot_name = service
+        .get_snapshots(client_id)?
+        .into_iter()
+        .find(|snapshot| snapshot.id == snapshot_id)
+        .map(|snapshot| snapshot.name)
+        .unwrap_or_else(|| snapshot_id.to_string());
+    // 差异方向为快照 -> 当前文件，恢复时增删互换
+    let files: Vec<String> = diff
+        .files
+        .iter()
+        .map(|file| {
+            format!(
+                "{} (+{} -{})",
+                file.path, file.removed_lines, file.added_lines
+            )
+        })
+        .collect();
+    let message = format!(
+        "恢复快照「{}」将覆盖以下文件的当前内容：\n\n{}",
+        snapshot_name,
+        files.join("\n")
+    );
+
+    let handle = app_handle.clone();
+    let client_id = client_id.to_string();
+    let snapshot_id = snapshot_id.to_string();
+    app_handle
+        .dialog()
+        .message(message)
+        .title("恢复快照")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "恢复".to_string(),
+            "取消".to_string(),
+        ))
+        .show(move |confirmed| {
+            if !confirmed {
+                return;
+            }
+            if let Err(err) = restore_snapshot(&handle, &client_id, &snapshot_id) {
+                eprintln!("托盘恢复快照失败: {}", err);
+            }
+        });
+    Ok(())
+}
+
+fn restore_snapshot<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    client_id: &str,
+    snapshot_id: &str,
+) -> TrayResult<()> {
+    let service = app_handle.state::<VaultService>();
+    let sink = TauriEventSink::new(app_handle.clone());
+    let snapshot = service.restore_snapshot(client_id, snapshot_id, &sink)?;
+    let snapshot_name = snapshot.name;
+    let created_at_iso = snapshot.created_at.to_rfc3339();
     eprintln!(
         "[Tray] Restored snapshot '{}' for client '{}'",
         snapshot_name, client_id
@@ -211,18 +247,11 @@ pub(crate) fn build_client_submenus<R: Runtime>(
     let mut clients = collect_clients(app_handle)?;
     clients.sort_by(|a, b| a.name.cmp(&b.name));
 
-    let snapshot_state = app_handle.state::<Arc<Mutex<SnapshotRepository>>>();
-    let snapshot_repo = Arc::clone(snapshot_state.inner());
-
+    let service = app_handle.state::<VaultService>();
     let mut data: Vec<(ClientConfig, Vec<Snapshot>)> = Vec::new();
-    {
-        let repo = snapshot_repo
-            .lock()
-            .map_err(|_| TrayError::from_poison("快照仓库"))?;
-        for client in &clients {
-            let snapshots = repo.get_snapshots(&client.id).map_err(TrayError::from)?;
-            data.push((client.clone(), snapshots));
-        }
+    for client in &clients {
+        let snapshots = service.get_snapshots(&client.id)?;
+        data.push((client.clone(), snapshots));
     }
 
     let mut submenus = Vec::new();
@@ -282,12 +311,8 @@ pub(crate) fn build_client_submenu<R: Runtime>(
 pub(crate) fn collect_clients<R: Runtime>(
     app_handle: &AppHandle<R>,
 ) -> TrayResult<Vec<ClientConfig>> {
-    let client_state = app_handle.state::<Arc<Mutex<ClientRepository>>>();
-    let repo = client_state
-        .inner()
-        .lock()
-        .map_err(|_| TrayError::from_poison("客户端仓库"))?;
-    repo.get_all().map_err(TrayError::from)
+    let service = app_handle.state::<VaultService>();
+    Ok(service.get_clients()?)
 }
 
 pub(crate) fn format_client_label(client: &ClientConfig, snapshot_count: usize) -> String {
@@ -297,10 +322,15 @@ pub(crate) fn format_client_label(client: &ClientConfig, snapshot_count: usize)
 pub(crate) fn format_snapshot_label(snapshot: &Snapshot, is_auto: bool) -> String {
     let local_time: DateTime<Local> = snapshot.created_at.with_timezone(&Local);
     let timestamp = local_time.format("%Y-%m-%d %H:%M:%S");
-    if is_auto {
+    let label = if is_auto {
         format!("Auto Saved {}", timestamp)
     } else {
         format!("{} {}", snapshot.name, timestamp)
+    };
+    if snapshot.pinned {
+        format!("📌 {}", label)
+    } else {
+        label
     }
 }
 
@@ -1,5 +1,6 @@
 pub mod file_ops;
 pub mod path_utils;
+pub mod structured;
 
-pub use file_ops::{atomic_write, read_config_file, write_config_file};
-pub use path_utils::{get_config_path, normalize_path, ConfigFileType};
+pub use file_ops::atomic_write;
+pub use path_utils::normalize_path;
@@ -1,81 +1,414 @@
 use dirs::home_dir;
 use std::env;
+use std::fs;
 use std::path::{Component, Path, PathBuf};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ConfigFileType {
-    Claude,
-    Codex,
-    Gemini,
+pub fn normalize_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let input = path.as_ref();
+    if input.is_absolute() {
+        clean_components(input)
+    } else {
+        env::current_dir()
+            .map(|cwd| clean_components(cwd.join(input)))
+            .unwrap_or_else(|_| clean_components(input))
+    }
+}
+
+/// 未设置时按工具默认位置解析的环境变量，值为相对主目录的路径
+const DEFAULT_DIR_VARS: [(&str, &str); 3] = [
+    ("XDG_CONFIG_HOME", ".config"),
+    ("CODEX_HOME", ".codex"),
+    ("CLAUDE_CONFIG_DIR", ".claude"),
+];
+
+/// 把保存的配置路径解析为实际路径，所有读写、快照、导出与监听都经由这里
+///
+/// 依次展开 `$VAR`、`${VAR}`、`${VAR:-default}`（变量未设置或为空时使用 `default`）与开头的 `~`。
+/// 未设置的 `XDG_CONFIG_HOME`、`CODEX_HOME`、`CLAUDE_CONFIG_DIR` 取各工具的默认目录，
+/// 其他未设置的变量保留原样。客户端配置中始终保存展开前的写法，便于在不同机器间使用。
+pub fn resolve_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let input = path.as_ref();
+    match input.to_str() {
+        Some(path_str) => expand_home(&expand_vars(path_str)),
+        None => input.to_path_buf(),
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    let Some(home) = home_dir() else {
+        return PathBuf::from(path);
+    };
+    if path == "~" {
+        return home;
+    }
+    match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some("") => home,
+        Some(remainder) => home.join(remainder),
+        None => PathBuf::from(path),
+    }
 }
 
-impl ConfigFileType {
-    pub fn from_str(value: &str) -> Result<Self, String> {
-        match value.to_lowercase().as_str() {
-            "claude" => Ok(Self::Claude),
-            "codex" => Ok(Self::Codex),
-            "gemini" => Ok(Self::Gemini),
-            other => Err(format!("未知的配置类型: {}", other)),
+fn expand_vars(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find('$') {
+        result.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+        let expanded = match after.strip_prefix('{') {
+            Some(braced) => expand_braced(braced).map(|(value, len)| (value, len + 1)),
+            None => match var_name_len(after) {
+                0 => None,
+                len => Some((lookup_var(&after[..len]), len)),
+            },
+        };
+        match expanded {
+            // 未设置的变量保留原样
+            Some((value, len)) => {
+                result.push_str(value.as_deref().unwrap_or(&rest[index..index + 1 + len]));
+                rest = &after[len..];
+            }
+            None => {
+                result.push('$');
+                rest = after;
+            }
         }
     }
+    result.push_str(rest);
+    result
+}
 
-    pub fn directory(&self) -> &'static str {
-        match self {
-            Self::Claude => ".claude",
-            Self::Codex => ".codex",
-            Self::Gemini => ".gemini",
+/// 解析 `{` 之后的 `VAR}` 或 `VAR:-default}`，返回展开结果与消耗的长度
+fn expand_braced(text: &str) -> Option<(Option<String>, usize)> {
+    let len = var_name_len(text);
+    if len == 0 {
+        return None;
+    }
+    let name = &text[..len];
+    let rest = &text[len..];
+    if rest.starts_with('}') {
+        return Some((lookup_var(name), len + 1));
+    }
+    let body = rest.strip_prefix(":-")?;
+    // 默认值中可以再嵌套 `${...}`
+    let mut depth = 0;
+    let end = body.char_indices().find_map(|(index, c)| match c {
+        '{' => {
+            depth += 1;
+            None
         }
+        '}' if depth == 0 => Some(index),
+        '}' => {
+            depth -= 1;
+            None
+        }
+        _ => None,
+    })?;
+    let value = env_var(name).unwrap_or_else(|| expand_vars(&body[..end]));
+    Some((Some(value), len + 2 + end + 1))
+}
+
+fn var_name_len(text: &str) -> usize {
+    text.char_indices()
+        .find(|&(index, c)| {
+            !(c == '_' || c.is_ascii_alphabetic() || (index > 0 && c.is_ascii_digit()))
+        })
+        .map_or(text.len(), |(index, _)| index)
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn lookup_var(name: &str) -> Option<String> {
+    env_var(name).or_else(|| {
+        let (_, dir) = DEFAULT_DIR_VARS.iter().find(|(var, _)| *var == name)?;
+        Some(home_dir()?.join(dir).to_string_lossy().into_owned())
+    })
+}
+
+/// 列出匹配 `pattern` 的文件，通配规则见 [`PathPattern`]；不含通配符时只检查文件是否存在
+pub fn expand_file_pattern(pattern: &Path) -> Vec<PathBuf> {
+    match PathPattern::parse(&pattern.to_string_lossy()) {
+        Some(pattern) => pattern.files(),
+        None if pattern.is_file() => vec![pattern.to_path_buf()],
+        None => Vec::new(),
+    }
+}
+
+/// 配置路径是否为通配模式：含 `*`、`?`，或以路径分隔符结尾表示整个目录
+pub fn is_path_pattern(entry: &str) -> bool {
+    entry.contains(['*', '?']) || entry.ends_with(['/', '\\'])
+}
+
+/// 通配模式展开为当前匹配的文件，普通路径原样返回
+///
+/// 结果沿用模式前缀的原始写法（例如 `~/`），与客户端配置中保存的路径形式一致。
+pub fn expand_path_entry(entry: &str) -> Vec<String> {
+    let Some((prefix, components)) = split_pattern(entry) else {
+        return vec![entry.to_string()];
+    };
+    let pattern = PathPattern::from_parts(prefix, components);
+    pattern
+        .files()
+        .iter()
+        .filter_map(|file| file.strip_prefix(&pattern.base).ok())
+        .map(|relative| join_entry(prefix, relative))
+        .collect()
+}
+
+/// `path` 是否列在 `entries` 中：与某一项相同，或是某个通配模式匹配的具体文件
+///
+/// 只比较字符串，不访问文件系统；通配模式本身不算具体文件。
+pub fn is_listed_path(entries: &[String], path: &str) -> bool {
+    !is_path_pattern(path) && entries.iter().any(|entry| entry_matches(entry, path))
+}
+
+fn entry_matches(entry: &str, path: &str) -> bool {
+    let Some((prefix, components)) = split_pattern(entry) else {
+        return entry == path;
+    };
+    let Some(rest) = path.strip_prefix(prefix) else {
+        return false;
+    };
+    if !prefix.is_empty() && !prefix.ends_with(['/', '\\']) && !rest.starts_with(['/', '\\']) {
+        return false;
+    }
+    let parts: Vec<String> = rest
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect();
+    match_components(&components, &parts)
+}
+
+/// 配置路径中的通配模式
+///
+/// `*` 匹配一级中的任意字符，`?` 匹配单个字符，`**` 匹配零到多级目录；以分隔符结尾的目录
+/// 等同于 `目录/**/*`。通配符不匹配以 `.` 开头的文件名，`**` 也不进入隐藏目录，
+/// 除非该级模式本身以 `.` 开头。通配部分中的 `.` 被忽略，`..` 不匹配任何路径，
+/// 因此匹配结果不会越出基准目录。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPattern {
+    /// 第一个通配级之前的目录
+    base: PathBuf,
+    components: Vec<String>,
+}
+
+impl PathPattern {
+    /// 解析通配模式，前缀按 [`resolve_path`] 展开；不是通配模式时返回 `None`
+    pub fn parse(entry: &str) -> Option<Self> {
+        split_pattern(entry).map(|(prefix, components)| Self::from_parts(prefix, components))
+    }
+
+    fn from_parts(prefix: &str, components: Vec<String>) -> Self {
+        let base = if prefix.is_empty() {
+            PathBuf::from(".")
+        } else {
+            resolve_path(prefix)
+        };
+        Self { base, components }
     }
 
-    pub fn file_name(&self) -> &'static str {
-        match self {
-            Self::Claude => "CLAUDE.md",
-            Self::Codex => "AGENTS.md",
-            Self::Gemini => "GEMINI.md",
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// 替换基准目录，用于匹配已解析符号链接的路径
+    pub fn with_base(&self, base: PathBuf) -> Self {
+        Self {
+            base,
+            components: self.components.clone(),
         }
     }
+
+    /// 是否会匹配基准目录下子目录中的文件
+    pub fn is_recursive(&self) -> bool {
+        self.components.len() > 1
+    }
+
+    /// `path` 是否与模式匹配，只比较路径，不访问文件系统
+    pub fn matches(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        let parts: Vec<String> = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        match_components(&self.components, &parts)
+    }
+
+    /// 当前匹配的文件，按路径排序
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        collect_matches(&self.base, &self.components, &mut files);
+        files.sort();
+        files.dedup();
+        files
+    }
 }
 
-pub fn normalize_path<P: AsRef<Path>>(path: P) -> PathBuf {
-    let input = path.as_ref();
-    if input.is_absolute() {
-        clean_components(input)
+/// 拆分为不含通配符的前缀与其后各级的模式；不是通配模式时返回 `None`
+fn split_pattern(entry: &str) -> Option<(&str, Vec<String>)> {
+    if !is_path_pattern(entry) {
+        return None;
+    }
+    let mut offset = 0;
+    let mut start = None;
+    for part in entry.split(['/', '\\']) {
+        if part.contains(['*', '?']) {
+            start = Some(offset);
+            break;
+        }
+        offset += part.len() + 1;
+    }
+    let (prefix, rest) = entry.split_at(start.unwrap_or(entry.len()));
+    let trimmed = prefix.trim_end_matches(['/', '\\']);
+    // 保留根目录本身的分隔符
+    let prefix = if trimmed.is_empty() {
+        &prefix[..prefix.len().min(1)]
     } else {
-        env::current_dir()
-            .map(|cwd| clean_components(cwd.join(input)))
-            .unwrap_or_else(|_| clean_components(input))
+        trimmed
+    };
+    let mut components: Vec<String> = rest
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .map(str::to_string)
+        .collect();
+    if entry.ends_with(['/', '\\']) {
+        components.extend(["**".to_string(), "*".to_string()]);
+    } else if components.last().is_some_and(|last| last == "**") {
+        components.push("*".to_string());
     }
+    Some((prefix, components))
 }
 
-pub fn expand_tilde<P: AsRef<Path>>(path: P) -> PathBuf {
-    let input = path.as_ref();
-    let Some(home) = home_dir() else {
-        return input.to_path_buf();
+fn join_entry(prefix: &str, relative: &Path) -> String {
+    let relative = to_slash_path(relative);
+    if prefix.is_empty() {
+        relative
+    } else if prefix.ends_with(['/', '\\']) {
+        format!("{}{}", prefix, relative)
+    } else {
+        format!("{}/{}", prefix, relative)
+    }
+}
+
+fn match_components(pattern: &[String], parts: &[String]) -> bool {
+    let Some((first, rest)) = pattern.split_first() else {
+        return parts.is_empty();
     };
+    if first == "**" {
+        return (0..=parts.len()).any(|skip| {
+            parts[..skip].iter().all(|dir| !dir.starts_with('.'))
+                && match_components(rest, &parts[skip..])
+        });
+    }
+    parts.split_first().is_some_and(|(part, remaining)| {
+        !is_dot_segment(part) && wildcard_match(first, part) && match_components(rest, remaining)
+    })
+}
 
-    if let Some(path_str) = input.to_str() {
-        if path_str == "~" {
-            return home;
-        }
+fn is_dot_segment(part: &str) -> bool {
+    part == "." || part == ".."
+}
 
-        if let Some(remainder) = path_str
-            .strip_prefix("~/")
-            .or_else(|| path_str.strip_prefix("~\\"))
-        {
-            let mut expanded = home;
-            if !remainder.is_empty() {
-                expanded.push(remainder);
+fn collect_matches(dir: &Path, components: &[String], out: &mut Vec<PathBuf>) {
+    let Some((first, rest)) = components.split_first() else {
+        return;
+    };
+    if is_dot_segment(first) {
+        return;
+    }
+    if !first.contains(['*', '?']) {
+        let path = dir.join(first);
+        if rest.is_empty() {
+            if path.is_file() {
+                out.push(path);
+            }
+        } else {
+            collect_matches(&path, rest, out);
+        }
+        return;
+    }
+    let recursive = first == "**";
+    if recursive {
+        collect_matches(dir, rest, out);
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        // 不跟随指向目录的符号链接，避免循环
+        let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+        if recursive {
+            if is_dir && !name.starts_with('.') {
+                collect_matches(&entry.path(), components, out);
+            }
+        } else if wildcard_match(first, name) {
+            let path = entry.path();
+            if rest.is_empty() {
+                if path.is_file() {
+                    out.push(path);
+                }
+            } else if is_dir {
+                collect_matches(&path, rest, out);
             }
-            return expanded;
         }
     }
+}
 
-    input.to_path_buf()
+/// 主目录下的路径转换为 `~/` 形式，与内置客户端的保存格式一致
+pub fn contract_tilde(path: &Path, home: &Path) -> String {
+    match path.strip_prefix(home) {
+        Ok(relative) => format!("~/{}", to_slash_path(relative)),
+        Err(_) => path.to_string_lossy().into_owned(),
+    }
 }
 
-pub fn get_config_path<P: AsRef<Path>>(project_path: P, file_type: ConfigFileType) -> PathBuf {
-    let base = normalize_path(project_path);
-    base.join(file_type.directory()).join(file_type.file_name())
+/// 以 `/` 连接路径各级，用于跨平台保存的相对路径
+pub fn to_slash_path(path: &Path) -> String {
+    let parts: Vec<String> = path
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    parts.join("/")
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // 最近一个 `*` 的位置与它已经匹配到的位置，失配时回溯
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 fn clean_components<P: AsRef<Path>>(path: P) -> PathBuf {
@@ -92,3 +425,254 @@ fn clean_components<P: AsRef<Path>>(path: P) -> PathBuf {
             acc
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在临时目录下创建文件，返回目录路径
+    fn fixture(name: &str, files: &[&str]) -> PathBuf {
+        let root = env::temp_dir().join(format!("spv-path-utils-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "x").unwrap();
+        }
+        root
+    }
+
+    /// 展开 `root` 下的模式，返回相对 `root` 的匹配结果
+    fn expand(root: &Path, pattern: &str) -> Vec<String> {
+        let prefix = root.to_string_lossy();
+        expand_path_entry(&format!("{}/{}", prefix, pattern))
+            .into_iter()
+            .map(|path| path[prefix.len() + 1..].to_string())
+            .collect()
+    }
+
+    fn listed(entry: &str, path: &str) -> bool {
+        is_listed_path(&[entry.to_string()], path)
+    }
+
+    #[test]
+    fn wildcard_matches_star_and_question_mark() {
+        assert!(wildcard_match("*.md", "a.md"));
+        assert!(!wildcard_match("*.md", ".md.md"));
+        assert!(wildcard_match("a*b*c", "aXXbYc"));
+        assert!(!wildcard_match("a*b*c", "aXXbY"));
+        assert!(wildcard_match("t?o.mdc", "two.mdc"));
+        assert!(!wildcard_match("t?o.mdc", "to.mdc"));
+        assert!(wildcard_match("*", "名称.md"));
+    }
+
+    #[test]
+    fn wildcard_skips_hidden_names_unless_pattern_starts_with_dot() {
+        assert!(!wildcard_match("*", ".env"));
+        assert!(!wildcard_match("?env", ".env"));
+        assert!(wildcard_match(".*", ".env"));
+        assert!(wildcard_match(".e?v", ".env"));
+    }
+
+    #[test]
+    fn expands_star_question_mark_and_double_star() {
+        let root = fixture(
+            "expand",
+            &[
+                "cmd/a.md",
+                "cmd/x.txt",
+                "cmd/sub/b.md",
+                "cmd/sub/deep/c.md",
+                "rules/one.mdc",
+                "rules/two.mdc",
+            ],
+        );
+        assert_eq!(
+            expand(&root, "rules/*.mdc"),
+            ["rules/one.mdc", "rules/two.mdc"]
+        );
+        assert_eq!(expand(&root, "rules/t?o.mdc"), ["rules/two.mdc"]);
+        assert_eq!(
+            expand(&root, "cmd/**/*.md"),
+            ["cmd/a.md", "cmd/sub/b.md", "cmd/sub/deep/c.md"]
+        );
+        assert_eq!(
+            expand(&root, "cmd/**"),
+            ["cmd/a.md", "cmd/sub/b.md", "cmd/sub/deep/c.md", "cmd/x.txt"]
+        );
+        assert_eq!(expand(&root, "*/sub/*.md"), ["cmd/sub/b.md"]);
+        assert!(expand(&root, "missing/*.md").is_empty());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn trailing_separator_expands_whole_directory() {
+        let root = fixture("dir", &["agents/r.md", "agents/n/s.md", "other/t.md"]);
+        assert_eq!(expand(&root, "agents/"), ["agents/n/s.md", "agents/r.md"]);
+        assert!(listed("~/.claude/agents/", "~/.claude/agents/x/y.md"));
+        assert!(!listed("~/.claude/agents/", "~/.claude/agentsx/y.md"));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn hidden_files_and_directories_need_explicit_dot() {
+        let root = fixture(
+            "hidden",
+            &["cmd/a.md", "cmd/.e.md", "cmd/.git/d.md", "cmd/sub/.f.md"],
+        );
+        assert_eq!(expand(&root, "cmd/*.md"), ["cmd/a.md"]);
+        assert_eq!(expand(&root, "cmd/**/*.md"), ["cmd/a.md"]);
+        assert_eq!(expand(&root, "cmd/.*.md"), ["cmd/.e.md"]);
+        assert_eq!(
+            expand(&root, "cmd/**/.*.md"),
+            ["cmd/.e.md", "cmd/sub/.f.md"]
+        );
+        assert_eq!(expand(&root, "cmd/.git/*.md"), ["cmd/.git/d.md"]);
+        assert!(!listed("~/.claude/agents/", "~/.claude/agents/.git/y"));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn patterns_do_not_escape_base_directory() {
+        let root = fixture("escape", &["base/sub/a.md", "secret.md", "base/b.md"]);
+        assert!(expand(&root, "base/*/../../secret.md").is_empty());
+        assert!(expand(&root, "base/**/../*.md").is_empty());
+        assert!(expand(&root, "base/.*/secret.md").is_empty());
+        assert_eq!(expand(&root, "base/./*.md"), ["base/./b.md"]);
+        assert_eq!(expand(&root, "base/*/./a.md"), ["base/sub/a.md"]);
+
+        let pattern = PathPattern::parse("/vault/cmd/**/*.md").unwrap();
+        assert!(pattern.matches(Path::new("/vault/cmd/x/a.md")));
+        assert!(!pattern.matches(Path::new("/vault/cmd/../a.md")));
+        assert!(!pattern.matches(Path::new("/vault/cmd/x/../../a.md")));
+        assert!(!listed("~/cmd/*/*.md", "~/cmd/../x.md"));
+        assert!(!listed("~/cmd/**/*.md", "~/cmd/../../x.md"));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn listed_path_matches_concrete_files_only() {
+        let entry = "~/.claude/commands/**/*.md";
+        assert!(listed(entry, "~/.claude/commands/b.md"));
+        assert!(listed(entry, "~/.claude/commands/a/b.md"));
+        assert!(!listed(entry, entry));
+        assert!(!listed(entry, "~/.claude/commandsx/b.md"));
+        assert!(listed(".cursor/rules/*.mdc", ".cursor/rules/a.mdc"));
+        assert!(!listed(".cursor/rules/*.mdc", ".cursor/rules/a/b.mdc"));
+        assert!(listed("~/a.md", "~/a.md"));
+    }
+
+    #[test]
+    fn pattern_recursion_and_matching() {
+        let recursive = PathPattern::parse("/vault/cmd/**/*.md").unwrap();
+        assert!(recursive.is_recursive());
+        assert_eq!(recursive.base(), Path::new("/vault/cmd"));
+        assert!(recursive.matches(Path::new("/vault/cmd/new/z.md")));
+        assert!(!recursive.matches(Path::new("/vault/cmd/new/z.txt")));
+        assert!(!recursive.matches(Path::new("/other/cmd/z.md")));
+
+        let flat = PathPattern::parse("/vault/rules/*.mdc").unwrap();
+        assert!(!flat.is_recursive());
+        assert!(PathPattern::parse("/vault/rules/a.mdc").is_none());
+    }
+
+    #[test]
+    fn expands_plain_and_braced_variables() {
+        env::set_var("SPV_TEST_DIR", "/opt/spv");
+        assert_eq!(resolve_path("$SPV_TEST_DIR/a"), PathBuf::from("/opt/spv/a"));
+        assert_eq!(
+            resolve_path("${SPV_TEST_DIR}/a"),
+            PathBuf::from("/opt/spv/a")
+        );
+        assert_eq!(
+            resolve_path("${SPV_TEST_DIR}x/a"),
+            PathBuf::from("/opt/spvx/a")
+        );
+        assert_eq!(
+            resolve_path("$SPV_TEST_DIRx/a"),
+            PathBuf::from("$SPV_TEST_DIRx/a")
+        );
+        assert_eq!(
+            resolve_path("${SPV_TEST_DIR:-/no}/a"),
+            PathBuf::from("/opt/spv/a")
+        );
+    }
+
+    #[test]
+    fn default_applies_to_unset_or_empty_variables() {
+        let home = home_dir().unwrap();
+        env::remove_var("SPV_TEST_UNSET");
+        env::set_var("SPV_TEST_EMPTY", "");
+        env::set_var("SPV_TEST_NESTED", "/opt/nested");
+        assert_eq!(
+            resolve_path("${SPV_TEST_UNSET:-/d}/a"),
+            PathBuf::from("/d/a")
+        );
+        assert_eq!(
+            resolve_path("${SPV_TEST_EMPTY:-/d}/a"),
+            PathBuf::from("/d/a")
+        );
+        assert_eq!(resolve_path("${SPV_TEST_UNSET:-~/d}/a"), home.join("d/a"));
+        assert_eq!(
+            resolve_path("${SPV_TEST_UNSET:-${SPV_TEST_NESTED}/y}/a"),
+            PathBuf::from("/opt/nested/y/a")
+        );
+    }
+
+    #[test]
+    fn unset_variables_are_kept_verbatim() {
+        env::remove_var("SPV_TEST_MISSING");
+        assert_eq!(
+            resolve_path("$SPV_TEST_MISSING/a"),
+            PathBuf::from("$SPV_TEST_MISSING/a")
+        );
+        assert_eq!(
+            resolve_path("${SPV_TEST_MISSING}/a"),
+            PathBuf::from("${SPV_TEST_MISSING}/a")
+        );
+    }
+
+    #[test]
+    fn literal_dollar_signs_are_kept() {
+        assert_eq!(resolve_path("a$/b$"), PathBuf::from("a$/b$"));
+        assert_eq!(resolve_path("$1/x"), PathBuf::from("$1/x"));
+        assert_eq!(resolve_path("${/x"), PathBuf::from("${/x"));
+        assert_eq!(resolve_path("${broken/x"), PathBuf::from("${broken/x"));
+        assert_eq!(resolve_path("$$"), PathBuf::from("$$"));
+    }
+
+    #[test]
+    fn expands_leading_tilde_only() {
+        let home = home_dir().unwrap();
+        assert_eq!(resolve_path("~"), home);
+        assert_eq!(resolve_path("~/a.md"), home.join("a.md"));
+        assert_eq!(resolve_path("a/~/b"), PathBuf::from("a/~/b"));
+        assert_eq!(resolve_path("~user/a"), PathBuf::from("~user/a"));
+    }
+
+    #[test]
+    fn tool_directories_fall_back_to_defaults() {
+        let home = home_dir().unwrap();
+        for (var, dir) in DEFAULT_DIR_VARS {
+            env::remove_var(var);
+            assert_eq!(
+                resolve_path(format!("${}/x", var)),
+                home.join(dir).join("x")
+            );
+            assert_eq!(
+                resolve_path(format!("${{{}}}/x", var)),
+                home.join(dir).join("x")
+            );
+            assert_eq!(
+                resolve_path(format!("${{{}:-/custom}}/x", var)),
+                PathBuf::from("/custom/x")
+            );
+            env::set_var(var, "/override");
+            assert_eq!(
+                resolve_path(format!("${}/x", var)),
+                PathBuf::from("/override/x")
+            );
+            env::remove_var(var);
+        }
+    }
+}
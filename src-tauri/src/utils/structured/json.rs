@@ -0,0 +1,348 @@
+//! JSON：先用 serde_json 严格校验，再扫描出各个值在原文中的位置，只替换或插入目标片段
+
+use super::{nest, parse_index, path_conflict, ConfigSyntaxError, PatchedContent};
+use crate::error::VaultResult;
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::Value;
+
+const DEFAULT_INDENT: &str = "  ";
+
+pub(super) fn parse(content: &str) -> Result<Value, ConfigSyntaxError> {
+    serde_json::from_str(content).map_err(|err| ConfigSyntaxError {
+        line: Some(err.line()),
+        column: Some(err.column()),
+        message: err.to_string(),
+    })
+}
+
+pub(super) fn set(
+    content: &str,
+    key_path: &[String],
+    value: &Value,
+) -> VaultResult<PatchedContent> {
+    // 空文件视为空对象，便于向尚未创建的 settings.json 写入第一个键
+    let source = if content.trim().is_empty() {
+        "{}\n"
+    } else {
+        content
+    };
+    parse(source).map_err(|err| err.into_error(crate::client_catalog::ConfigFormat::Json))?;
+    let root = Scanner::new(source).scan();
+    let unit = detect_indent_unit(source);
+
+    let mut node = &root;
+    for (depth, key) in key_path.iter().enumerate() {
+        let child = match &node.kind {
+            NodeKind::Object(members) => match members.iter().rev().find(|m| &m.key == key) {
+                Some(member) => &member.value,
+                None => {
+                    let inner = nest(&key_path[depth + 1..], value);
+                    let content = insert_member(source, node, members, key, &inner, &unit);
+                    return Ok(PatchedContent {
+                        content,
+                        formatting_preserved: true,
+                    });
+                }
+            },
+            NodeKind::Array(items) => parse_index(key)
+                .and_then(|index| items.get(index))
+                .ok_or_else(|| path_conflict(key_path, depth))?,
+            NodeKind::Scalar => return Err(path_conflict(key_path, depth)),
+        };
+        node = child;
+    }
+
+    let rendered = render(value, &line_indent(source, node.start), &unit);
+    let content = splice(source, node.start, node.end, &rendered);
+    Ok(PatchedContent {
+        content,
+        formatting_preserved: true,
+    })
+}
+
+/// 在对象末尾追加成员，沿用已有成员的缩进；单行对象保持单行
+fn insert_member(
+    source: &str,
+    object: &Node,
+    members: &[Member],
+    key: &str,
+    value: &Value,
+    unit: &str,
+) -> String {
+    let key = serde_json::to_string(key).unwrap_or_default();
+    let Some(last) = members.last() else {
+        let base = line_indent(source, object.start);
+        let inner = format!("{}{}", base, unit);
+        let body = format!(
+            "{{\n{}{}: {}\n{}}}",
+            inner,
+            key,
+            render(value, &inner, unit),
+            base
+        );
+        return splice(source, object.start, object.end, &body);
+    };
+    let multiline = source[object.start..members[0].key_start].contains('\n');
+    let addition = if multiline {
+        let indent = line_indent(source, last.key_start);
+        format!(",\n{}{}: {}", indent, key, render(value, &indent, unit))
+    } else {
+        format!(", {}: {}", key, value)
+    };
+    splice(source, last.value.end, last.value.end, &addition)
+}
+
+/// 标量直接输出；对象与数组按文件的缩进单位展开，后续行接在 `base` 缩进之后
+fn render(value: &Value, base: &str, unit: &str) -> String {
+    if !matches!(value, Value::Object(_) | Value::Array(_)) {
+        return value.to_string();
+    }
+    let mut buffer = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(
+        &mut buffer,
+        PrettyFormatter::with_indent(unit.as_bytes()),
+    );
+    if value.serialize(&mut serializer).is_err() {
+        return value.to_string();
+    }
+    String::from_utf8_lossy(&buffer).replace('\n', &format!("\n{}", base))
+}
+
+fn splice(source: &str, start: usize, end: usize, replacement: &str) -> String {
+    let mut result = String::with_capacity(source.len() + replacement.len());
+    result.push_str(&source[..start]);
+    result.push_str(replacement);
+    result.push_str(&source[end..]);
+    result
+}
+
+/// `offset` 所在行的前导空白
+fn line_indent(source: &str, offset: usize) -> String {
+    let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    source[line_start..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
+/// 第一处缩进即为一级缩进，文件没有缩进时使用两个空格
+fn detect_indent_unit(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| {
+            line.chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect::<String>()
+        })
+        .find(|indent| !indent.is_empty())
+        .unwrap_or_else(|| DEFAULT_INDENT.to_string())
+}
+
+struct Node {
+    start: usize,
+    end: usize,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    Object(Vec<Member>),
+    Array(Vec<Node>),
+    Scalar,
+}
+
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+}
+
+/// 只处理已经通过 serde_json 校验的内容，因此不再重复报告语法错误
+struct Scanner<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            bytes: source.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn scan(mut self) -> Node {
+        self.skip_whitespace();
+        self.value()
+    }
+
+    fn value(&mut self) -> Node {
+        let start = self.pos;
+        let kind = match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => {
+                self.string();
+                NodeKind::Scalar
+            }
+            _ => {
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b']' | b'}') || byte.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                NodeKind::Scalar
+            }
+        };
+        Node {
+            start,
+            end: self.pos,
+            kind,
+        }
+    }
+
+    fn object(&mut self) -> NodeKind {
+        self.pos += 1;
+        let mut members = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'}') | None => {
+                    self.pos += 1;
+                    return NodeKind::Object(members);
+                }
+                Some(b',') => {
+                    self.pos += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            let key_start = self.pos;
+            self.string();
+            let key = serde_json::from_str(&self.source[key_start..self.pos]).unwrap_or_default();
+            self.skip_whitespace();
+            self.pos += 1; // ':'
+            self.skip_whitespace();
+            let value = self.value();
+            members.push(Member {
+                key,
+                key_start,
+                value,
+            });
+        }
+    }
+
+    fn array(&mut self) -> NodeKind {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b']') | None => {
+                    self.pos += 1;
+                    return NodeKind::Array(items);
+                }
+                Some(b',') => {
+                    self.pos += 1;
+                    continue;
+                }
+                _ => items.push(self.value()),
+            }
+        }
+    }
+
+    fn string(&mut self) {
+        self.pos += 1;
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            match byte {
+                b'\\' => self.pos += 1,
+                b'"' => return,
+                _ => {}
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+    use serde_json::json;
+
+    fn keys(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    fn patch(content: &str, path: &str, value: Value) -> String {
+        let patched = set(content, &keys(path), &value).unwrap();
+        assert!(patched.formatting_preserved);
+        patched.content
+    }
+
+    const SETTINGS: &str = "{\n    \"model\": \"opus\",\n    \"permissions\": {\n        \"allow\": [\"a\"]\n    }\n}\n";
+
+    #[test]
+    fn replaces_value_and_keeps_layout() {
+        assert_eq!(
+            patch(SETTINGS, "model", json!("sonnet")),
+            "{\n    \"model\": \"sonnet\",\n    \"permissions\": {\n        \"allow\": [\"a\"]\n    }\n}\n"
+        );
+        assert_eq!(
+            patch(SETTINGS, "permissions.allow.0", json!("b")),
+            "{\n    \"model\": \"opus\",\n    \"permissions\": {\n        \"allow\": [\"b\"]\n    }\n}\n"
+        );
+        assert_eq!(
+            patch("{\"a\": 1, \"b\": 2}", "b", json!(3)),
+            "{\"a\": 1, \"b\": 3}"
+        );
+    }
+
+    #[test]
+    fn inserts_missing_members_with_file_indent() {
+        assert_eq!(
+            patch(SETTINGS, "permissions.deny", json!(["x"])),
+            "{\n    \"model\": \"opus\",\n    \"permissions\": {\n        \"allow\": [\"a\"],\n        \"deny\": [\n            \"x\"\n        ]\n    }\n}\n"
+        );
+        assert_eq!(
+            patch(SETTINGS, "env.FOO", json!("1")),
+            "{\n    \"model\": \"opus\",\n    \"permissions\": {\n        \"allow\": [\"a\"]\n    },\n    \"env\": {\n        \"FOO\": \"1\"\n    }\n}\n"
+        );
+        assert_eq!(
+            patch("", "a.b", json!(1)),
+            "{\n  \"a\": {\n    \"b\": 1\n  }\n}\n"
+        );
+        assert_eq!(patch("{\"a\": 1}", "b", json!(2)), "{\"a\": 1, \"b\": 2}");
+    }
+
+    #[test]
+    fn reports_key_path_conflicts() {
+        for path in ["model.name", "permissions.allow.3", "permissions.allow.x"] {
+            let err = set(SETTINGS, &keys(path), &json!(1)).unwrap_err();
+            assert_eq!(err.code, ErrorCode::ConfigKeyPathConflict, "{}", path);
+        }
+        let err = set(SETTINGS, &keys("model.name"), &json!(1)).unwrap_err();
+        assert_eq!(err.params["key"], "model.name");
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let err = set("{\n  \"a\": 1,\n}\n", &keys("a"), &json!(2)).unwrap_err();
+        assert_eq!(err.code, ErrorCode::ConfigInvalidSyntax);
+        assert_eq!(err.params["line"], "3");
+        assert!(parse("// comment\n{}").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! JSON、TOML、YAML 配置文件的校验与按键路径读写
+//!
+//! 键路径的每一级是对象的键，或数组的下标（十进制数字）。写入时尽量只改动目标值所在的文本，
+//! 保留其余内容的注释、缩进与键顺序；无法局部修改时才整体重新序列化，并在结果中说明。
+
+mod json;
+mod toml;
+mod yaml;
+
+use crate::client_catalog::ConfigFormat;
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use serde::Serialize;
+use serde_json::Value;
+
+/// 配置内容的语法错误，行列号从 1 开始
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigSyntaxError {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigSyntaxError {
+    fn at_offset(content: &str, offset: usize, message: impl Into<String>) -> Self {
+        let before = &content[..offset.min(content.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .map_or(1, |tail| tail.chars().count() + 1);
+        Self {
+            line: Some(line),
+            column: Some(column),
+            message: message.into(),
+        }
+    }
+
+    /// 转换为 `config.invalid_syntax` 错误
+    pub fn into_error(self, format: ConfigFormat) -> VaultError {
+        let mut error =
+            VaultError::new(ErrorCode::ConfigInvalidSyntax).with_param("format", format.as_str());
+        if let Some(line) = self.line {
+            error = error.with_param("line", line);
+        }
+        if let Some(column) = self.column {
+            error = error.with_param("column", column);
+        }
+        error.with_cause(crate::error::ErrorCause::other(self.message))
+    }
+}
+
+/// 写入键值后的新内容
+#[derive(Debug, Clone)]
+pub struct PatchedContent {
+    pub content: String,
+    /// 为 `false` 时文件被整体重新序列化，原有注释与排版可能丢失
+    pub formatting_preserved: bool,
+}
+
+/// 校验内容语法；非结构化格式总是通过
+pub fn validate(format: ConfigFormat, content: &str) -> Result<(), ConfigSyntaxError> {
+    match format {
+        ConfigFormat::Json => json::parse(content).map(|_| ()),
+        ConfigFormat::Toml => toml::parse(content).map(|_| ()),
+        ConfigFormat::Yaml => yaml::parse(content).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+/// 读取键路径上的值，键路径为空时返回整个文档；路径不存在时返回 `None`
+pub fn get_value(
+    format: ConfigFormat,
+    content: &str,
+    key_path: &[String],
+) -> VaultResult<Option<Value>> {
+    let syntax = |err: ConfigSyntaxError| err.into_error(format);
+    let document = match format {
+        ConfigFormat::Json => json::parse(content).map_err(syntax)?,
+        ConfigFormat::Toml => toml::to_json(toml::parse(content).map_err(syntax)?.as_item()),
+        ConfigFormat::Yaml => yaml::to_json(&yaml::parse(content).map_err(syntax)?)?,
+        _ => return Err(not_structured(format)),
+    };
+    Ok(lookup(document, key_path))
+}
+
+/// 把键路径上的值设为 `value`，缺少的中间对象会自动创建
+pub fn set_value(
+    format: ConfigFormat,
+    content: &str,
+    key_path: &[String],
+    value: &Value,
+) -> VaultResult<PatchedContent> {
+    if key_path.is_empty() {
+        return Err(ErrorCode::ConfigKeyPathEmpty.into());
+    }
+    let patched = match format {
+        ConfigFormat::Json => json::set(content, key_path, value)?,
+        ConfigFormat::Toml => toml::set(content, key_path, value)?,
+        ConfigFormat::Yaml => yaml::set(content, key_path, value)?,
+        _ => return Err(not_structured(format)),
+    };
+    // 写入前再次校验，确保任何情况下都不会产生语法错误的文件
+    validate(format, &patched.content).map_err(|err| err.into_error(format))?;
+    Ok(patched)
+}
+
+fn lookup(document: Value, key_path: &[String]) -> Option<Value> {
+    let mut current = document;
+    for key in key_path {
+        current = match current {
+            Value::Object(mut map) => map.remove(key)?,
+            Value::Array(mut items) => {
+                let index = parse_index(key)?;
+                if index >= items.len() {
+                    return None;
+                }
+                items.swap_remove(index)
+            }
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn parse_index(key: &str) -> Option<usize> {
+    if key.is_empty() || !key.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    key.parse().ok()
+}
+
+fn not_structured(format: ConfigFormat) -> VaultError {
+    VaultError::new(ErrorCode::ConfigNotStructured).with_param("format", format.as_str())
+}
+
+/// 键路径在第 `depth` 级遇到了既不是对象也不是数组的值
+fn path_conflict(key_path: &[String], depth: usize) -> VaultError {
+    VaultError::new(ErrorCode::ConfigKeyPathConflict)
+        .with_param("key", key_path[..=depth.min(key_path.len() - 1)].join("."))
+}
+
+/// 把剩余的键路径包装成嵌套对象，用于创建缺少的中间层级
+fn nest(keys: &[String], value: &Value) -> Value {
+    keys.iter().rev().fold(value.clone(), |inner, key| {
+        let mut map = serde_json::Map::new();
+        map.insert(key.clone(), inner);
+        Value::Object(map)
+    })
+}
@@ -0,0 +1,242 @@
+//! TOML：基于 toml_edit 的文档模型，修改值时保留注释、空行与键顺序
+
+use super::{parse_index, path_conflict, ConfigSyntaxError, PatchedContent};
+use crate::client_catalog::ConfigFormat;
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use serde_json::Value as JsonValue;
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, Value};
+
+pub(super) fn parse(content: &str) -> Result<DocumentMut, ConfigSyntaxError> {
+    content.parse::<DocumentMut>().map_err(|err| {
+        let message = err.message().to_string();
+        match err.span() {
+            Some(span) => ConfigSyntaxError::at_offset(content, span.start, message),
+            None => ConfigSyntaxError {
+                line: None,
+                column: None,
+                message,
+            },
+        }
+    })
+}
+
+pub(super) fn set(
+    content: &str,
+    key_path: &[String],
+    value: &JsonValue,
+) -> VaultResult<PatchedContent> {
+    let mut document = parse(content).map_err(|err| err.into_error(ConfigFormat::Toml))?;
+    let value = from_json(value)?;
+    set_item(document.as_item_mut(), key_path, 0, value)?;
+    Ok(PatchedContent {
+        content: document.to_string(),
+        formatting_preserved: true,
+    })
+}
+
+fn set_item(item: &mut Item, key_path: &[String], depth: usize, value: Value) -> VaultResult<()> {
+    let Some(key) = key_path.get(depth) else {
+        // 沿用原值前后的空白与行尾注释
+        let mut value = value;
+        if let Item::Value(previous) = item {
+            *value.decor_mut() = previous.decor().clone();
+        }
+        *item = Item::Value(value);
+        return Ok(());
+    };
+    let is_last = depth + 1 == key_path.len();
+    let inline = item.is_inline_table();
+
+    if let Some(table) = item.as_table_like_mut() {
+        if table.get_mut(key).is_none() {
+            if is_last {
+                table.insert(key, Item::Value(value));
+                return Ok(());
+            }
+            // 标准表下新建隐式子表，内联表中只能继续使用内联表
+            let child = if inline {
+                Item::Value(Value::InlineTable(InlineTable::new()))
+            } else {
+                let mut child = Table::new();
+                child.set_implicit(true);
+                Item::Table(child)
+            };
+            table.insert(key, child);
+        }
+        let child = table
+            .get_mut(key)
+            .ok_or_else(|| path_conflict(key_path, depth))?;
+        return set_item(child, key_path, depth + 1, value);
+    }
+
+    let index = parse_index(key).ok_or_else(|| path_conflict(key_path, depth))?;
+    if let Some(array) = item.as_array_mut() {
+        let mut element = array
+            .get(index)
+            .cloned()
+            .map(Item::Value)
+            .ok_or_else(|| path_conflict(key_path, depth))?;
+        set_item(&mut element, key_path, depth + 1, value)?;
+        let element = element
+            .into_value()
+            .map_err(|_| path_conflict(key_path, depth))?;
+        array.replace_formatted(index, element);
+        return Ok(());
+    }
+    if let Some(tables) = item.as_array_of_tables_mut() {
+        let table = tables
+            .get_mut(index)
+            .ok_or_else(|| path_conflict(key_path, depth))?;
+        let mut element = Item::Table(std::mem::take(table));
+        let result = set_item(&mut element, key_path, depth + 1, value);
+        *table = element.into_table().unwrap_or_default();
+        return result;
+    }
+    Err(path_conflict(key_path, depth))
+}
+
+fn from_json(value: &JsonValue) -> VaultResult<Value> {
+    Ok(match value {
+        JsonValue::Null => {
+            return Err(
+                VaultError::new(ErrorCode::ConfigValueUnsupported).with_param("format", "toml")
+            )
+        }
+        JsonValue::Bool(flag) => Value::from(*flag),
+        JsonValue::Number(number) => match number.as_i64() {
+            Some(integer) => Value::from(integer),
+            None => Value::from(number.as_f64().unwrap_or_default()),
+        },
+        JsonValue::String(text) => Value::from(text.as_str()),
+        JsonValue::Array(items) => {
+            let mut array = Array::new();
+            for item in items {
+                array.push(from_json(item)?);
+            }
+            Value::Array(array)
+        }
+        JsonValue::Object(map) => {
+            let mut table = InlineTable::new();
+            for (key, item) in map {
+                table.insert(key, from_json(item)?);
+            }
+            Value::InlineTable(table)
+        }
+    })
+}
+
+pub(super) fn to_json(item: &Item) -> JsonValue {
+    match item {
+        Item::None => JsonValue::Null,
+        Item::Value(value) => value_to_json(value),
+        Item::Table(table) => JsonValue::Object(
+            table
+                .iter()
+                .map(|(key, item)| (key.to_string(), to_json(item)))
+                .collect(),
+        ),
+        Item::ArrayOfTables(tables) => JsonValue::Array(
+            tables
+                .iter()
+                .map(|table| {
+                    JsonValue::Object(
+                        table
+                            .iter()
+                            .map(|(key, item)| (key.to_string(), to_json(item)))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::String(text) => JsonValue::String(text.value().clone()),
+        Value::Integer(integer) => JsonValue::from(*integer.value()),
+        Value::Float(float) => serde_json::Number::from_f64(*float.value()).map_or_else(
+            || JsonValue::String(float.value().to_string()),
+            JsonValue::Number,
+        ),
+        Value::Boolean(flag) => JsonValue::Bool(*flag.value()),
+        Value::Datetime(datetime) => JsonValue::String(datetime.value().to_string()),
+        Value::Array(array) => JsonValue::Array(array.iter().map(value_to_json).collect()),
+        Value::InlineTable(table) => JsonValue::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn keys(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    fn patch(content: &str, path: &str, value: JsonValue) -> String {
+        set(content, &keys(path), &value).unwrap().content
+    }
+
+    const CONFIG: &str = "# top\nmodel = \"o3\" # pick\n\n[mcp_servers.foo]\ncommand = \"x\"\nargs = [\"a\", \"b\"]\n\n[[profiles]]\nname = \"p\"\n";
+
+    #[test]
+    fn keeps_comments_and_layout() {
+        assert_eq!(
+            patch(CONFIG, "model", json!("gpt-5")),
+            CONFIG.replace("\"o3\"", "\"gpt-5\"")
+        );
+        assert_eq!(
+            patch(CONFIG, "mcp_servers.foo.args.1", json!("z")),
+            CONFIG.replace("\"b\"", "\"z\"")
+        );
+        assert_eq!(
+            patch(CONFIG, "profiles.0.name", json!("q")),
+            CONFIG.replace("\"p\"", "\"q\"")
+        );
+    }
+
+    #[test]
+    fn creates_missing_tables() {
+        assert_eq!(
+            patch(CONFIG, "mcp_servers.bar.command", json!("y")),
+            CONFIG.replace(
+                "\n[[profiles]]",
+                "\n[mcp_servers.bar]\ncommand = \"y\"\n\n[[profiles]]"
+            )
+        );
+        assert_eq!(patch("", "a.b.c", json!(1)), "[a.b]\nc = 1\n");
+        // 内联表中只能继续创建内联表
+        let inline = patch("point = { x = 1 }\n", "point.y.z", json!(2));
+        assert!(!inline.contains('['));
+        assert_eq!(
+            to_json(parse(&inline).unwrap().as_item()),
+            json!({ "point": { "x": 1, "y": { "z": 2 } } })
+        );
+    }
+
+    #[test]
+    fn reports_key_path_conflicts() {
+        for path in ["model.name", "mcp_servers.foo.args.5", "profiles.1.name"] {
+            let err = set(CONFIG, &keys(path), &json!(1)).unwrap_err();
+            assert_eq!(err.code, ErrorCode::ConfigKeyPathConflict, "{}", path);
+        }
+        let err = set(CONFIG, &keys("model.name"), &json!(1)).unwrap_err();
+        assert_eq!(err.params["key"], "model.name");
+    }
+
+    #[test]
+    fn rejects_invalid_input_and_values() {
+        let err = set("a = \nb = 1\n", &keys("b"), &json!(2)).unwrap_err();
+        assert_eq!(err.code, ErrorCode::ConfigInvalidSyntax);
+        assert_eq!(err.params["line"], "1");
+        let err = set(CONFIG, &keys("model"), &JsonValue::Null).unwrap_err();
+        assert_eq!(err.code, ErrorCode::ConfigValueUnsupported);
+    }
+}
@@ -0,0 +1,344 @@
+//! YAML：serde_yaml 不保留注释，因此修改块映射中的单行标量或追加新键时直接编辑原文，
+//! 再重新解析确认结果与预期一致；其余情况退回整体重新序列化
+
+use super::{parse_index, path_conflict, ConfigSyntaxError, PatchedContent};
+use crate::client_catalog::ConfigFormat;
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use serde_json::Value as JsonValue;
+use serde_yaml::{Mapping, Value};
+
+pub(super) fn parse(content: &str) -> Result<Value, ConfigSyntaxError> {
+    serde_yaml::from_str(content).map_err(|err| {
+        let location = err.location();
+        ConfigSyntaxError {
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+            message: err.to_string(),
+        }
+    })
+}
+
+pub(super) fn to_json(document: &Value) -> VaultResult<JsonValue> {
+    serde_json::to_value(document).map_err(|err| {
+        VaultError::new(ErrorCode::ConfigValueUnsupported)
+            .with_param("format", "yaml")
+            .with_cause(err)
+    })
+}
+
+pub(super) fn set(
+    content: &str,
+    key_path: &[String],
+    value: &JsonValue,
+) -> VaultResult<PatchedContent> {
+    let mut expected = parse(content).map_err(|err| err.into_error(ConfigFormat::Yaml))?;
+    let value = serde_yaml::to_value(value).map_err(|err| {
+        VaultError::new(ErrorCode::ConfigValueUnsupported)
+            .with_param("format", "yaml")
+            .with_cause(crate::error::ErrorCause::other(err))
+    })?;
+    set_in_value(&mut expected, key_path, 0, value.clone())?;
+
+    if let Some(patched) = patch_in_place(content, key_path, &value) {
+        if parse(&patched).is_ok_and(|document| document == expected) {
+            return Ok(PatchedContent {
+                content: patched,
+                formatting_preserved: true,
+            });
+        }
+    }
+    let content = serde_yaml::to_string(&expected).map_err(|err| {
+        VaultError::new(ErrorCode::SerializeFailed)
+            .with_param("target", "yaml")
+            .with_cause(crate::error::ErrorCause::other(err))
+    })?;
+    Ok(PatchedContent {
+        content,
+        formatting_preserved: false,
+    })
+}
+
+fn set_in_value(
+    target: &mut Value,
+    key_path: &[String],
+    depth: usize,
+    value: Value,
+) -> VaultResult<()> {
+    let Some(key) = key_path.get(depth) else {
+        *target = value;
+        return Ok(());
+    };
+    // 空文档与缺少的中间层级都视为空映射
+    if target.is_null() {
+        *target = Value::Mapping(Mapping::new());
+    }
+    let child = match target {
+        Value::Mapping(map) => map.entry(Value::String(key.clone())).or_insert(Value::Null),
+        Value::Sequence(items) => parse_index(key)
+            .and_then(|index| items.get_mut(index))
+            .ok_or_else(|| path_conflict(key_path, depth))?,
+        _ => return Err(path_conflict(key_path, depth)),
+    };
+    set_in_value(child, key_path, depth + 1, value)
+}
+
+/// 沿块映射的缩进逐级定位键，只替换同一行内的标量值或在映射末尾追加新键，保留注释
+fn patch_in_place(content: &str, key_path: &[String], value: &Value) -> Option<String> {
+    if matches!(
+        value,
+        Value::Mapping(_) | Value::Sequence(_) | Value::Tagged(_)
+    ) {
+        return None;
+    }
+    let rendered = serde_yaml::to_string(value).ok()?;
+    let rendered = rendered.trim_end();
+    if rendered.contains('\n') {
+        return None;
+    }
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let (mut from, mut to) = (0, lines.len());
+    let mut parent_indent: Option<usize> = None;
+    for (depth, key) in key_path.iter().enumerate() {
+        let is_last = depth + 1 == key_path.len();
+        let first_line = (from..to).find(|&index| is_content_line(lines[index]));
+        let Some(child_indent) = first_line.map(|index| indent_of(lines[index])) else {
+            // 空文档直接追加顶层键
+            if is_last && parent_indent.is_none() {
+                let line = format!("{}: {}\n", render_key(key)?, rendered);
+                let separator = if content.is_empty() || content.ends_with('\n') {
+                    ""
+                } else {
+                    "\n"
+                };
+                return Some(format!("{}{}{}", content, separator, line));
+            }
+            return None;
+        };
+        if parent_indent.is_some_and(|parent| child_indent <= parent) {
+            return None;
+        }
+        let found = (from..to).find_map(|index| {
+            let line = lines[index];
+            if !is_content_line(line) || indent_of(line) != child_indent {
+                return None;
+            }
+            match_key(&line[child_indent..], key).map(|offset| (index, child_indent + offset))
+        });
+        let Some((line_index, value_offset)) = found else {
+            if !is_last || lines[first_line?][child_indent..].starts_with('-') {
+                return None;
+            }
+            // 在映射最后一个内容行之后追加新键，沿用同级缩进
+            let last = (from..to)
+                .rev()
+                .find(|&index| is_content_line(lines[index]))?;
+            let offset: usize = lines[..=last].iter().map(|line| line.len()).sum();
+            let separator = if lines[last].ends_with('\n') {
+                ""
+            } else {
+                "\n"
+            };
+            let line = format!(
+                "{}{}{}: {}\n",
+                separator,
+                " ".repeat(child_indent),
+                render_key(key)?,
+                rendered
+            );
+            return Some(format!(
+                "{}{}{}",
+                &content[..offset],
+                line,
+                &content[offset..]
+            ));
+        };
+
+        let line = lines[line_index];
+        let rest = &line[value_offset..];
+        if is_last {
+            let (start, end) = scalar_span(rest)?;
+            let line_start: usize = lines[..line_index].iter().map(|line| line.len()).sum();
+            let start = line_start + value_offset + start;
+            let end = line_start + value_offset + end;
+            return Some(format!(
+                "{}{}{}",
+                &content[..start],
+                rendered,
+                &content[end..]
+            ));
+        }
+        // 中间层级必须是在下一行开始的块映射
+        let trimmed = rest.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            return None;
+        }
+        from = line_index + 1;
+        to = (from..to)
+            .find(|&index| is_content_line(lines[index]) && indent_of(lines[index]) <= child_indent)
+            .unwrap_or(to);
+        parent_indent = Some(child_indent);
+    }
+    None
+}
+
+fn render_key(key: &str) -> Option<String> {
+    let rendered = serde_yaml::to_string(&Value::String(key.to_string())).ok()?;
+    let rendered = rendered.trim_end();
+    (!rendered.contains('\n')).then(|| rendered.to_string())
+}
+
+fn is_content_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty()
+        && !trimmed.starts_with('#')
+        && !trimmed.starts_with("---")
+        && !trimmed.starts_with("...")
+        && !trimmed.starts_with('%')
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// 行首为 `key:` 时返回冒号之后的偏移
+fn match_key(line: &str, key: &str) -> Option<usize> {
+    let quoted_double = format!("\"{}\"", key);
+    let quoted_single = format!("'{}'", key);
+    [key, quoted_double.as_str(), quoted_single.as_str()]
+        .iter()
+        .find_map(|candidate| {
+            let rest = line.strip_prefix(candidate)?.strip_prefix(':')?;
+            if rest.is_empty() || rest.starts_with([' ', '\t', '\r', '\n']) {
+                Some(candidate.len() + 1)
+            } else {
+                None
+            }
+        })
+}
+
+/// 冒号之后单行标量的范围，不含前导空白与行尾注释
+fn scalar_span(rest: &str) -> Option<(usize, usize)> {
+    let start = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    let body = &rest[start..];
+    let first = body.chars().next()?;
+    if matches!(
+        first,
+        '\r' | '\n' | '#' | '|' | '>' | '{' | '[' | '&' | '*' | '!'
+    ) {
+        return None;
+    }
+    let length = match first {
+        '"' => {
+            let mut escaped = false;
+            let close = body[1..].char_indices().find_map(|(index, c)| {
+                if escaped {
+                    escaped = false;
+                    None
+                } else if c == '\\' {
+                    escaped = true;
+                    None
+                } else if c == '"' {
+                    Some(index)
+                } else {
+                    None
+                }
+            })?;
+            close + 2
+        }
+        '\'' => {
+            let bytes = body.as_bytes();
+            let mut index = 1;
+            loop {
+                match bytes.get(index)? {
+                    b'\'' if bytes.get(index + 1) == Some(&b'\'') => index += 2,
+                    b'\'' => break index + 1,
+                    b'\n' => return None,
+                    _ => index += 1,
+                }
+            }
+        }
+        _ => {
+            let line_end = body.find(['\r', '\n']).unwrap_or(body.len());
+            let line = &body[..line_end];
+            let comment = line.find(" #").unwrap_or(line.len());
+            line[..comment].trim_end().len()
+        }
+    };
+    Some((start, start + length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn keys(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    fn patch(content: &str, path: &str, value: JsonValue) -> PatchedContent {
+        set(content, &keys(path), &value).unwrap()
+    }
+
+    const CONFIG: &str = "# comment\nname: test # keep\nnested:\n  deep:\n    flag: true\n  other: 'x'\n  # trailing\nlist:\n  - a\n";
+
+    #[test]
+    fn replaces_scalars_in_place() {
+        let patched = patch(CONFIG, "nested.deep.flag", json!(false));
+        assert!(patched.formatting_preserved);
+        assert_eq!(patched.content, CONFIG.replace("true", "false"));
+
+        let patched = patch(CONFIG, "name", json!("a b: c"));
+        assert!(patched.formatting_preserved);
+        assert_eq!(
+            patched.content,
+            CONFIG.replace("name: test", "name: 'a b: c'")
+        );
+
+        // 数字形式的字符串需要加引号
+        let patched = patch(CONFIG, "nested.other", json!("123"));
+        assert_eq!(patched.content, CONFIG.replace("'x'", "'123'"));
+    }
+
+    #[test]
+    fn appends_missing_keys_after_last_sibling() {
+        let patched = patch(CONFIG, "nested.added", json!(1));
+        assert!(patched.formatting_preserved);
+        assert_eq!(
+            patched.content,
+            CONFIG.replace("  other: 'x'\n", "  other: 'x'\n  added: 1\n")
+        );
+        assert_eq!(patch("", "a", json!(1)).content, "a: 1\n");
+        assert_eq!(
+            patch("# only comment", "a", json!(1)).content,
+            "# only comment\na: 1\n"
+        );
+    }
+
+    #[test]
+    fn creates_missing_mappings_by_reserializing() {
+        let patched = patch(CONFIG, "extra.inner.value", json!("v"));
+        assert!(!patched.formatting_preserved);
+        let document = to_json(&parse(&patched.content).unwrap()).unwrap();
+        assert_eq!(document["extra"], json!({ "inner": { "value": "v" } }));
+        assert_eq!(document["nested"]["deep"]["flag"], json!(true));
+    }
+
+    #[test]
+    fn reports_key_path_conflicts() {
+        for path in ["name.first", "list.3", "list.x"] {
+            let err = set(CONFIG, &keys(path), &json!(1)).unwrap_err();
+            assert_eq!(err.code, ErrorCode::ConfigKeyPathConflict, "{}", path);
+        }
+        let err = set(CONFIG, &keys("name.first"), &json!(1)).unwrap_err();
+        assert_eq!(err.params["key"], "name.first");
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let err = set("a: [1,\nb: 2\n", &keys("b"), &json!(3)).unwrap_err();
+        assert_eq!(err.code, ErrorCode::ConfigInvalidSyntax);
+        assert!(err.params.contains_key("line"));
+    }
+}